@@ -17,35 +17,29 @@ use std::task::{Context, Poll};
 #[cfg(feature = "async")]
 use std::convert::TryInto;
 #[cfg(feature = "async")]
-use std::sync::mpsc::{self, Receiver};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 #[cfg(feature = "async")]
 use std::thread;
 
 #[cfg(feature = "async")]
 struct CanSocketStream {
-    receiver: Receiver<Result<socketcan::CanFrame, std::io::Error>>,
+    receiver: UnboundedReceiver<Result<socketcan::CanFrame, std::io::Error>>,
 }
 
 #[cfg(feature = "async")]
 impl Stream for CanSocketStream {
     type Item = Result<socketcan::CanFrame, std::io::Error>;
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match self.receiver.try_recv() {
-            Ok(frame) => Poll::Ready(Some(frame)),
-            Err(mpsc::TryRecvError::Empty) => {
-                cx.waker().wake_by_ref();
-                Poll::Pending
-            }
-            Err(mpsc::TryRecvError::Disconnected) => Poll::Ready(None),
-        }
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Woken by the reader thread instead of polling in a loop.
+        self.receiver.poll_recv(cx)
     }
 }
 
 #[cfg(feature = "async")]
 impl CanSocketStream {
     fn new(socket: socketcan::CanSocket) -> Self {
-        let (tx, rx) = mpsc::channel();
+        let (tx, rx) = mpsc::unbounded_channel();
 
         thread::spawn(move || {
             loop {
@@ -0,0 +1,96 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio_stream::Stream;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::primitives::can_frame::{CanFrame, CanFrameError};
+
+/// CAN bus driven by an async runtime.
+///
+/// Unlike the polled `Bus`, received frames are delivered as a stream, and
+/// sending is a future that completes once the frame was handed to the bus.
+pub trait AsyncBus: Send + Sync + 'static {
+    /// Stream of received frames.
+    type Frames: Stream<Item = CanFrame> + Send + Unpin + 'static;
+
+    /// Sends a frame on the bus.
+    fn send(&self, can_frame: CanFrame) -> impl Future<Output = Result<(), CanFrameError>> + Send;
+
+    /// Subscribes to the frames received from now on.
+    ///
+    /// Each subscription receives every frame; frames sent through this
+    /// handle are not received back, as on a real CAN controller.
+    fn frames(&self) -> Self::Frames;
+}
+
+struct Subscriber {
+    handle: usize,
+    sender: UnboundedSender<CanFrame>,
+}
+
+#[derive(Default)]
+struct Medium {
+    subscribers: Vec<Subscriber>,
+    next_handle: usize,
+}
+
+/// In-memory CAN bus shared by nodes in one process.
+///
+/// Each clone is a node's connection to the same bus, like separate CAN
+/// controllers on one cable. Useful for simulations and tests without a
+/// CAN interface.
+pub struct VirtualBus {
+    medium: Arc<Mutex<Medium>>,
+    handle: usize,
+}
+
+impl VirtualBus {
+    pub fn new() -> Self {
+        VirtualBus {
+            medium: Arc::new(Mutex::new(Medium::default())),
+            handle: 0,
+        }
+    }
+}
+
+impl Default for VirtualBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for VirtualBus {
+    fn clone(&self) -> Self {
+        let mut medium = self.medium.lock().unwrap();
+        medium.next_handle += 1;
+        VirtualBus {
+            medium: self.medium.clone(),
+            handle: medium.next_handle,
+        }
+    }
+}
+
+impl AsyncBus for VirtualBus {
+    type Frames = UnboundedReceiverStream<CanFrame>;
+
+    async fn send(&self, can_frame: CanFrame) -> Result<(), CanFrameError> {
+        let mut medium = self.medium.lock().unwrap();
+        // Subscribers whose stream was dropped are removed.
+        medium.subscribers.retain(|subscriber| {
+            subscriber.handle == self.handle || subscriber.sender.send(can_frame.clone()).is_ok()
+        });
+        Ok(())
+    }
+
+    fn frames(&self) -> Self::Frames {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.medium.lock().unwrap().subscribers.push(Subscriber {
+            handle: self.handle,
+            sender,
+        });
+        UnboundedReceiverStream::new(receiver)
+    }
+}
@@ -0,0 +1,4 @@
+pub mod bus;
+pub mod network;
+pub mod node;
+pub mod registry;
@@ -0,0 +1,123 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+
+use tokio_stream::Stream;
+
+use super::bus::AsyncBus;
+use crate::primitives::can_frame::{CanFrame, CanFrameError};
+pub use crate::primitives::network::BusSelection;
+
+/// Bus selection shared between the network and its frame streams.
+#[derive(Clone, Default)]
+struct Selection {
+    bus_b: Arc<AtomicBool>,
+}
+
+impl Selection {
+    fn get(&self) -> BusSelection {
+        if self.bus_b.load(Ordering::Acquire) {
+            BusSelection::B
+        } else {
+            BusSelection::A
+        }
+    }
+
+    fn set(&self, bus: BusSelection) {
+        self.bus_b.store(bus == BusSelection::B, Ordering::Release);
+    }
+}
+
+/// Async network of two redundant buses, of which one is selected at a time.
+///
+/// Frames are sent on the selected bus only, and frame streams only yield
+/// frames received on the selected bus. Both buses are listened to, so a
+/// switch takes effect for existing streams without losing the
+/// subscription.
+pub struct AsyncNetwork<B: AsyncBus> {
+    bus_a: B,
+    bus_b: B,
+    selection: Selection,
+}
+
+impl<B: AsyncBus> AsyncNetwork<B> {
+    /// Creates a network with bus A selected.
+    pub fn new(bus_a: B, bus_b: B) -> Self {
+        AsyncNetwork {
+            bus_a,
+            bus_b,
+            selection: Selection::default(),
+        }
+    }
+
+    /// Returns one of the buses.
+    pub fn bus(&self, bus: BusSelection) -> &B {
+        match bus {
+            BusSelection::A => &self.bus_a,
+            BusSelection::B => &self.bus_b,
+        }
+    }
+
+    /// Returns the selected bus.
+    pub fn selected_bus(&self) -> BusSelection {
+        self.selection.get()
+    }
+
+    /// Selects a bus for sending and receiving.
+    pub fn select_bus(&self, bus: BusSelection) {
+        self.selection.set(bus);
+    }
+
+    /// Switches to the other bus and returns it.
+    pub fn switch_bus(&self) -> BusSelection {
+        let bus = self.selected_bus().other();
+        self.select_bus(bus);
+        bus
+    }
+
+    /// Sends a frame on the selected bus.
+    pub async fn send(&self, can_frame: CanFrame) -> Result<(), CanFrameError> {
+        self.bus(self.selected_bus()).send(can_frame).await
+    }
+
+    /// Subscribes to the frames received on the selected bus from now on.
+    pub fn frames(&self) -> NetworkFrames<B> {
+        NetworkFrames {
+            frames_a: self.bus_a.frames(),
+            frames_b: self.bus_b.frames(),
+            selection: self.selection.clone(),
+        }
+    }
+}
+
+/// Stream of the frames received on the selected bus of a network.
+///
+/// Frames arriving on the other bus are discarded. The stream ends when
+/// the selected bus's stream ends.
+pub struct NetworkFrames<B: AsyncBus> {
+    frames_a: B::Frames,
+    frames_b: B::Frames,
+    selection: Selection,
+}
+
+impl<B: AsyncBus> Stream for NetworkFrames<B> {
+    type Item = CanFrame;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<CanFrame>> {
+        let this = &mut *self;
+        loop {
+            let selected = this.selection.get();
+            let (selected_frames, other_frames) = match selected {
+                BusSelection::A => (&mut this.frames_a, &mut this.frames_b),
+                BusSelection::B => (&mut this.frames_b, &mut this.frames_a),
+            };
+            // The other bus is drained first so its frames do not pile up.
+            let discarded = matches!(Pin::new(other_frames).poll_next(cx), Poll::Ready(Some(_)));
+            match Pin::new(selected_frames).poll_next(cx) {
+                Poll::Pending if discarded => continue,
+                poll => return poll,
+            }
+        }
+    }
+}
@@ -0,0 +1,553 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::vec::Vec;
+
+use tokio::sync::mpsc;
+use tokio::sync::{Notify, watch};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant, MissedTickBehavior};
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use super::bus::AsyncBus;
+use super::network::{AsyncNetwork, BusSelection};
+use super::registry::{NodeEvent, NodeEventHandler, NodeInfo, NodeRegistry};
+use crate::primitives::can_frame::{CanFrame, CanFrameError};
+use crate::primitives::packet::{Packet, PacketAssembler};
+use crate::primitives::roster::{NodeRoster, SwitchoverStatus};
+use crate::primitives::scheduler::{self, Clock, SharedClock, StdClock};
+use crate::primitives::sync::{SyncConsumer, SyncCounter, SyncScheduler};
+use crate::primitives::time::{self as time_frames, CdsTime, CucTime, TimeFrame};
+use crate::services::ST17_test::Reachability;
+
+const ID_SYNC: u32 = 0x080;
+const ID_HEARTBEAT: u32 = 0x700;
+const ID_TC: u32 = 0x280;
+const ID_TM: u32 = 0x300;
+const NODE_MASK: u32 = 0x07F;
+/// How often the controller checks whether nodes went silent.
+const LIVENESS_CHECK_PERIOD: Duration = Duration::from_millis(100);
+
+/// Background tasks of a node, stopped together on shutdown.
+struct Tasks {
+    shutdown: watch::Sender<bool>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Tasks {
+    fn new() -> Self {
+        Tasks {
+            shutdown: watch::Sender::new(false),
+            handles: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Spawns a task that is cancelled at its next await point on shutdown.
+    fn spawn<F: Future<Output = ()> + Send + 'static>(&self, future: F) {
+        let mut shutdown = self.shutdown.subscribe();
+        let handle = tokio::spawn(async move {
+            tokio::select! {
+                _ = future => {}
+                _ = shutdown.wait_for(|stop| *stop) => {}
+            }
+        });
+        let mut handles = self.handles.lock().unwrap();
+        handles.retain(|handle| !handle.is_finished());
+        handles.push(handle);
+    }
+
+    /// Spawns a task running every `period`, first one period from now.
+    ///
+    /// Runs that would be late by more than a period are skipped.
+    fn spawn_periodic<F, Fut>(&self, period: Duration, mut task: F)
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        self.spawn(async move {
+            let mut interval = time::interval_at(Instant::now() + period, period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                task().await;
+            }
+        });
+    }
+
+    /// Stops all tasks and waits until they have ended.
+    async fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+        let handles: Vec<_> = self.handles.lock().unwrap().drain(..).collect();
+        for handle in handles {
+            let _ = handle.await;
+        }
+    }
+}
+
+/// Builds a sync frame, carrying the next counter value if a counter is given.
+fn sync_frame(counter: Option<&SyncCounter>) -> Result<CanFrame, CanFrameError> {
+    match counter {
+        Some(counter) => counter.frame(),
+        None => CanFrame::new(ID_SYNC, None),
+    }
+}
+
+/// Splits a packet into frames with the given CAN ID and sends them in order.
+async fn send_packet<B: AsyncBus>(network: &AsyncNetwork<B>, can_id: u32, data: Vec<u8>) -> Result<(), CanFrameError> {
+    for frame_data in Packet::new(Some(data)).split() {
+        network.send(CanFrame::new(can_id, Some(frame_data))?).await?;
+    }
+    Ok(())
+}
+
+/// Calls the node event handlers outside the registry lock.
+fn dispatch_node_event(handlers: &Mutex<Vec<NodeEventHandler>>, node_id: u32, event: NodeEvent) {
+    let handlers = handlers.lock().unwrap().clone();
+    for handler in handlers {
+        handler(node_id, event);
+    }
+}
+
+/// Controller node running on a tokio runtime.
+///
+/// Sends heartbeats, syncs and telecommands, and receives the telemetry
+/// packets of the responders. All background tasks end on `shutdown`.
+///
+/// Heartbeats and syncs are sent on the selected bus only, so switching
+/// the controller's bus commands the responders to follow (cold
+/// redundancy). The node roster tracks which bus each responder is on,
+/// and the node registry tracks their liveness.
+pub struct AsyncController<B: AsyncBus> {
+    network: Arc<AsyncNetwork<B>>,
+    tasks: Tasks,
+    clock: SharedClock,
+    roster: Arc<Mutex<NodeRoster>>,
+    roster_changed: Arc<Notify>,
+    registry: Arc<Mutex<NodeRegistry>>,
+    node_handlers: Arc<Mutex<Vec<NodeEventHandler>>>,
+    sync_counter: Option<SyncCounter>,
+}
+
+impl<B: AsyncBus> AsyncController<B> {
+    /// Creates a controller timed by the system clock.
+    pub fn new(network: AsyncNetwork<B>) -> Self {
+        Self::with_clock(network, Arc::new(StdClock::new()))
+    }
+
+    /// Creates a controller whose roster and registry are timed by the given clock.
+    pub fn with_clock(network: AsyncNetwork<B>, clock: SharedClock) -> Self {
+        AsyncController {
+            network: Arc::new(network),
+            tasks: Tasks::new(),
+            clock,
+            roster: Arc::new(Mutex::new(NodeRoster::new())),
+            roster_changed: Arc::new(Notify::new()),
+            registry: Arc::new(Mutex::new(NodeRegistry::default())),
+            node_handlers: Arc::new(Mutex::new(Vec::new())),
+            sync_counter: None,
+        }
+    }
+
+    pub fn network(&self) -> &Arc<AsyncNetwork<B>> {
+        &self.network
+    }
+
+    /// Enables the sync counter with the given overflow value, or disables it with `None`.
+    ///
+    /// Applies to periodic syncs started afterwards.
+    pub fn set_sync_counter(&mut self, overflow: Option<u8>) {
+        self.sync_counter = overflow.map(SyncCounter::new);
+    }
+
+    /// Returns the counter value of the last sent sync, if the counter is enabled and a sync was sent.
+    pub fn sync_counter(&self) -> Option<u8> {
+        self.sync_counter.as_ref()?.value()
+    }
+
+    /// Sends a heartbeat frame.
+    pub async fn send_heartbeat(&self) -> Result<(), CanFrameError> {
+        self.network.send(CanFrame::new(ID_HEARTBEAT, None)?).await
+    }
+
+    /// Sends a sync frame, with the sync counter if it is enabled.
+    pub async fn send_sync(&self) -> Result<(), CanFrameError> {
+        self.network.send(sync_frame(self.sync_counter.as_ref())?).await
+    }
+
+    /// Sends the spacecraft elapsed time as a SCET frame.
+    pub async fn send_scet(&self, time: &CucTime) -> Result<(), CanFrameError> {
+        self.network.send(time_frames::scet_frame(time)?).await
+    }
+
+    /// Sends the UTC time as a UTC frame.
+    pub async fn send_utc(&self, time: &CdsTime) -> Result<(), CanFrameError> {
+        self.network.send(time_frames::utc_frame(time)?).await
+    }
+
+    /// Starts sending heartbeats with the given period.
+    pub fn start_heartbeat(&self, period: Duration) {
+        self.start_periodic_frame(period, || CanFrame::new(ID_HEARTBEAT, None));
+    }
+
+    /// Starts sending syncs with the given period, with the sync counter if it is enabled.
+    pub fn start_sync(&self, period: Duration) {
+        let counter = self.sync_counter.clone();
+        self.start_periodic_frame(period, move || sync_frame(counter.as_ref()));
+    }
+
+    fn start_periodic_frame<F>(&self, period: Duration, make_frame: F)
+    where
+        F: Fn() -> Result<CanFrame, CanFrameError> + Send + 'static,
+    {
+        let network = self.network.clone();
+        self.tasks.spawn_periodic(period, move || {
+            let network = network.clone();
+            let can_frame = make_frame();
+            async move {
+                if let Ok(can_frame) = can_frame {
+                    let _ = network.send(can_frame).await;
+                }
+            }
+        });
+    }
+
+    /// Runs a task every `period` until shutdown, e.g. to request housekeeping reports.
+    pub fn every<F, Fut>(&self, period: Duration, task: F)
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        self.tasks.spawn_periodic(period, task);
+    }
+
+    /// Sends a telecommand packet to a responder.
+    ///
+    /// The registry counts the command as pending until a final verification
+    /// report for its service type and subtype arrives.
+    pub async fn send_packet(&self, node_id: u32, data: Vec<u8>) -> Result<(), CanFrameError> {
+        let request_id = match data[..] {
+            [service, subtype, ..] => Some((service, subtype)),
+            _ => None,
+        };
+        send_packet(&self.network, ID_TC + node_id, data).await?;
+        if let Some(request_id) = request_id {
+            self.registry.lock().unwrap().command_sent(node_id, request_id);
+        }
+        Ok(())
+    }
+
+    /// Returns a stream of the telemetry packets received from now on, with the sending node ID.
+    ///
+    /// The stream ends on shutdown.
+    pub fn packets(&self) -> UnboundedReceiverStream<(u32, Vec<u8>)> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut frames = self.network.frames();
+        self.tasks.spawn(async move {
+            let mut assembler = PacketAssembler::new();
+            while let Some(can_frame) = frames.next().await {
+                let can_id = can_frame.can_id();
+                if can_id & !NODE_MASK != ID_TM {
+                    continue;
+                }
+                if let Some(packet) = assembler.process_frame(can_frame)
+                    && sender.send((can_id & NODE_MASK, packet.data().clone())).is_err()
+                {
+                    break;
+                }
+            }
+        });
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    /// Sets the responders that must follow a bus switch even if they were never heard.
+    pub fn set_nodes(&self, nodes: Vec<u32>) {
+        self.roster.lock().unwrap().set_expected_nodes(nodes);
+    }
+
+    /// Replaces the node registry, e.g. with one loaded from the configuration.
+    ///
+    /// The registry's nodes are also expected to follow bus switches.
+    pub fn set_registry(&self, registry: NodeRegistry) {
+        let nodes = registry.nodes().iter().map(|node| node.node_id).collect();
+        self.set_nodes(nodes);
+        *self.registry.lock().unwrap() = registry;
+    }
+
+    /// Returns a snapshot of the nodes in the registry, ordered by node ID.
+    pub fn nodes(&self) -> Vec<NodeInfo> {
+        self.registry.lock().unwrap().nodes()
+    }
+
+    /// Registers a handler called when a node appears, goes silent or recovers.
+    pub fn add_node_handler(&self, handler: NodeEventHandler) {
+        self.node_handlers.lock().unwrap().push(handler);
+    }
+
+    /// Records the result of an ST17 connection test in the registry.
+    pub fn set_reachability(&self, node_id: u32, reachability: Reachability) {
+        self.registry.lock().unwrap().set_reachability(node_id, reachability);
+    }
+
+    /// Starts tracking the responders, listening on both buses.
+    ///
+    /// Updates the roster with the bus each responder transmits on and the
+    /// registry with its telemetry, and periodically checks for silent nodes.
+    pub fn start_roster(&self) {
+        let frames_a = self.network.bus(BusSelection::A).frames().map(|frame| (BusSelection::A, frame));
+        let frames_b = self.network.bus(BusSelection::B).frames().map(|frame| (BusSelection::B, frame));
+        let mut frames = frames_a.merge(frames_b);
+        let clock = self.clock.clone();
+        let roster = self.roster.clone();
+        let roster_changed = self.roster_changed.clone();
+        let registry = self.registry.clone();
+        let node_handlers = self.node_handlers.clone();
+        self.tasks.spawn(async move {
+            let mut assemblers = [PacketAssembler::new(), PacketAssembler::new()];
+            while let Some((bus, can_frame)) = frames.next().await {
+                let node_id = can_frame.can_id() & NODE_MASK;
+                if can_frame.can_id() & !NODE_MASK != ID_TM || node_id == 0 {
+                    continue;
+                }
+                let now = clock.now();
+                roster.lock().unwrap().record(node_id, bus, now);
+                roster_changed.notify_waiters();
+
+                let malformed = can_frame.len() < 2;
+                let packet = assemblers[bus as usize].process_frame(can_frame);
+                let event = {
+                    let mut registry = registry.lock().unwrap();
+                    if malformed {
+                        registry.record_packet_error(node_id);
+                    }
+                    if let Some(packet) = packet {
+                        registry.record_packet(node_id, packet.data());
+                    }
+                    registry.record_telemetry(node_id, bus, now)
+                };
+                if let Some(event) = event {
+                    dispatch_node_event(&node_handlers, node_id, event);
+                }
+            }
+        });
+
+        let clock = self.clock.clone();
+        let registry = self.registry.clone();
+        let node_handlers = self.node_handlers.clone();
+        self.tasks.spawn_periodic(LIVENESS_CHECK_PERIOD, move || {
+            let events = registry.lock().unwrap().check_liveness(clock.now());
+            for (node_id, event) in events {
+                dispatch_node_event(&node_handlers, node_id, event);
+            }
+            async {}
+        });
+    }
+
+    /// Returns a snapshot of the node roster.
+    pub fn roster(&self) -> NodeRoster {
+        self.roster.lock().unwrap().clone()
+    }
+
+    /// Switches to the other bus, so heartbeats and syncs move there, and returns it.
+    ///
+    /// All expected and known responders must be heard on the new bus within
+    /// `timeout`; `verify_switchover` waits for the outcome.
+    pub fn switch_bus(&self, timeout: Duration) -> BusSelection {
+        let bus = self.network.switch_bus();
+        let now = self.clock.now();
+        self.roster
+            .lock()
+            .unwrap()
+            .start_switchover(bus, now, scheduler::from_core_duration(timeout));
+        self.roster_changed.notify_waiters();
+        bus
+    }
+
+    /// Waits until all responders followed the last bus switch or its deadline passed.
+    ///
+    /// Returns the responders that did not follow in time. Requires `start_roster`.
+    pub async fn verify_switchover(&self) -> Result<(), Vec<u32>> {
+        loop {
+            let changed = self.roster_changed.notified();
+            let (status, deadline, pending) = {
+                let roster = self.roster.lock().unwrap();
+                let Some(switchover) = roster.switchover() else {
+                    return Ok(());
+                };
+                (switchover.status(self.clock.now()), switchover.deadline, switchover.pending())
+            };
+            match status {
+                SwitchoverStatus::Completed => return Ok(()),
+                SwitchoverStatus::Failed => return Err(pending),
+                SwitchoverStatus::InProgress => {
+                    let remaining = deadline.checked_duration_since(self.clock.now());
+                    // Wake up just after the deadline to report the failure.
+                    let remaining = remaining.map(scheduler::to_core_duration).unwrap_or_default() + Duration::from_millis(1);
+                    tokio::select! {
+                        _ = changed => {}
+                        _ = time::sleep(remaining) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stops sending and receiving, and waits until all background tasks have ended.
+    pub async fn shutdown(&self) {
+        self.tasks.shutdown().await;
+    }
+}
+
+/// Event received by a responder node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponderEvent {
+    Heartbeat,
+    /// Sync frame, with the sync counter if the controller sends one.
+    Sync(Option<u8>),
+    Time(TimeFrame),
+    /// Telecommand packet addressed to this node.
+    Telecommand(Vec<u8>),
+    /// No heartbeat was received within the timeout and the responder switched to this bus.
+    BusSwitched(BusSelection),
+}
+
+/// Responder node running on a tokio runtime.
+///
+/// Receives heartbeats, syncs, time and telecommands as a stream of
+/// events and sends telemetry packets. With a heartbeat timeout set, the
+/// responder switches buses when the controller's heartbeats stop.
+pub struct AsyncResponder<B: AsyncBus> {
+    node_id: u32,
+    network: Arc<AsyncNetwork<B>>,
+    heartbeat_timeout: Option<Duration>,
+    tasks: Tasks,
+}
+
+impl<B: AsyncBus> AsyncResponder<B> {
+    pub fn new(network: AsyncNetwork<B>, node_id: u32) -> Self {
+        assert!((1..=127).contains(&node_id), "node id must be in range 1..127");
+        AsyncResponder {
+            node_id,
+            network: Arc::new(network),
+            heartbeat_timeout: None,
+            tasks: Tasks::new(),
+        }
+    }
+
+    pub fn node_id(&self) -> u32 {
+        self.node_id
+    }
+
+    pub fn network(&self) -> &Arc<AsyncNetwork<B>> {
+        &self.network
+    }
+
+    /// Sets the time without heartbeat after which the responder switches buses.
+    ///
+    /// Applies to event streams created afterwards.
+    pub fn set_heartbeat_timeout(&mut self, timeout: Option<Duration>) {
+        self.heartbeat_timeout = timeout;
+    }
+
+    /// Sends a telemetry packet to the controller.
+    pub async fn send_packet(&self, data: Vec<u8>) -> Result<(), CanFrameError> {
+        send_packet(&self.network, ID_TM + self.node_id, data).await
+    }
+
+    /// Runs a task every `period` until shutdown, e.g. to send housekeeping reports.
+    pub fn every<F, Fut>(&self, period: Duration, task: F)
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        self.tasks.spawn_periodic(period, task);
+    }
+
+    /// Runs the due actions of a sync scheduler on every sync received on the
+    /// selected bus, until shutdown.
+    ///
+    /// Returns the scheduler, e.g. to register actions or read the sync statistics.
+    pub fn start_sync_scheduler(&self, sync_scheduler: SyncScheduler) -> Arc<Mutex<SyncScheduler>> {
+        let sync_scheduler = Arc::new(Mutex::new(sync_scheduler));
+        let mut frames = self.network.frames();
+        let scheduler = sync_scheduler.clone();
+        self.tasks.spawn(async move {
+            while let Some(can_frame) = frames.next().await {
+                if can_frame.can_id() == ID_SYNC {
+                    scheduler.lock().unwrap().received_sync();
+                }
+            }
+        });
+        sync_scheduler
+    }
+
+    /// Passes every sync received on the selected bus to a sync consumer,
+    /// until shutdown.
+    ///
+    /// Returns the consumer, e.g. to check the sync counter, missed syncs or
+    /// the synchronous window.
+    pub fn start_sync_consumer(&self, sync_consumer: SyncConsumer) -> Arc<SyncConsumer> {
+        let sync_consumer = Arc::new(sync_consumer);
+        let mut frames = self.network.frames();
+        let consumer = sync_consumer.clone();
+        self.tasks.spawn(async move {
+            while let Some(can_frame) = frames.next().await {
+                if can_frame.can_id() == ID_SYNC {
+                    consumer.receive_sync_frame(can_frame.data());
+                }
+            }
+        });
+        sync_consumer
+    }
+
+    /// Returns a stream of the events received from now on.
+    ///
+    /// The stream ends on shutdown.
+    pub fn events(&self) -> UnboundedReceiverStream<ResponderEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut frames = self.network.frames();
+        let network = self.network.clone();
+        let node_id = self.node_id;
+        let heartbeat_timeout = self.heartbeat_timeout;
+        self.tasks.spawn(async move {
+            let mut assembler = PacketAssembler::new();
+            let mut deadline = heartbeat_timeout.map(|timeout| Instant::now() + timeout);
+            loop {
+                let received = match deadline {
+                    Some(at) => time::timeout_at(at, frames.next()).await.ok(),
+                    None => Some(frames.next().await),
+                };
+                let event = match received {
+                    // The stream of the selected bus ended.
+                    Some(None) => break,
+                    Some(Some(can_frame)) => match can_frame.can_id() {
+                        ID_HEARTBEAT => {
+                            deadline = heartbeat_timeout.map(|timeout| Instant::now() + timeout);
+                            Some(ResponderEvent::Heartbeat)
+                        }
+                        ID_SYNC => Some(ResponderEvent::Sync(can_frame.data().first().copied())),
+                        can_id if can_id == ID_TC + node_id => assembler
+                            .process_frame(can_frame)
+                            .map(|packet| ResponderEvent::Telecommand(packet.data().clone())),
+                        _ => time_frames::decode_time_frame(&can_frame).map(ResponderEvent::Time),
+                    },
+                    None => {
+                        deadline = heartbeat_timeout.map(|timeout| Instant::now() + timeout);
+                        Some(ResponderEvent::BusSwitched(network.switch_bus()))
+                    }
+                };
+                if let Some(event) = event
+                    && sender.send(event).is_err()
+                {
+                    break;
+                }
+            }
+        });
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    /// Stops sending and receiving, and waits until all background tasks have ended.
+    pub async fn shutdown(&self) {
+        self.tasks.shutdown().await;
+    }
+}
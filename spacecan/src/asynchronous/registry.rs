@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use serde::Deserialize;
+
+use super::network::BusSelection;
+use crate::primitives::scheduler::{self, Duration, Instant};
+use crate::services::ST17_test::Reachability;
+
+/// Default time without telemetry after which a node is considered silent.
+const DEFAULT_SILENCE_TIMEOUT: core::time::Duration = core::time::Duration::from_secs(3);
+
+/// Liveness of a node as seen by the controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeState {
+    /// Configured, but never heard.
+    Unknown,
+    Alive,
+    /// No telemetry within the silence timeout.
+    Silent,
+}
+
+/// Change of a node's liveness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeEvent {
+    /// First telemetry from the node.
+    Appeared,
+    /// The node stopped sending telemetry.
+    Silent,
+    /// A silent node sends telemetry again.
+    Recovered,
+}
+
+/// Handler called with the node ID on liveness changes.
+pub type NodeEventHandler = Arc<dyn Fn(u32, NodeEvent) + Send + Sync>;
+
+/// What the controller knows about one responder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeInfo {
+    pub node_id: u32,
+    pub name: Option<String>,
+    pub state: NodeState,
+    pub last_telemetry: Option<Instant>,
+    /// Bus the node's telemetry last arrived on.
+    pub bus: Option<BusSelection>,
+    /// Telecommands sent without a final verification report yet.
+    pub pending_commands: u32,
+    /// Received packets that could not be processed.
+    pub packet_errors: u32,
+    /// Result of the last ST17 connection test.
+    pub reachability: Option<Reachability>,
+    /// Pending telecommands by request ID, their service type and subtype.
+    pending_requests: BTreeMap<(u8, u8), u32>,
+}
+
+impl NodeInfo {
+    fn new(node_id: u32, name: Option<String>) -> Self {
+        NodeInfo {
+            node_id,
+            name,
+            state: NodeState::Unknown,
+            last_telemetry: None,
+            bus: None,
+            pending_commands: 0,
+            packet_errors: 0,
+            reachability: None,
+            pending_requests: BTreeMap::new(),
+        }
+    }
+
+    /// Settles a pending telecommand on its final verification report.
+    fn settle_request(&mut self, request_id: (u8, u8)) {
+        let Some(pending) = self.pending_requests.get_mut(&request_id) else {
+            return;
+        };
+        *pending -= 1;
+        if *pending == 0 {
+            self.pending_requests.remove(&request_id);
+        }
+        self.pending_commands = self.pending_commands.saturating_sub(1);
+    }
+}
+
+#[derive(Deserialize)]
+struct NodeConfig {
+    node_id: u32,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RegistryConfig {
+    silence_timeout_ms: Option<u64>,
+    nodes: Vec<NodeConfig>,
+}
+
+/// Registry of the responders known to the controller and their liveness.
+///
+/// Nodes are configured up front and nodes that are heard without being
+/// configured are added on the fly. A node is alive while its telemetry
+/// keeps arriving, and silent once none arrived within the silence timeout.
+#[derive(Debug, Clone)]
+pub struct NodeRegistry {
+    nodes: BTreeMap<u32, NodeInfo>,
+    silence_timeout: Duration,
+}
+
+impl Default for NodeRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_SILENCE_TIMEOUT)
+    }
+}
+
+impl NodeRegistry {
+    pub fn new(silence_timeout: core::time::Duration) -> Self {
+        NodeRegistry {
+            nodes: BTreeMap::new(),
+            silence_timeout: scheduler::from_core_duration(silence_timeout),
+        }
+    }
+
+    /// Creates a registry from a configuration such as
+    /// `{"silence_timeout_ms": 3000, "nodes": [{"node_id": 1, "name": "eps"}]}`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let config: RegistryConfig = serde_json::from_str(json)?;
+        let silence_timeout = config
+            .silence_timeout_ms
+            .map_or(DEFAULT_SILENCE_TIMEOUT, core::time::Duration::from_millis);
+        let mut registry = Self::new(silence_timeout);
+        for node in config.nodes {
+            registry.add_node(node.node_id, node.name);
+        }
+        Ok(registry)
+    }
+
+    /// Adds a node, or renames it if it is already known.
+    pub fn add_node(&mut self, node_id: u32, name: Option<String>) {
+        self.nodes
+            .entry(node_id)
+            .and_modify(|node| node.name = name.clone())
+            .or_insert_with(|| NodeInfo::new(node_id, name));
+    }
+
+    pub fn node(&self, node_id: u32) -> Option<&NodeInfo> {
+        self.nodes.get(&node_id)
+    }
+
+    /// Returns all nodes ordered by node ID.
+    pub fn nodes(&self) -> Vec<NodeInfo> {
+        self.nodes.values().cloned().collect()
+    }
+
+    /// Records a telemetry frame from a node. Returns the liveness change, if any.
+    pub fn record_telemetry(&mut self, node_id: u32, bus: BusSelection, now: Instant) -> Option<NodeEvent> {
+        let node = self.node_mut(node_id);
+        node.last_telemetry = Some(now);
+        node.bus = Some(bus);
+        let event = match node.state {
+            NodeState::Unknown => Some(NodeEvent::Appeared),
+            NodeState::Silent => Some(NodeEvent::Recovered),
+            NodeState::Alive => None,
+        };
+        node.state = NodeState::Alive;
+        event
+    }
+
+    /// Records a complete telemetry packet, settling commands on their final verification report.
+    ///
+    /// The report carries the request ID of the telecommand; reports for
+    /// requests that are not pending are ignored. Packets too short to hold a
+    /// service type and subtype count as packet errors.
+    pub fn record_packet(&mut self, node_id: u32, data: &[u8]) {
+        let node = self.node_mut(node_id);
+        match data {
+            // Failed acceptance, successful and failed completion end a request.
+            [1, 2 | 7 | 8, service, subtype, ..] => node.settle_request((*service, *subtype)),
+            [_, _, ..] => {}
+            _ => node.packet_errors = node.packet_errors.saturating_add(1),
+        }
+    }
+
+    /// Records a packet from a node that could not be assembled or decoded.
+    pub fn record_packet_error(&mut self, node_id: u32) {
+        let node = self.node_mut(node_id);
+        node.packet_errors = node.packet_errors.saturating_add(1);
+    }
+
+    /// Records a telecommand sent to a node, by its request ID: the service type and subtype.
+    pub fn command_sent(&mut self, node_id: u32, request_id: (u8, u8)) {
+        let node = self.node_mut(node_id);
+        *node.pending_requests.entry(request_id).or_default() += 1;
+        node.pending_commands = node.pending_commands.saturating_add(1);
+    }
+
+    /// Records the result of an ST17 connection test.
+    pub fn set_reachability(&mut self, node_id: u32, reachability: Reachability) {
+        self.node_mut(node_id).reachability = Some(reachability);
+    }
+
+    /// Marks alive nodes without telemetry within the silence timeout as silent and returns them.
+    pub fn check_liveness(&mut self, now: Instant) -> Vec<(u32, NodeEvent)> {
+        let silence_timeout = self.silence_timeout;
+        self.nodes
+            .values_mut()
+            .filter(|node| node.state == NodeState::Alive)
+            .filter(|node| node.last_telemetry.is_some_and(|last| now - last > silence_timeout))
+            .map(|node| {
+                node.state = NodeState::Silent;
+                (node.node_id, NodeEvent::Silent)
+            })
+            .collect()
+    }
+
+    fn node_mut(&mut self, node_id: u32) -> &mut NodeInfo {
+        self.nodes
+            .entry(node_id)
+            .or_insert_with(|| NodeInfo::new(node_id, None))
+    }
+}
@@ -8,6 +8,12 @@ pub mod services;
 pub mod transport;
 pub mod parser;
 pub mod protocol;
+pub mod storage;
+#[cfg(feature = "std")]
+pub mod asynchronous;
 
 #[cfg(not(feature = "std"))]
 pub mod panic_handler;
+
+#[cfg(all(test, feature = "std"))]
+mod tests;
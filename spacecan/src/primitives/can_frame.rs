@@ -31,6 +31,7 @@ pub enum CanFrameError {
     DataTooLong,
     InvalidCanId(u32),
     SendFailed,
+    QueueFull,
 }
 
 impl fmt::Display for CanFrameError {
@@ -39,6 +40,7 @@ impl fmt::Display for CanFrameError {
             CanFrameError::DataTooLong => write!(f, "Data length exceeds 8 bytes"),
             CanFrameError::InvalidCanId(id) => write!(f, "Invalid CAN ID: {}", id),
             CanFrameError::SendFailed => write!(f, "CAN frame send failed"),
+            CanFrameError::QueueFull => write!(f, "CAN transmit queue full"),
         }
     }
 }
@@ -0,0 +1,24 @@
+/// Initial value of the CRC-16-CCITT register used by ECSS packets.
+const CRC16_INIT: u16 = 0xFFFF;
+/// Generator polynomial x^16 + x^12 + x^5 + 1.
+const CRC16_POLY: u16 = 0x1021;
+
+/// Computes the CRC-16-CCITT checksum over the given bytes.
+pub fn crc16(data: &[u8]) -> u16 {
+    crc16_update(CRC16_INIT, data)
+}
+
+/// Continues a CRC-16-CCITT computation from a previous register value.
+pub fn crc16_update(mut crc: u16, data: &[u8]) -> u16 {
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            if crc & 0x8000 != 0 {
+                crc = (crc << 1) ^ CRC16_POLY;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
@@ -2,79 +2,25 @@
 
 extern crate alloc;
 
+use alloc::boxed::Box;
 use alloc::sync::Arc;
-#[cfg(feature = "std")]
-use std::sync::Mutex;
-#[cfg(not(feature = "std"))]
-use cortex_m::interrupt::Mutex;
 
+use core::sync::atomic::{AtomicU32, Ordering};
 use core::time::Duration;
-#[cfg(feature = "std")]
-use std::thread;
-#[cfg(feature = "std")]
-use std::time::Instant;
 
 use super::can_frame::{CanFrame, CanFrameError}; // Import improved CanFrame
+#[cfg(feature = "std")]
+use super::scheduler::StdClock;
+use super::scheduler::{self, Clock, InstantCell, Scheduler, SharedClock};
 use crate::primitives::network::Parent;
 
 const ID_HEARTBEAT: u32 = 0x700;
 
-/// Timer struct to handle periodic heartbeat signals.
-struct Timer {
-    period: Duration,
-    callback: Arc<dyn Fn() + Send + Sync>,
-    running: Arc<Mutex<bool>>,
-}
-
-impl Timer {
-    /// Creates a new timer with the specified period and callback.
-    fn new(period: Duration, callback: Arc<dyn Fn() + Send + Sync>) -> Self {
-        Timer {
-            period,
-            callback,
-            running: Arc::new(Mutex::new(false)),
-        }
-    }
-
-    /// Starts the timer, invoking the callback periodically.
-    #[cfg(feature = "std")]
-    fn start(&self) {
-        let running = Arc::clone(&self.running);
-        let callback = Arc::clone(&self.callback);
-        let period = self.period;
-        *running.lock().unwrap() = true;
-
-        std::thread::spawn(move || {
-            while *running.lock().unwrap() {
-                std::thread::sleep(period);
-                callback();
-            }
-        });
-    }
-
-    #[cfg(not(feature = "std"))]
-    fn start(&self) {
-        // No-op or alternative implementation for no_std
-    }
-
-    /// Stops the timer.
-    #[cfg(feature = "std")]
-    fn stop(&self) {
-        *self.running.lock().unwrap() = false;
-    }
-
-    #[cfg(not(feature = "std"))]
-    fn stop(&self) {
-        // no_std alternative implementation if needed
-    }
-}
-
 /// HeartbeatProducer struct to send periodic heartbeats.
 pub struct HeartbeatProducer {
     parent: Arc<dyn Parent>,
-    running: bool,
-    timer: Option<Timer>,
-    period: Option<Duration>,
+    task: Option<usize>,
+    period: Option<scheduler::Duration>,
     can_frame: CanFrame,
 }
 
@@ -84,8 +30,7 @@ impl HeartbeatProducer {
         let can_frame = CanFrame::new(ID_HEARTBEAT, None)?;
         Ok(HeartbeatProducer {
             parent,
-            running: false,
-            timer: None,
+            task: None,
             period: None,
             can_frame,
         })
@@ -96,80 +41,110 @@ impl HeartbeatProducer {
         self.parent.send(&self.can_frame)
     }
 
-    /// Starts sending heartbeats periodically with the given period.
-    pub fn start(&mut self, period: Duration) {
+    /// Starts sending heartbeats periodically with the given period, driven by the scheduler's polls.
+    pub fn start<C: Clock>(&mut self, scheduler: &mut Scheduler<C>, period: scheduler::Duration) {
+        self.stop(scheduler);
         self.period = Some(period);
-        if self.running {
-            self.stop();
-        }
-        self.running = true;
         let parent_clone = Arc::clone(&self.parent);
         let frame_clone = self.can_frame.clone();
 
-        let timer = Timer::new(period, Arc::new(move || {
+        self.task = Some(scheduler.add_periodic(period, Box::new(move |_| {
             let _ = parent_clone.send(&frame_clone);
-        }));
-        self.timer = Some(timer);
-        self.timer.as_ref().unwrap().start();
+        })));
     }
 
     /// Stops sending heartbeats.
-    pub fn stop(&mut self) {
-        self.running = false;
-        if let Some(timer) = self.timer.take() {
-            timer.stop();
+    pub fn stop<C: Clock>(&mut self, scheduler: &mut Scheduler<C>) {
+        if let Some(task) = self.task.take() {
+            scheduler.remove(task);
         }
     }
+
+    /// Returns true if heartbeats are sent periodically.
+    pub fn is_running(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Returns the period of the last start, if any.
+    pub fn period(&self) -> Option<scheduler::Duration> {
+        self.period
+    }
 }
 
+/// Handler called when no heartbeat arrived within the timeout, e.g. to switch buses.
+pub type HeartbeatTimeoutHandler = Arc<dyn Fn() + Send + Sync>;
+
 /// HeartbeatConsumer struct to monitor received heartbeats.
 pub struct HeartbeatConsumer {
-    #[cfg(feature = "std")]
-    last_received: Arc<Mutex<Option<Instant>>>,
-    #[cfg(not(feature = "std"))]
-    last_received: Arc<Mutex<Option<()>>>,
+    clock: SharedClock,
+    last_received: InstantCell,
     timeout: Duration,
+    timeouts: AtomicU32,
+    timeout_handler: Option<HeartbeatTimeoutHandler>,
 }
 
 impl HeartbeatConsumer {
-    /// Creates a new HeartbeatConsumer with the specified timeout.
+    /// Creates a new HeartbeatConsumer with the specified timeout, timed by the system clock.
+    #[cfg(feature = "std")]
     pub fn new(timeout: Duration) -> Self {
+        Self::with_clock(timeout, Arc::new(StdClock::new()))
+    }
+
+    /// Creates a new HeartbeatConsumer with the specified timeout, timed by the given clock.
+    pub fn with_clock(timeout: Duration, clock: SharedClock) -> Self {
         HeartbeatConsumer {
-            #[cfg(feature = "std")]
-            last_received: Arc::new(Mutex::new(None)),
-            #[cfg(not(feature = "std"))]
-            last_received: Arc::new(Mutex::new(None)),
+            clock,
+            last_received: InstantCell::new(),
             timeout,
+            timeouts: AtomicU32::new(0),
+            timeout_handler: None,
         }
     }
 
-    /// Records the receipt of a heartbeat.
-    #[cfg(feature = "std")]
-    pub fn receive_heartbeat(&self) {
-        let mut last_received = self.last_received.lock().unwrap();
-        *last_received = Some(Instant::now());
+    /// Sets the handler called by `poll` when the heartbeat timed out.
+    pub fn set_timeout_handler(&mut self, handler: HeartbeatTimeoutHandler) {
+        self.timeout_handler = Some(handler);
     }
 
-    #[cfg(not(feature = "std"))]
+    /// Records the receipt of a heartbeat.
     pub fn receive_heartbeat(&self) {
-        // No-op or alternative implementation for no_std
+        self.last_received.set(Some(self.clock.now()));
     }
 
     /// Checks if the heartbeat has timed out.
-    #[cfg(feature = "std")]
     pub fn check_timeout(&self) -> bool {
-        let last_received = self.last_received.lock().unwrap();
-        if let Some(last) = *last_received {
-            return last.elapsed() > self.timeout;
+        match self.last_received.get() {
+            Some(last) => self.clock.now() - last > scheduler::from_core_duration(self.timeout),
+            None => true,
         }
-        true
     }
 
-    #[cfg(not(feature = "std"))]
-    pub fn check_timeout(&self) -> bool {
-        // Always timeout in no_std or implement alternative logic
+    /// Checks the heartbeat and calls the timeout handler if it timed out.
+    ///
+    /// Monitoring starts with the first poll or heartbeat. After a timeout the
+    /// timeout restarts, so the handler is called once per timeout period
+    /// while heartbeats stay away. Returns true if the heartbeat timed out.
+    pub fn poll(&self) -> bool {
+        let now = self.clock.now();
+        let Some(last) = self.last_received.get() else {
+            self.last_received.set(Some(now));
+            return false;
+        };
+        if now - last <= scheduler::from_core_duration(self.timeout) {
+            return false;
+        }
+        self.last_received.set(Some(now));
+        self.timeouts.fetch_add(1, Ordering::Relaxed);
+        if let Some(handler) = &self.timeout_handler {
+            handler();
+        }
         true
     }
+
+    /// Returns the number of timeouts detected by `poll`.
+    pub fn timeouts(&self) -> u32 {
+        self.timeouts.load(Ordering::Relaxed)
+    }
 }
 
 /// Heartbeat struct for main.rs usage.
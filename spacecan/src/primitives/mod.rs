@@ -1,6 +1,12 @@
 pub mod can_frame;
+pub mod crc;
 pub mod heartbeat;
 pub mod network;
 pub mod packet;
+pub mod redundancy;
+pub mod roster;
+pub mod scheduler;
+pub mod statistics;
 pub mod sync;
-pub mod timer;
+pub mod time;
+pub mod transmit_queue;
@@ -1,14 +1,28 @@
-
 extern crate alloc;
 
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicBool, Ordering};
 #[cfg(feature = "std")]
 use std::sync::Mutex;
 #[cfg(not(feature = "std"))]
 use cortex_m::interrupt::Mutex;
 
 use super::can_frame::{CanFrame, CanFrameError}; // Import improved CanFrame
-use core::ptr;
+use super::packet::FrameSequenceTracker;
+use super::redundancy::{BusHealth, BusSwitch, RedundancyManager, SwitchPolicy};
+#[cfg(feature = "std")]
+use super::scheduler::StdClock;
+use super::scheduler::SharedClock;
+use super::statistics::{ControllerErrorCounters, NetworkStatistics, DEFAULT_BITRATE};
+use super::transmit_queue::{OverflowPolicy, OverflowReport, TransmitQueue};
+
+const ID_HEARTBEAT: u32 = 0x700;
+const ID_TC: u32 = 0x280;
+const ID_TM: u32 = 0x300;
+const NODE_MASK: u32 = 0x07F;
 
 // Define a trait for Bus
 pub trait Bus {
@@ -17,127 +31,369 @@ pub trait Bus {
     fn stop_receive(&self);
     fn send(&self, can_frame: &CanFrame) -> Result<(), CanFrameError>;
     fn get_frame(&self) -> Option<CanFrame>;
+
+    /// Returns the error counters of the CAN controller, if the backend provides them.
+    ///
+    /// None of the buses in this crate have access to a CAN controller, so
+    /// only backends for controller hardware override this.
+    fn error_counters(&self) -> Option<ControllerErrorCounters> {
+        None
+    }
+}
+
+/// One of the two redundant buses of a network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusSelection {
+    A,
+    B,
 }
 
-// Network struct with thread-safe bus switching
+impl BusSelection {
+    /// Returns the other bus.
+    pub fn other(self) -> Self {
+        match self {
+            BusSelection::A => BusSelection::B,
+            BusSelection::B => BusSelection::A,
+        }
+    }
+}
+
+// Network of two redundant buses; the redundancy manager selects the bus in use
 
 pub struct Network<T: Bus> {
     parent: Arc<dyn Parent>, // Assuming Parent is a trait that has the method received_frame
     node_id: u32,
     bus_a: T,
     bus_b: T,
+    clock: SharedClock,
+    running: AtomicBool,
+    /// Set while the frame at the front of the queue fails on the selected bus.
+    stalled: AtomicBool,
+    #[cfg(feature = "std")]
+    redundancy: Mutex<RedundancyManager>, // Ensures safe concurrent access
+    #[cfg(not(feature = "std"))]
+    redundancy: Mutex<RefCell<RedundancyManager>>,
     #[cfg(feature = "std")]
-    selected_bus: Mutex<T>, // Ensures safe concurrent access
+    statistics: Mutex<NetworkStatistics>,
     #[cfg(not(feature = "std"))]
-    selected_bus: cortex_m::interrupt::Mutex<core::cell::RefCell<T>>,
+    statistics: Mutex<RefCell<NetworkStatistics>>,
+    #[cfg(feature = "std")]
+    queue: Mutex<TransmitQueue>,
+    #[cfg(not(feature = "std"))]
+    queue: Mutex<RefCell<TransmitQueue>>,
+    #[cfg(feature = "std")]
+    sequences: Mutex<FrameSequenceTracker>,
+    #[cfg(not(feature = "std"))]
+    sequences: Mutex<RefCell<FrameSequenceTracker>>,
 }
 
-impl<T: Bus + Clone> Network<T> {
+impl<T: Bus> Network<T> {
+    /// Creates a network on bus A that only switches buses on command, timed by the system clock.
+    #[cfg(feature = "std")]
     pub fn new(parent: Arc<dyn Parent>, node_id: u32, bus_a: T, bus_b: T) -> Self {
+        let redundancy = RedundancyManager::new(SwitchPolicy::ManualOnly);
+        Self::with_redundancy(parent, node_id, bus_a, bus_b, redundancy, Arc::new(StdClock::new()))
+    }
+
+    /// Creates a network whose bus selection is managed by the given redundancy manager.
+    ///
+    /// The clock timestamps heartbeats and bus switches.
+    pub fn with_redundancy(
+        parent: Arc<dyn Parent>,
+        node_id: u32,
+        bus_a: T,
+        bus_b: T,
+        redundancy: RedundancyManager,
+        clock: SharedClock,
+    ) -> Self {
+        let statistics = NetworkStatistics::new(DEFAULT_BITRATE, clock.now());
         Network {
             parent,
             node_id,
-            bus_a: bus_a.clone(),
-            bus_b: bus_b.clone(),
+            bus_a,
+            bus_b,
+            clock,
+            running: AtomicBool::new(false),
+            stalled: AtomicBool::new(false),
+            #[cfg(feature = "std")]
+            redundancy: Mutex::new(redundancy),
+            #[cfg(not(feature = "std"))]
+            redundancy: Mutex::new(RefCell::new(redundancy)),
+            #[cfg(feature = "std")]
+            statistics: Mutex::new(statistics),
+            #[cfg(not(feature = "std"))]
+            statistics: Mutex::new(RefCell::new(statistics)),
+            #[cfg(feature = "std")]
+            queue: Mutex::new(TransmitQueue::default()),
+            #[cfg(not(feature = "std"))]
+            queue: Mutex::new(RefCell::new(TransmitQueue::default())),
             #[cfg(feature = "std")]
-            selected_bus: Mutex::new(bus_a.clone()), // Start with bus_a clone
+            sequences: Mutex::new(FrameSequenceTracker::new()),
             #[cfg(not(feature = "std"))]
-            selected_bus: cortex_m::interrupt::Mutex::new(core::cell::RefCell::new(bus_a.clone())),
+            sequences: Mutex::new(RefCell::new(FrameSequenceTracker::new())),
         }
     }
 
-    pub fn start(&self) {
-        #[cfg(feature = "std")]
-        {
-            let mut bus = self.selected_bus.lock().unwrap();
-            bus.flush_frame_buffer();
-            bus.start_receive();
-        }
-        #[cfg(not(feature = "std"))]
-        {
-            cortex_m::interrupt::free(|cs| {
-                let bus = self.selected_bus.borrow(cs).borrow_mut();
-                bus.flush_frame_buffer();
-                bus.start_receive();
-            });
+    #[cfg(feature = "std")]
+    fn with_manager<R>(&self, f: impl FnOnce(&mut RedundancyManager) -> R) -> R {
+        f(&mut self.redundancy.lock().unwrap())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_manager<R>(&self, f: impl FnOnce(&mut RedundancyManager) -> R) -> R {
+        cortex_m::interrupt::free(|cs| f(&mut self.redundancy.borrow(cs).borrow_mut()))
+    }
+
+    #[cfg(feature = "std")]
+    fn with_statistics<R>(&self, f: impl FnOnce(&mut NetworkStatistics) -> R) -> R {
+        f(&mut self.statistics.lock().unwrap())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_statistics<R>(&self, f: impl FnOnce(&mut NetworkStatistics) -> R) -> R {
+        cortex_m::interrupt::free(|cs| f(&mut self.statistics.borrow(cs).borrow_mut()))
+    }
+
+    #[cfg(feature = "std")]
+    fn with_queue<R>(&self, f: impl FnOnce(&mut TransmitQueue) -> R) -> R {
+        f(&mut self.queue.lock().unwrap())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_queue<R>(&self, f: impl FnOnce(&mut TransmitQueue) -> R) -> R {
+        cortex_m::interrupt::free(|cs| f(&mut self.queue.borrow(cs).borrow_mut()))
+    }
+
+    #[cfg(feature = "std")]
+    fn with_sequences<R>(&self, f: impl FnOnce(&mut FrameSequenceTracker) -> R) -> R {
+        f(&mut self.sequences.lock().unwrap())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_sequences<R>(&self, f: impl FnOnce(&mut FrameSequenceTracker) -> R) -> R {
+        cortex_m::interrupt::free(|cs| f(&mut self.sequences.borrow(cs).borrow_mut()))
+    }
+
+    pub fn node_id(&self) -> u32 {
+        self.node_id
+    }
+
+    /// Returns one of the buses.
+    pub fn bus(&self, bus: BusSelection) -> &T {
+        match bus {
+            BusSelection::A => &self.bus_a,
+            BusSelection::B => &self.bus_b,
         }
     }
 
+    pub fn start(&self) {
+        self.running.store(true, Ordering::Relaxed);
+        let bus = self.bus(self.selected_bus());
+        bus.flush_frame_buffer();
+        bus.start_receive();
+    }
+
     pub fn stop(&self) {
-        #[cfg(feature = "std")]
-        {
-            let mut bus = self.selected_bus.lock().unwrap();
-            bus.flush_frame_buffer();
-            bus.stop_receive();
-        }
-        #[cfg(not(feature = "std"))]
-        {
-            cortex_m::interrupt::free(|cs| {
-                let bus = self.selected_bus.borrow(cs).borrow_mut();
-                bus.flush_frame_buffer();
-                bus.stop_receive();
-            });
-        }
+        self.running.store(false, Ordering::Relaxed);
+        let bus = self.bus(self.selected_bus());
+        bus.flush_frame_buffer();
+        bus.stop_receive();
     }
 
-    // Process frames from the bus
+    // Process frames from the bus; packet frames out of sequence count as reassembly errors
     pub fn process(&self) {
-        #[cfg(feature = "std")]
-        {
-            let mut bus = self.selected_bus.lock().unwrap();
-            if let Some(can_frame) = bus.get_frame() {
-                self.parent.received_frame(can_frame);
+        let selected = self.selected_bus();
+        if let Some(can_frame) = self.bus(selected).get_frame() {
+            self.with_statistics(|statistics| statistics.record_received(selected, can_frame.can_id(), can_frame.len()));
+            let function = can_frame.can_id() & !NODE_MASK;
+            if can_frame.can_id() == ID_HEARTBEAT {
+                let now = self.clock.now();
+                self.with_manager(|manager| manager.record_heartbeat(selected, now));
+            } else if (function == ID_TC || function == ID_TM)
+                && !self.with_sequences(|sequences| sequences.record(&can_frame))
+            {
+                self.reassembly_error(can_frame.can_id() & NODE_MASK);
             }
-        }
-        #[cfg(not(feature = "std"))]
-        {
-            cortex_m::interrupt::free(|cs| {
-                let bus = self.selected_bus.borrow(cs).borrow_mut();
-                if let Some(can_frame) = bus.get_frame() {
-                    self.parent.received_frame(can_frame);
-                }
-            });
+            self.parent.received_frame(can_frame);
         }
     }
 
+    /// Queues a frame and transmits the queue on the selected bus.
+    ///
+    /// Succeeds once the frame is queued, so callers must not send it again.
+    /// Fails with `CanFrameError::QueueFull` if the queue is full and its
+    /// policy rejects further frames. Transmit failures are reported by
+    /// `transmit`, the statistics and the bus health.
     pub fn send(&self, can_frame: &CanFrame) -> Result<(), CanFrameError> {
-        #[cfg(feature = "std")]
-        {
-            let mut bus = self.selected_bus.lock().unwrap();
-            bus.send(can_frame)
-        }
-        #[cfg(not(feature = "std"))]
-        {
-            let mut result = Err(CanFrameError::SendFailed);
-            cortex_m::interrupt::free(|cs| {
-                let bus = self.selected_bus.borrow(cs).borrow_mut();
-                result = bus.send(can_frame);
-            });
-            result
-        }
+        self.with_queue(|queue| queue.push(can_frame.clone()))?;
+        let _ = self.transmit();
+        Ok(())
     }
 
-    // Thread-safe bus switching
-    pub fn switch_bus(&self) {
-        #[cfg(feature = "std")]
-        {
-            let mut selected = self.selected_bus.lock().unwrap();
-            if ptr::eq(&*selected, &self.bus_a) {
-                *selected = self.bus_b.clone();
-            } else {
-                *selected = self.bus_a.clone();
+    /// Queues the frames of a packet and transmits the queue, see `TransmitQueue::push_all`.
+    ///
+    /// Succeeds once the packet is queued or discarded by the queue's policy, see `send`.
+    pub fn send_all(&self, can_frames: &[CanFrame]) -> Result<(), CanFrameError> {
+        self.with_queue(|queue| queue.push_all(can_frames))?;
+        let _ = self.transmit();
+        Ok(())
+    }
+
+    /// Sends queued frames in priority order until the queue is empty or the bus refuses a frame.
+    ///
+    /// The refused frame stays queued and the bus's error is returned. A
+    /// stalled queue counts as one transmit failure towards the statistics
+    /// and the bus's health, however often it is retried, until a frame gets
+    /// through or the bus is switched.
+    pub fn transmit(&self) -> Result<(), CanFrameError> {
+        // The queue stays locked so that concurrent senders cannot reorder frames
+        self.with_queue(|queue| {
+            while let Some(can_frame) = queue.front() {
+                let selected = self.selected_bus();
+                if let Err(error) = self.bus(selected).send(can_frame) {
+                    if !self.stalled.swap(true, Ordering::Relaxed) {
+                        self.with_statistics(|statistics| statistics.record_send_failure(selected, can_frame.can_id()));
+                        let now = self.clock.now();
+                        let switch = self.with_manager(|manager| manager.record_tx_failure(selected, now));
+                        self.apply(switch);
+                    }
+                    return Err(error);
+                }
+                self.stalled.store(false, Ordering::Relaxed);
+                self.with_statistics(|statistics| {
+                    statistics.record_sent(selected, can_frame.can_id(), can_frame.len())
+                });
+                queue.pop();
             }
+            Ok(())
+        })
+    }
+
+    /// Returns true while the frame at the front of the queue cannot be sent on the selected bus.
+    pub fn is_transmit_stalled(&self) -> bool {
+        self.stalled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of frames waiting for transmission.
+    pub fn queued_frames(&self) -> usize {
+        self.with_queue(|queue| queue.len())
+    }
+
+    /// Replaces the transmit queue with an empty one of the given capacity and policy.
+    pub fn set_transmit_queue(&self, capacity: usize, policy: OverflowPolicy) {
+        self.with_queue(|queue| *queue = TransmitQueue::new(capacity, policy));
+    }
+
+    pub fn overflow_report(&self) -> OverflowReport {
+        self.with_queue(|queue| queue.report())
+    }
+
+    /// Discards all frames waiting for transmission.
+    pub fn clear_transmit_queue(&self) {
+        self.with_queue(|queue| queue.clear());
+    }
+
+    /// Switches to the other bus on command and returns the switch.
+    pub fn switch_bus(&self) -> BusSwitch {
+        let now = self.clock.now();
+        let switch = self.with_manager(|manager| manager.switch(now));
+        self.apply(Some(switch));
+        switch
+    }
+
+    /// Selects a bus on command. Returns the switch, or None if the bus was already selected.
+    pub fn select_bus(&self, bus: BusSelection) -> Option<BusSwitch> {
+        let now = self.clock.now();
+        let switch = self.with_manager(|manager| manager.select(bus, now));
+        self.apply(switch);
+        switch
+    }
+
+    /// Records a missed heartbeat, e.g. from a heartbeat consumer's timeout handler.
+    ///
+    /// Returns the switch if the policy triggered one.
+    pub fn heartbeat_missed(&self) -> Option<BusSwitch> {
+        let now = self.clock.now();
+        let switch = self.with_manager(|manager| manager.record_heartbeat_missed(now));
+        self.apply(switch);
+        switch
+    }
+
+    /// Records an error reported by a bus driver. Returns the switch if the policy triggered one.
+    pub fn bus_error(&self, bus: BusSelection) -> Option<BusSwitch> {
+        let now = self.clock.now();
+        let switch = self.with_manager(|manager| manager.record_error(bus, now));
+        self.apply(switch);
+        switch
+    }
+
+    pub fn selected_bus(&self) -> BusSelection {
+        self.with_manager(|manager| manager.selected())
+    }
+
+    pub fn nominal_bus(&self) -> BusSelection {
+        self.with_manager(|manager| manager.nominal())
+    }
+
+    pub fn redundant_bus(&self) -> BusSelection {
+        self.with_manager(|manager| manager.redundant())
+    }
+
+    pub fn bus_health(&self, bus: BusSelection) -> BusHealth {
+        self.with_manager(|manager| manager.health(bus))
+    }
+
+    pub fn switch_history(&self) -> Vec<BusSwitch> {
+        self.with_manager(|manager| manager.history())
+    }
+
+    pub fn set_switch_policy(&self, policy: SwitchPolicy) {
+        self.with_manager(|manager| manager.set_policy(policy));
+    }
+
+    /// Records a packet from a node that could not be reassembled from its frames.
+    ///
+    /// Called by `process` for packet frames out of sequence; receivers that
+    /// reject packets for other reasons may record them too.
+    pub fn reassembly_error(&self, node_id: u32) {
+        self.with_statistics(|statistics| statistics.record_reassembly_error(node_id));
+    }
+
+    /// Sets the bit rate of the buses, used to estimate the bus load.
+    pub fn set_bitrate(&self, bitrate: u32) {
+        self.with_statistics(|statistics| statistics.bitrate = bitrate);
+    }
+
+    /// Returns a snapshot of the traffic statistics, with the controller error counters of both buses.
+    pub fn statistics(&self) -> NetworkStatistics {
+        let now = self.clock.now();
+        let mut snapshot = self.with_statistics(|statistics| statistics.snapshot(now));
+        for bus in [BusSelection::A, BusSelection::B] {
+            snapshot.buses[bus as usize].controller = self.bus(bus).error_counters();
         }
-        #[cfg(not(feature = "std"))]
-        {
-            cortex_m::interrupt::free(|cs| {
-                let mut selected = self.selected_bus.borrow(cs).borrow_mut();
-                if ptr::eq(&*selected, &self.bus_a) {
-                    *selected = self.bus_b.clone();
-                } else {
-                    *selected = self.bus_a.clone();
-                }
-            });
+        snapshot
+    }
+
+    /// Clears the traffic statistics and restarts the bus load window.
+    pub fn reset_statistics(&self) {
+        let now = self.clock.now();
+        self.with_statistics(|statistics| *statistics = NetworkStatistics::new(statistics.bitrate, now));
+    }
+
+    // Moves reception to the newly selected bus if the network is running
+    fn apply(&self, switch: Option<BusSwitch>) {
+        let Some(switch) = switch else {
+            return;
+        };
+        // The queued frames get a fresh start on the other bus
+        self.stalled.store(false, Ordering::Relaxed);
+        if self.running.load(Ordering::Relaxed) {
+            let previous = self.bus(switch.from);
+            previous.stop_receive();
+            previous.flush_frame_buffer();
+            let next = self.bus(switch.to);
+            next.flush_frame_buffer();
+            next.start_receive();
         }
     }
 }
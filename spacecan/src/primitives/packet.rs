@@ -6,6 +6,8 @@ use alloc::vec::Vec;
 use super::can_frame::{CanFrame, CanFrameError};
 
 const MAX_DATA_LENGTH: usize = 6;
+/// Largest packet that can be split: the frame counter is one byte.
+pub const MAX_PACKET_LENGTH: usize = 256 * MAX_DATA_LENGTH;
 
 #[derive(Debug)]
 pub struct Packet {
@@ -18,6 +20,10 @@ impl Packet {
         Packet { data }
     }
 
+    pub fn data(&self) -> &Vec<u8> {
+        &self.data
+    }
+
     pub fn split(&self) -> Vec<Vec<u8>> {
         let total_frames = (self.data.len() + MAX_DATA_LENGTH - 1) / MAX_DATA_LENGTH;
         let mut frames = Vec::with_capacity(total_frames);
@@ -67,3 +73,38 @@ impl PacketAssembler {
         }
     }
 }
+
+/// Follows the frames of the packets in transit, one packet per CAN ID, to
+/// detect packets that cannot be reassembled.
+///
+/// The frames of a packet are sent in order on one CAN ID, so a frame out of
+/// sequence means that frames were lost.
+#[derive(Debug, Default)]
+pub struct FrameSequenceTracker {
+    // Maps can_id to the last and the next expected frame index
+    in_transit: BTreeMap<u32, (u8, u8)>,
+}
+
+impl FrameSequenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a packet frame. Returns false if it breaks off a packet or cannot belong to one.
+    pub fn record(&mut self, can_frame: &CanFrame) -> bool {
+        let can_id = can_frame.can_id();
+        let previous = self.in_transit.remove(&can_id);
+        let (Some(&last), Some(&index)) = (can_frame.data().first(), can_frame.data().get(1)) else {
+            return false;
+        };
+        let continues = previous == Some((last, index));
+        let starts = index == 0;
+        if (continues || starts) && index < last {
+            self.in_transit.insert(can_id, (last, index + 1));
+        }
+        match previous {
+            Some(_) => continues,
+            None => starts,
+        }
+    }
+}
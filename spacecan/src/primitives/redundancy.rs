@@ -0,0 +1,231 @@
+extern crate alloc;
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use super::network::BusSelection;
+use super::scheduler::Instant;
+
+/// Number of bus switches kept in the history.
+pub const MAX_SWITCH_HISTORY: usize = 32;
+
+/// When the redundancy manager switches buses on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchPolicy {
+    /// Only switch when commanded.
+    ManualOnly,
+    /// Switch after this many consecutive missed heartbeats on the selected bus.
+    HeartbeatMiss { max_missed: u32 },
+    /// Switch after this many errors and transmit failures on the selected bus since it was selected.
+    ErrorThreshold { max_errors: u32 },
+}
+
+/// Why the buses were switched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchReason {
+    Manual,
+    HeartbeatMissed,
+    ErrorThreshold,
+}
+
+/// Entry of the switch history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusSwitch {
+    pub time: Instant,
+    pub from: BusSelection,
+    pub to: BusSelection,
+    pub reason: SwitchReason,
+}
+
+/// Health metrics of one bus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BusHealth {
+    /// Errors reported by the bus driver, e.g. error frames or bus-off events.
+    pub errors: u32,
+    /// Frames that could not be sent.
+    pub tx_failures: u32,
+    /// Errors and transmit failures since the bus was last selected.
+    pub recent_errors: u32,
+    /// Heartbeats received.
+    pub heartbeats: u32,
+    /// Heartbeats missed since the last received one.
+    pub missed_heartbeats: u32,
+    pub last_heartbeat: Option<Instant>,
+}
+
+/// Tracks the health of the two redundant buses and decides when to switch.
+///
+/// One bus is nominal and the other redundant; the node starts on the
+/// nominal bus. Errors, transmit failures and heartbeats are recorded
+/// per bus, and the policy is applied to the selected bus. Automatic
+/// switches can be limited, so a node with two failed buses does not keep
+/// switching back and forth; commanded switches are always carried out.
+#[derive(Debug, Clone)]
+pub struct RedundancyManager {
+    policy: SwitchPolicy,
+    nominal: BusSelection,
+    selected: BusSelection,
+    health: [BusHealth; 2],
+    max_switches: Option<u32>,
+    automatic_switches: u32,
+    history: VecDeque<BusSwitch>,
+}
+
+impl RedundancyManager {
+    /// Creates a manager with bus A as nominal and selected bus.
+    pub fn new(policy: SwitchPolicy) -> Self {
+        RedundancyManager {
+            policy,
+            nominal: BusSelection::A,
+            selected: BusSelection::A,
+            health: [BusHealth::default(); 2],
+            max_switches: None,
+            automatic_switches: 0,
+            history: VecDeque::new(),
+        }
+    }
+
+    pub fn policy(&self) -> SwitchPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: SwitchPolicy) {
+        self.policy = policy;
+    }
+
+    /// Limits the number of automatic switches; None allows any number.
+    pub fn set_max_switches(&mut self, max_switches: Option<u32>) {
+        self.max_switches = max_switches;
+    }
+
+    /// Sets the nominal bus. The selection is not changed.
+    pub fn set_nominal(&mut self, bus: BusSelection) {
+        self.nominal = bus;
+    }
+
+    pub fn nominal(&self) -> BusSelection {
+        self.nominal
+    }
+
+    pub fn redundant(&self) -> BusSelection {
+        self.nominal.other()
+    }
+
+    pub fn selected(&self) -> BusSelection {
+        self.selected
+    }
+
+    /// Returns true if the node runs on the nominal bus.
+    pub fn is_nominal(&self) -> bool {
+        self.selected == self.nominal
+    }
+
+    pub fn health(&self, bus: BusSelection) -> BusHealth {
+        self.health[Self::index(bus)]
+    }
+
+    /// Returns the switches from oldest to newest, up to `MAX_SWITCH_HISTORY`.
+    pub fn history(&self) -> Vec<BusSwitch> {
+        self.history.iter().copied().collect()
+    }
+
+    /// Resets the health metrics of both buses and the count of automatic switches.
+    pub fn reset_health(&mut self) {
+        self.health = [BusHealth::default(); 2];
+        self.automatic_switches = 0;
+    }
+
+    /// Records a heartbeat received on a bus.
+    pub fn record_heartbeat(&mut self, bus: BusSelection, now: Instant) {
+        let health = self.health_mut(bus);
+        health.heartbeats = health.heartbeats.saturating_add(1);
+        health.missed_heartbeats = 0;
+        health.last_heartbeat = Some(now);
+    }
+
+    /// Records a missed heartbeat on the selected bus. Returns the switch if the policy triggered one.
+    pub fn record_heartbeat_missed(&mut self, now: Instant) -> Option<BusSwitch> {
+        let health = self.health_mut(self.selected);
+        health.missed_heartbeats = health.missed_heartbeats.saturating_add(1);
+        let missed = health.missed_heartbeats;
+        match self.policy {
+            SwitchPolicy::HeartbeatMiss { max_missed } if missed >= max_missed => {
+                self.switch_automatically(now, SwitchReason::HeartbeatMissed)
+            }
+            _ => None,
+        }
+    }
+
+    /// Records an error reported by a bus driver. Returns the switch if the policy triggered one.
+    pub fn record_error(&mut self, bus: BusSelection, now: Instant) -> Option<BusSwitch> {
+        let health = self.health_mut(bus);
+        health.errors = health.errors.saturating_add(1);
+        self.record_recent_error(bus, now)
+    }
+
+    /// Records a frame that could not be sent on a bus. Returns the switch if the policy triggered one.
+    pub fn record_tx_failure(&mut self, bus: BusSelection, now: Instant) -> Option<BusSwitch> {
+        let health = self.health_mut(bus);
+        health.tx_failures = health.tx_failures.saturating_add(1);
+        self.record_recent_error(bus, now)
+    }
+
+    /// Switches to the other bus on command.
+    pub fn switch(&mut self, now: Instant) -> BusSwitch {
+        self.switch_to(self.selected.other(), now, SwitchReason::Manual)
+    }
+
+    /// Selects a bus on command. Returns the switch, or None if the bus was already selected.
+    pub fn select(&mut self, bus: BusSelection, now: Instant) -> Option<BusSwitch> {
+        (bus != self.selected).then(|| self.switch_to(bus, now, SwitchReason::Manual))
+    }
+
+    fn record_recent_error(&mut self, bus: BusSelection, now: Instant) -> Option<BusSwitch> {
+        let health = self.health_mut(bus);
+        health.recent_errors = health.recent_errors.saturating_add(1);
+        let recent_errors = health.recent_errors;
+        match self.policy {
+            SwitchPolicy::ErrorThreshold { max_errors } if bus == self.selected && recent_errors >= max_errors => {
+                self.switch_automatically(now, SwitchReason::ErrorThreshold)
+            }
+            _ => None,
+        }
+    }
+
+    fn switch_automatically(&mut self, now: Instant, reason: SwitchReason) -> Option<BusSwitch> {
+        if self.max_switches.is_some_and(|max_switches| self.automatic_switches >= max_switches) {
+            return None;
+        }
+        self.automatic_switches += 1;
+        Some(self.switch_to(self.selected.other(), now, reason))
+    }
+
+    fn switch_to(&mut self, bus: BusSelection, now: Instant, reason: SwitchReason) -> BusSwitch {
+        let switch = BusSwitch {
+            time: now,
+            from: self.selected,
+            to: bus,
+            reason,
+        };
+        self.selected = bus;
+        let health = self.health_mut(bus);
+        health.recent_errors = 0;
+        health.missed_heartbeats = 0;
+        if self.history.len() == MAX_SWITCH_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(switch);
+        switch
+    }
+
+    fn health_mut(&mut self, bus: BusSelection) -> &mut BusHealth {
+        &mut self.health[Self::index(bus)]
+    }
+
+    fn index(bus: BusSelection) -> usize {
+        match bus {
+            BusSelection::A => 0,
+            BusSelection::B => 1,
+        }
+    }
+}
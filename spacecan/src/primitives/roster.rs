@@ -0,0 +1,128 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use super::network::BusSelection;
+use super::scheduler::{Duration, Instant};
+
+/// Bus a responder was last heard on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RosterEntry {
+    pub bus: BusSelection,
+    pub last_seen: Instant,
+}
+
+/// State of a commanded bus switchover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchoverStatus {
+    /// Some responders have not been heard on the new bus yet, and the deadline has not passed.
+    InProgress,
+    /// All responders were heard on the new bus.
+    Completed,
+    /// The deadline passed before all responders were heard on the new bus.
+    Failed,
+}
+
+/// Commanded switchover of all responders to another bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Switchover {
+    pub target: BusSelection,
+    pub started: Instant,
+    pub deadline: Instant,
+    pending: BTreeSet<u32>,
+}
+
+impl Switchover {
+    /// Returns the responders not yet heard on the new bus, ordered by node ID.
+    pub fn pending(&self) -> Vec<u32> {
+        self.pending.iter().copied().collect()
+    }
+
+    pub fn status(&self, now: Instant) -> SwitchoverStatus {
+        if self.pending.is_empty() {
+            SwitchoverStatus::Completed
+        } else if now > self.deadline {
+            SwitchoverStatus::Failed
+        } else {
+            SwitchoverStatus::InProgress
+        }
+    }
+}
+
+/// Controller-side record of which bus each responder is listening on.
+///
+/// In cold redundancy the controller selects the bus by sending heartbeats
+/// only on it, and responders follow once they miss the heartbeats on
+/// their bus. Responders only transmit on the bus they listen on, so the
+/// bus a responder's frames arrive on shows where it is. A switchover
+/// completes when every expected or known responder was heard on the new
+/// bus after the switch.
+#[derive(Debug, Clone, Default)]
+pub struct NodeRoster {
+    nodes: BTreeMap<u32, RosterEntry>,
+    expected: BTreeSet<u32>,
+    switchover: Option<Switchover>,
+}
+
+impl NodeRoster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the responders that must follow a switchover even if they were never heard.
+    pub fn set_expected_nodes(&mut self, nodes: Vec<u32>) {
+        self.expected = nodes.into_iter().collect();
+    }
+
+    /// Records a frame from a responder received on a bus.
+    pub fn record(&mut self, node_id: u32, bus: BusSelection, now: Instant) {
+        self.nodes.insert(node_id, RosterEntry { bus, last_seen: now });
+        if let Some(switchover) = &mut self.switchover
+            && switchover.target == bus
+            && now >= switchover.started
+        {
+            switchover.pending.remove(&node_id);
+        }
+    }
+
+    pub fn entry(&self, node_id: u32) -> Option<RosterEntry> {
+        self.nodes.get(&node_id).copied()
+    }
+
+    /// Returns all responders heard so far, ordered by node ID.
+    pub fn entries(&self) -> Vec<(u32, RosterEntry)> {
+        self.nodes.iter().map(|(node_id, entry)| (*node_id, *entry)).collect()
+    }
+
+    /// Returns the responders last heard on a bus, ordered by node ID.
+    pub fn nodes_on(&self, bus: BusSelection) -> Vec<u32> {
+        self.nodes
+            .iter()
+            .filter(|(_, entry)| entry.bus == bus)
+            .map(|(node_id, _)| *node_id)
+            .collect()
+    }
+
+    /// Starts tracking a switchover to `target`, which must complete within `timeout`.
+    ///
+    /// A switchover in progress is replaced.
+    pub fn start_switchover(&mut self, target: BusSelection, now: Instant, timeout: Duration) -> &Switchover {
+        let pending = self.expected.iter().chain(self.nodes.keys()).copied().collect();
+        self.switchover.insert(Switchover {
+            target,
+            started: now,
+            deadline: now + timeout,
+            pending,
+        })
+    }
+
+    /// Returns the last switchover.
+    pub fn switchover(&self) -> Option<&Switchover> {
+        self.switchover.as_ref()
+    }
+
+    pub fn switchover_status(&self, now: Instant) -> Option<SwitchoverStatus> {
+        self.switchover.as_ref().map(|switchover| switchover.status(now))
+    }
+}
@@ -0,0 +1,302 @@
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicU64, Ordering};
+#[cfg(not(feature = "std"))]
+use core::cell::Cell;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+#[cfg(not(feature = "std"))]
+use cortex_m::interrupt::Mutex;
+#[cfg(feature = "std")]
+use std::time::Instant as StdInstant;
+
+/// Point in time on a monotonic clock, in microsecond ticks.
+pub type Instant = fugit::TimerInstantU64<1_000_000>;
+
+/// Length of time in microsecond ticks.
+pub type Duration = fugit::TimerDurationU64<1_000_000>;
+
+/// Monotonic time source driving the scheduler.
+///
+/// On firmware this wraps a hardware timer; a timer running at another
+/// rate converts its ticks with `fugit`'s `convert`.
+pub trait Clock {
+    /// Returns the current time. Successive calls never go backwards.
+    fn now(&self) -> Instant;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+/// Clock shared by the timing components of a node.
+pub type SharedClock = Arc<dyn Clock + Send + Sync>;
+
+/// Converts a `core` duration into clock ticks.
+pub fn from_core_duration(duration: core::time::Duration) -> Duration {
+    Duration::from_ticks(duration.as_micros() as u64)
+}
+
+/// Converts clock ticks into a `core` duration.
+pub fn to_core_duration(duration: Duration) -> core::time::Duration {
+    core::time::Duration::from_micros(duration.ticks())
+}
+
+/// Clock counting the time since its creation with the operating system's monotonic clock.
+#[cfg(feature = "std")]
+pub struct StdClock {
+    start: StdInstant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    pub fn new() -> Self {
+        StdClock {
+            start: StdInstant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now(&self) -> Instant {
+        Instant::from_ticks(self.start.elapsed().as_micros() as u64)
+    }
+}
+
+/// Clock that only advances when told to, for deterministic simulations and tests.
+///
+/// Share it between the scheduler and the timing components, then advance
+/// it to replay seconds or hours of operation instantly.
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct SimulatedClock {
+    micros: AtomicU64,
+}
+
+#[cfg(feature = "std")]
+impl SimulatedClock {
+    /// Creates a clock at time zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the clock.
+    pub fn advance(&self, duration: Duration) {
+        self.micros.fetch_add(duration.ticks(), Ordering::Relaxed);
+    }
+
+    /// Sets the clock to a later time; earlier times are ignored to keep it monotonic.
+    pub fn set(&self, instant: Instant) {
+        self.micros.fetch_max(instant.ticks(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for SimulatedClock {
+    fn now(&self) -> Instant {
+        Instant::from_ticks(self.micros.load(Ordering::Relaxed))
+    }
+}
+
+/// Time of the last occurrence of an event, shared between threads or with interrupts.
+pub(crate) struct InstantCell {
+    #[cfg(feature = "std")]
+    instant: Mutex<Option<Instant>>,
+    #[cfg(not(feature = "std"))]
+    instant: Mutex<Cell<Option<Instant>>>,
+}
+
+impl InstantCell {
+    pub(crate) fn new() -> Self {
+        InstantCell {
+            #[cfg(feature = "std")]
+            instant: Mutex::new(None),
+            #[cfg(not(feature = "std"))]
+            instant: Mutex::new(Cell::new(None)),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn get(&self) -> Option<Instant> {
+        *self.instant.lock().unwrap()
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn get(&self) -> Option<Instant> {
+        cortex_m::interrupt::free(|cs| self.instant.borrow(cs).get())
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn set(&self, instant: Option<Instant>) {
+        *self.instant.lock().unwrap() = instant;
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn set(&self, instant: Option<Instant>) {
+        cortex_m::interrupt::free(|cs| self.instant.borrow(cs).set(instant));
+    }
+}
+
+/// Task run by the scheduler, called with the time of the poll running it.
+///
+/// Tasks are closures, so they carry their own state between runs.
+pub type Task = Box<dyn FnMut(Instant) + Send>;
+
+/// Task registered with the scheduler.
+struct ScheduledTask {
+    id: usize,
+    due: Instant,
+    period: Option<Duration>,
+    task: Task,
+}
+
+/// Runs periodic and one-shot tasks from a polling loop.
+///
+/// Nothing runs in the background: the application calls `poll` from its
+/// main loop or a timer interrupt, on firmware and host alike, and the
+/// due tasks run in the caller's context. Tasks due in the same poll run
+/// in order of their due time, ties broken by registration order.
+///
+/// A periodic task keeps its phase: it is due at whole multiples of its
+/// period after the time it was added. If polls are late by more than a
+/// period, the task runs once and the missed runs are skipped.
+pub struct Scheduler<C: Clock> {
+    clock: C,
+    tasks: Vec<ScheduledTask>,
+    next_id: usize,
+}
+
+impl<C: Clock> Scheduler<C> {
+    /// Creates a scheduler without tasks.
+    pub fn new(clock: C) -> Self {
+        Scheduler {
+            clock,
+            tasks: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Returns the scheduler's clock.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns the current time of the scheduler's clock.
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Registers a task running every `period`, first one period from now.
+    ///
+    /// Returns an ID for removing the task.
+    pub fn add_periodic(&mut self, period: Duration, task: Task) -> usize {
+        self.add_periodic_with_delay(period, period, task)
+    }
+
+    /// Registers a task running every `period`, first after `delay`.
+    ///
+    /// Returns an ID for removing the task.
+    pub fn add_periodic_with_delay(&mut self, delay: Duration, period: Duration, task: Task) -> usize {
+        // A zero period would make the task due forever.
+        let period = period.max(Duration::from_ticks(1));
+        self.add(delay, Some(period), task)
+    }
+
+    /// Registers a task running once after `delay`.
+    ///
+    /// Returns an ID for removing the task before it ran.
+    pub fn add_once(&mut self, delay: Duration, task: Task) -> usize {
+        self.add(delay, None, task)
+    }
+
+    fn add(&mut self, delay: Duration, period: Option<Duration>, task: Task) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.push(ScheduledTask {
+            id,
+            due: self.clock.now() + delay,
+            period,
+            task,
+        });
+        id
+    }
+
+    /// Removes a task. Returns true if it was registered.
+    pub fn remove(&mut self, id: usize) -> bool {
+        let length = self.tasks.len();
+        self.tasks.retain(|task| task.id != id);
+        self.tasks.len() != length
+    }
+
+    /// Returns true if the task is registered; one-shot tasks are removed after running.
+    pub fn contains(&self, id: usize) -> bool {
+        self.tasks.iter().any(|task| task.id == id)
+    }
+
+    /// Returns the number of registered tasks.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns true if no tasks are registered.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Returns the earliest due time of all tasks, for sleeping until the next poll.
+    pub fn next_due(&self) -> Option<Instant> {
+        self.tasks.iter().map(|task| task.due).min()
+    }
+
+    /// Runs the tasks that are due and returns their number.
+    ///
+    /// Each task runs at most once per poll.
+    pub fn poll(&mut self) -> usize {
+        let now = self.clock.now();
+        let mut due: Vec<(Instant, usize)> = self
+            .tasks
+            .iter()
+            .filter(|task| task.due <= now)
+            .map(|task| (task.due, task.id))
+            .collect();
+        due.sort_unstable();
+
+        for &(_, id) in &due {
+            let Some(index) = self.tasks.iter().position(|task| task.id == id) else {
+                continue;
+            };
+            let task = &mut self.tasks[index];
+            (task.task)(now);
+            match task.period {
+                Some(period) => {
+                    let missed = (now - task.due).ticks() / period.ticks();
+                    task.due += Duration::from_ticks(period.ticks() * (missed + 1));
+                }
+                None => {
+                    self.tasks.remove(index);
+                }
+            }
+        }
+        due.len()
+    }
+}
@@ -0,0 +1,195 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+
+use super::network::BusSelection;
+use super::scheduler::Instant;
+
+/// Number of function codes: the upper four bits of the 11-bit CAN ID.
+pub const FUNCTION_CODES: usize = 16;
+
+/// Default bit rate of a SpaceCAN bus.
+pub const DEFAULT_BITRATE: u32 = 1_000_000;
+
+const FUNCTION_MASK: u32 = 0x780;
+const NODE_MASK: u32 = 0x07F;
+
+/// Returns the function code of a CAN ID, e.g. 5 for telecommands (0x280).
+pub fn function_code(can_id: u32) -> usize {
+    ((can_id & FUNCTION_MASK) >> 7) as usize
+}
+
+/// Returns the bits a standard data frame occupies on the bus, including worst-case stuff bits.
+///
+/// A frame with 11-bit identifier has 47 bits of overhead including the
+/// interframe space; 34 of them and the data are subject to bit stuffing,
+/// which inserts at most one bit per four.
+pub fn frame_bits(data_length: usize) -> u32 {
+    let data_bits = 8 * data_length as u32;
+    47 + data_bits + (34 + data_bits - 1) / 4
+}
+
+/// Frames and bytes sent and received.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrafficCounters {
+    pub frames_sent: u32,
+    pub bytes_sent: u32,
+    pub frames_received: u32,
+    pub bytes_received: u32,
+}
+
+impl TrafficCounters {
+    fn record_sent(&mut self, data_length: usize) {
+        self.frames_sent = self.frames_sent.wrapping_add(1);
+        self.bytes_sent = self.bytes_sent.wrapping_add(data_length as u32);
+    }
+
+    fn record_received(&mut self, data_length: usize) {
+        self.frames_received = self.frames_received.wrapping_add(1);
+        self.bytes_received = self.bytes_received.wrapping_add(data_length as u32);
+    }
+}
+
+/// Error counters of a CAN controller, as provided by the bus backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ControllerErrorCounters {
+    /// Transmit error counter (TEC).
+    pub transmit_errors: u8,
+    /// Receive error counter (REC).
+    pub receive_errors: u8,
+    /// Times the controller went bus-off.
+    pub bus_off_events: u32,
+}
+
+/// Statistics of one bus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusStatistics {
+    /// Traffic per function code, indexed by `function_code`.
+    pub by_function: [TrafficCounters; FUNCTION_CODES],
+    pub send_failures: u32,
+    /// Bits sent and received, including frame overhead.
+    pub bits: u64,
+    /// Share of the bus capacity used since the statistics were reset, from 0 to 1.
+    pub bus_load: f32,
+    /// Error counters of the CAN controller, if the backend provides them.
+    pub controller: Option<ControllerErrorCounters>,
+}
+
+impl Default for BusStatistics {
+    fn default() -> Self {
+        BusStatistics {
+            by_function: [TrafficCounters::default(); FUNCTION_CODES],
+            send_failures: 0,
+            bits: 0,
+            bus_load: 0.0,
+            controller: None,
+        }
+    }
+}
+
+impl BusStatistics {
+    /// Returns the traffic summed over all function codes.
+    pub fn total(&self) -> TrafficCounters {
+        self.by_function.iter().fold(TrafficCounters::default(), |total, counters| TrafficCounters {
+            frames_sent: total.frames_sent.wrapping_add(counters.frames_sent),
+            bytes_sent: total.bytes_sent.wrapping_add(counters.bytes_sent),
+            frames_received: total.frames_received.wrapping_add(counters.frames_received),
+            bytes_received: total.bytes_received.wrapping_add(counters.bytes_received),
+        })
+    }
+}
+
+/// Statistics of the traffic to and from one node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeStatistics {
+    pub traffic: TrafficCounters,
+    pub send_failures: u32,
+    /// Packets from the node that could not be reassembled from their frames.
+    pub reassembly_errors: u32,
+}
+
+/// Snapshot of the traffic statistics of a network.
+///
+/// Frames are attributed to the node ID in the lower seven bits of their
+/// CAN ID; node 0 holds the controller's broadcasts such as heartbeat,
+/// sync and time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkStatistics {
+    pub buses: [BusStatistics; 2],
+    pub nodes: BTreeMap<u32, NodeStatistics>,
+    /// Bit rate used for the bus load estimate.
+    pub bitrate: u32,
+    /// Time of the last reset.
+    pub since: Instant,
+    /// Time of the snapshot.
+    pub at: Instant,
+}
+
+impl NetworkStatistics {
+    pub fn new(bitrate: u32, now: Instant) -> Self {
+        NetworkStatistics {
+            buses: [BusStatistics::default(), BusStatistics::default()],
+            nodes: BTreeMap::new(),
+            bitrate,
+            since: now,
+            at: now,
+        }
+    }
+
+    pub fn bus(&self, bus: BusSelection) -> &BusStatistics {
+        &self.buses[bus as usize]
+    }
+
+    pub fn node(&self, node_id: u32) -> NodeStatistics {
+        self.nodes.get(&node_id).copied().unwrap_or_default()
+    }
+
+    /// Records a frame sent on a bus.
+    pub fn record_sent(&mut self, bus: BusSelection, can_id: u32, data_length: usize) {
+        let statistics = &mut self.buses[bus as usize];
+        statistics.by_function[function_code(can_id)].record_sent(data_length);
+        statistics.bits += frame_bits(data_length) as u64;
+        self.node_mut(can_id).traffic.record_sent(data_length);
+    }
+
+    /// Records a frame received on a bus.
+    pub fn record_received(&mut self, bus: BusSelection, can_id: u32, data_length: usize) {
+        let statistics = &mut self.buses[bus as usize];
+        statistics.by_function[function_code(can_id)].record_received(data_length);
+        statistics.bits += frame_bits(data_length) as u64;
+        self.node_mut(can_id).traffic.record_received(data_length);
+    }
+
+    /// Records a frame that could not be sent on a bus.
+    pub fn record_send_failure(&mut self, bus: BusSelection, can_id: u32) {
+        let statistics = &mut self.buses[bus as usize];
+        statistics.send_failures = statistics.send_failures.wrapping_add(1);
+        let node = self.node_mut(can_id);
+        node.send_failures = node.send_failures.wrapping_add(1);
+    }
+
+    /// Records a packet from a node that could not be reassembled.
+    pub fn record_reassembly_error(&mut self, node_id: u32) {
+        let node = self.nodes.entry(node_id & NODE_MASK).or_default();
+        node.reassembly_errors = node.reassembly_errors.wrapping_add(1);
+    }
+
+    /// Returns a copy taken at `now`, with the bus load computed over the time since the last reset.
+    pub fn snapshot(&self, now: Instant) -> Self {
+        let mut snapshot = self.clone();
+        snapshot.at = now;
+        let capacity = self.bitrate as f64 * (now - self.since).ticks() as f64 / 1_000_000.0;
+        for bus in &mut snapshot.buses {
+            bus.bus_load = if capacity > 0.0 {
+                (bus.bits as f64 / capacity).min(1.0) as f32
+            } else {
+                0.0
+            };
+        }
+        snapshot
+    }
+
+    fn node_mut(&mut self, can_id: u32) -> &mut NodeStatistics {
+        self.nodes.entry(can_id & NODE_MASK).or_default()
+    }
+}
@@ -2,77 +2,75 @@
 
 extern crate alloc;
 
+use alloc::boxed::Box;
 use alloc::sync::Arc;
-#[cfg(feature = "std")]
-use std::sync::Mutex;
-#[cfg(not(feature = "std"))]
-use cortex_m::interrupt::Mutex;
+use alloc::vec::Vec;
 
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
 use core::time::Duration;
-#[cfg(feature = "std")]
-use std::thread;
-#[cfg(feature = "std")]
-use std::time::Instant;
 
 use super::can_frame::{CanFrame, CanFrameError};
+#[cfg(feature = "std")]
+use super::scheduler::StdClock;
+use super::scheduler::{self, Clock, InstantCell, Scheduler, SharedClock};
 use crate::primitives::network::Parent;
 
 const ID_SYNC: u32 = 0x080;
 
-// Timer struct for periodic sync frame transmission
-struct Timer {
-    period: Duration,
-    callback: Arc<dyn Fn() + Send + Sync>,
-    running: Arc<Mutex<bool>>,
+/// Sync counter carried as the single data byte of the sync frame.
+///
+/// As in CANopen, the counter runs from 1 up to the overflow value and then
+/// starts over at 1. Clones share the counter value.
+#[derive(Debug, Clone)]
+pub struct SyncCounter {
+    overflow: u8,
+    value: Arc<AtomicU8>,
 }
 
-impl Timer {
-    fn new(period: Duration, callback: Arc<dyn Fn() + Send + Sync>) -> Self {
-        Timer {
-            period,
-            callback,
-            running: Arc::new(Mutex::new(false)),
+impl SyncCounter {
+    /// Creates a counter with the given overflow value (at least 2).
+    pub fn new(overflow: u8) -> Self {
+        SyncCounter {
+            overflow: overflow.max(2),
+            value: Arc::new(AtomicU8::new(0)),
         }
     }
 
-    #[cfg(feature = "std")]
-    fn start(&self) {
-        let running = Arc::clone(&self.running);
-        let callback = Arc::clone(&self.callback);
-        let period = self.period;
-        *running.lock().unwrap() = true;
-
-        thread::spawn(move || {
-            while *running.lock().unwrap() {
-                thread::sleep(period);
-                callback();
-            }
-        });
+    /// Returns the overflow value.
+    pub fn overflow(&self) -> u8 {
+        self.overflow
     }
 
-    #[cfg(not(feature = "std"))]
-    fn start(&self) {
-        // No-op or alternative implementation for no_std
+    /// Returns the counter value of the last sync frame, if one was built.
+    pub fn value(&self) -> Option<u8> {
+        let value = self.value.load(Ordering::Relaxed);
+        (value != 0).then_some(value)
     }
 
-    #[cfg(feature = "std")]
-    fn stop(&self) {
-        *self.running.lock().unwrap() = false;
+    /// Advances the counter and returns the new value.
+    pub fn next(&self) -> u8 {
+        let overflow = self.overflow;
+        let advance = |value: u8| if value >= overflow { 1 } else { value + 1 };
+        let previous = self
+            .value
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |value| Some(advance(value)))
+            .unwrap_or_default();
+        advance(previous)
     }
 
-    #[cfg(not(feature = "std"))]
-    fn stop(&self) {
-        // no_std alternative implementation if needed
+    /// Builds the sync frame carrying the next counter value.
+    pub fn frame(&self) -> Result<CanFrame, CanFrameError> {
+        CanFrame::new(ID_SYNC, Some(alloc::vec![self.next()]))
     }
 }
 
 // SyncProducer struct to send periodic synchronization frames
 pub struct SyncProducer {
     parent: Arc<dyn Parent>,
-    running: bool,
-    timer: Option<Timer>,
-    period: Option<Duration>,
+    task: Option<usize>,
+    period: Option<scheduler::Duration>,
     can_frame: CanFrame,
+    counter: Option<SyncCounter>,
 }
 
 impl SyncProducer {
@@ -80,84 +78,335 @@ impl SyncProducer {
         let can_frame = CanFrame::new(ID_SYNC, None)?;
         Ok(SyncProducer {
             parent,
-            running: false,
-            timer: None,
+            task: None,
             period: None,
             can_frame,
+            counter: None,
         })
     }
 
+    /// Enables the sync counter with the given overflow value (at least 2), or disables it.
+    ///
+    /// The counter restarts at 1. A running producer picks up the change when restarted.
+    pub fn set_counter(&mut self, overflow: Option<u8>) {
+        self.counter = overflow.map(SyncCounter::new);
+    }
+
+    /// Returns the counter value of the last sent sync, if the counter is enabled and a sync was sent.
+    pub fn counter(&self) -> Option<u8> {
+        self.counter.as_ref()?.value()
+    }
+
     pub fn send(&self) -> Result<(), CanFrameError> {
-        self.parent.send(&self.can_frame)
+        match &self.counter {
+            Some(counter) => self.parent.send(&counter.frame()?),
+            None => self.parent.send(&self.can_frame),
+        }
     }
 
-    pub fn start(&mut self, period: Duration) {
+    /// Starts sending syncs periodically with the given period, driven by the scheduler's polls.
+    pub fn start<C: Clock>(&mut self, scheduler: &mut Scheduler<C>, period: scheduler::Duration) {
+        self.stop(scheduler);
         self.period = Some(period);
-        if self.running {
-            self.stop();
-        }
-        self.running = true;
         let parent_clone = Arc::clone(&self.parent);
         let frame_clone = self.can_frame.clone();
+        let counter_clone = self.counter.clone();
 
-        let timer = Timer::new(period, Arc::new(move || {
-            let _ = parent_clone.send(&frame_clone);
-        }));
-        self.timer = Some(timer);
-        self.timer.as_ref().unwrap().start();
+        self.task = Some(scheduler.add_periodic(period, Box::new(move |_| {
+            match &counter_clone {
+                Some(counter) => {
+                    if let Ok(frame) = counter.frame() {
+                        let _ = parent_clone.send(&frame);
+                    }
+                }
+                None => {
+                    let _ = parent_clone.send(&frame_clone);
+                }
+            }
+        })));
     }
 
-    pub fn stop(&mut self) {
-        self.running = false;
-        if let Some(timer) = self.timer.take() {
-            timer.stop();
+    pub fn stop<C: Clock>(&mut self, scheduler: &mut Scheduler<C>) {
+        if let Some(task) = self.task.take() {
+            scheduler.remove(task);
         }
     }
+
+    /// Returns true if syncs are sent periodically.
+    pub fn is_running(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Returns the period of the last start, if any.
+    pub fn period(&self) -> Option<scheduler::Duration> {
+        self.period
+    }
 }
 
 // SyncConsumer struct to track received sync frames
 pub struct SyncConsumer {
-    #[cfg(feature = "std")]
-    last_received: Arc<Mutex<Option<Instant>>>,
-    #[cfg(not(feature = "std"))]
-    last_received: Arc<Mutex<Option<()>>>,
+    clock: SharedClock,
+    last_received: InstantCell,
     timeout: Duration,
+    /// Counter of the last received sync; 0 until a sync with a counter arrived.
+    counter: AtomicU8,
+    counter_overflow: Option<u8>,
+    missed: AtomicU32,
+    window: Option<Duration>,
 }
 
 impl SyncConsumer {
+    /// Creates a consumer timed by the system clock.
+    #[cfg(feature = "std")]
     pub fn new(timeout: Duration) -> Self {
+        Self::with_clock(timeout, Arc::new(StdClock::new()))
+    }
+
+    /// Creates a consumer timed by the given clock.
+    pub fn with_clock(timeout: Duration, clock: SharedClock) -> Self {
         SyncConsumer {
-            #[cfg(feature = "std")]
-            last_received: Arc::new(Mutex::new(None)),
-            #[cfg(not(feature = "std"))]
-            last_received: Arc::new(Mutex::new(None)),
+            clock,
+            last_received: InstantCell::new(),
             timeout,
+            counter: AtomicU8::new(0),
+            counter_overflow: None,
+            missed: AtomicU32::new(0),
+            window: None,
         }
     }
 
-    #[cfg(feature = "std")]
-    pub fn receive_sync(&self) {
-        let mut last_received = self.last_received.lock().unwrap();
-        *last_received = Some(Instant::now());
+    /// Sets the overflow value of the producer's sync counter, enabling missed-sync detection.
+    pub fn set_counter_overflow(&mut self, overflow: Option<u8>) {
+        self.counter_overflow = overflow.map(|overflow| overflow.max(2));
+    }
+
+    /// Sets the synchronous window: the time after a sync during which synchronous data may be sent.
+    pub fn set_window(&mut self, window: Option<Duration>) {
+        self.window = window;
+    }
+
+    /// Returns the synchronous window, if one is configured.
+    pub fn window(&self) -> Option<Duration> {
+        self.window
+    }
+
+    /// Handles a received sync frame with its data, which holds the sync counter if enabled.
+    ///
+    /// With a known counter overflow, gaps in the counter are counted as missed syncs.
+    pub fn receive_sync_frame(&self, data: &[u8]) {
+        self.receive_sync();
+        let Some(&counter) = data.first() else {
+            return;
+        };
+        let previous = self.counter.swap(counter, Ordering::Relaxed);
+        if let Some(overflow) = self.counter_overflow
+            && previous != 0
+        {
+            let expected = if previous >= overflow { 1 } else { previous + 1 };
+            let gap = (counter as i32 - expected as i32).rem_euclid(overflow as i32);
+            self.missed.fetch_add(gap as u32, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the counter of the last received sync, if it carried one.
+    pub fn counter(&self) -> Option<u8> {
+        let counter = self.counter.load(Ordering::Relaxed);
+        (counter != 0).then_some(counter)
+    }
+
+    /// Returns the number of syncs missed according to the sync counter.
+    pub fn missed(&self) -> u32 {
+        self.missed.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if synchronous data may be sent: a sync was received and the window has not elapsed.
+    pub fn is_in_window(&self) -> bool {
+        match (self.last_received.get(), self.window) {
+            (Some(last), Some(window)) => self.clock.now() - last <= scheduler::from_core_duration(window),
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
     }
 
-    #[cfg(not(feature = "std"))]
     pub fn receive_sync(&self) {
-        // No-op or alternative implementation for no_std
+        self.last_received.set(Some(self.clock.now()));
     }
 
-    #[cfg(feature = "std")]
     pub fn check_timeout(&self) -> bool {
-        let last_received = self.last_received.lock().unwrap();
-        if let Some(last) = *last_received {
-            return last.elapsed() > self.timeout;
+        match self.last_received.get() {
+            Some(last) => self.clock.now() - last > scheduler::from_core_duration(self.timeout),
+            None => true,
         }
-        true
     }
+}
 
-    #[cfg(not(feature = "std"))]
-    pub fn check_timeout(&self) -> bool {
-        // Always timeout in no_std or implement alternative logic
-        true
+/// Action run by the sync scheduler, called with the sync count.
+pub type SyncAction = Box<dyn FnMut(u32) + Send>;
+
+/// Action registered with the sync scheduler.
+struct ScheduledSyncAction {
+    id: usize,
+    every: u32,
+    offset: u32,
+    action: SyncAction,
+}
+
+/// Runs actions on every Nth received sync frame and measures the sync timing.
+///
+/// Syncs are timestamped with the scheduler's clock. The sync count starts
+/// at zero and increases with every received sync.
+/// An action registered to run every `n` syncs with offset `k` runs when
+/// `count % n == k`. The interval between consecutive syncs is compared
+/// with the expected period: deviations are recorded as jitter, and
+/// intervals of 1.5 periods or more count the syncs missed in between.
+pub struct SyncScheduler {
+    clock: SharedClock,
+    period: Duration,
+    count: u32,
+    missed: u32,
+    last_received: Option<scheduler::Instant>,
+    last_jitter: Option<Duration>,
+    max_jitter: Duration,
+    actions: Vec<ScheduledSyncAction>,
+    next_id: usize,
+}
+
+impl SyncScheduler {
+    /// Creates a scheduler for syncs sent with the given period, timed by the system clock.
+    #[cfg(feature = "std")]
+    pub fn new(period: Duration) -> Self {
+        Self::with_clock(period, Arc::new(StdClock::new()))
+    }
+
+    /// Creates a scheduler for syncs sent with the given period, timed by the given clock.
+    pub fn with_clock(period: Duration, clock: SharedClock) -> Self {
+        SyncScheduler {
+            clock,
+            period,
+            count: 0,
+            missed: 0,
+            last_received: None,
+            last_jitter: None,
+            max_jitter: Duration::ZERO,
+            actions: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Registers an action running on every `every`-th sync, starting with the next one.
+    ///
+    /// Returns an ID for removing the action.
+    pub fn add_action(&mut self, every: u32, action: SyncAction) -> usize {
+        let offset = self.count % every.max(1);
+        self.add_action_with_offset(every, offset, action)
+    }
+
+    /// Registers an action running on the syncs whose count modulo `every` equals `offset`.
+    ///
+    /// Offsets let actions with the same rate run on different syncs. Returns
+    /// an ID for removing the action.
+    pub fn add_action_with_offset(&mut self, every: u32, offset: u32, action: SyncAction) -> usize {
+        let every = every.max(1);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.actions.push(ScheduledSyncAction {
+            id,
+            every,
+            offset: offset % every,
+            action,
+        });
+        id
+    }
+
+    /// Removes an action. Returns true if it was registered.
+    pub fn remove_action(&mut self, id: usize) -> bool {
+        let length = self.actions.len();
+        self.actions.retain(|action| action.id != id);
+        self.actions.len() != length
+    }
+
+    /// Handles a sync received now.
+    ///
+    /// Runs the due actions in registration order and returns their number.
+    pub fn received_sync(&mut self) -> usize {
+        let now = self.clock.now();
+        if let Some(last_received) = self.last_received {
+            let interval = self.since(last_received, now);
+            let periods = self.elapsed_periods(interval);
+            if periods > 1 {
+                self.missed = self.missed.saturating_add(periods - 1);
+            } else {
+                let jitter = interval.abs_diff(self.period);
+                self.last_jitter = Some(jitter);
+                self.max_jitter = self.max_jitter.max(jitter);
+            }
+        }
+        self.last_received = Some(now);
+
+        let count = self.count;
+        let mut executed = 0;
+        for scheduled in self.actions.iter_mut().filter(|scheduled| count % scheduled.every == scheduled.offset) {
+            (scheduled.action)(count);
+            executed += 1;
+        }
+        self.count = self.count.wrapping_add(1);
+        executed
+    }
+
+    /// Returns the time from `earlier` to `now`, zero if the clock went backwards.
+    fn since(&self, earlier: scheduler::Instant, now: scheduler::Instant) -> Duration {
+        now.checked_duration_since(earlier)
+            .map_or(Duration::ZERO, scheduler::to_core_duration)
+    }
+
+    /// Returns the number of whole periods in an interval, rounded to the nearest.
+    fn elapsed_periods(&self, interval: Duration) -> u32 {
+        if self.period.is_zero() {
+            return 1;
+        }
+        let periods = (interval.as_nanos() + self.period.as_nanos() / 2) / self.period.as_nanos();
+        periods.min(u32::MAX as u128) as u32
+    }
+
+    /// Returns true if no sync was received for 1.5 periods.
+    pub fn is_overdue(&self) -> bool {
+        match self.last_received {
+            Some(last_received) => self.elapsed_periods(self.since(last_received, self.clock.now())) > 1,
+            None => false,
+        }
+    }
+
+    /// Returns the expected sync period.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns the number of received syncs.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the number of syncs missed between received ones.
+    pub fn missed(&self) -> u32 {
+        self.missed
+    }
+
+    /// Returns the jitter of the last interval without missed syncs.
+    pub fn last_jitter(&self) -> Option<Duration> {
+        self.last_jitter
+    }
+
+    /// Returns the largest jitter measured.
+    pub fn max_jitter(&self) -> Duration {
+        self.max_jitter
+    }
+
+    /// Resets the sync count, missed syncs and jitter measurements.
+    pub fn reset_statistics(&mut self) {
+        self.count = 0;
+        self.missed = 0;
+        self.last_received = None;
+        self.last_jitter = None;
+        self.max_jitter = Duration::ZERO;
     }
 }
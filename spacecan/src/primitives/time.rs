@@ -0,0 +1,331 @@
+extern crate alloc;
+
+use alloc::sync::Arc;
+use core::time::Duration;
+
+use super::can_frame::{CanFrame, CanFrameError};
+use crate::primitives::network::Parent;
+
+const ID_SCET: u32 = 0x180;
+const ID_UTC: u32 = 0x200;
+
+/// Number of CUC fine time units per second (24-bit fine time).
+const FINE_PER_SECOND: u64 = 1 << 24;
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+const MILLIS_PER_DAY: u64 = 86_400_000;
+/// Largest relative rate correction the onboard clock applies (500 ppm).
+const MAX_RATE_DEVIATION: f64 = 500e-6;
+/// Fraction of the measured rate error corrected on each time update.
+const RATE_GAIN: f64 = 0.5;
+
+/// CCSDS Unsegmented Time Code with 4 octets coarse and 3 octets fine time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct CucTime {
+    /// Seconds since the epoch.
+    pub coarse: u32,
+    /// Fractions of a second in units of 2^-24 s.
+    pub fine: u32,
+}
+
+impl CucTime {
+    /// Creates a CUC time, truncating the fine time to 24 bits.
+    pub fn new(coarse: u32, fine: u32) -> Self {
+        CucTime {
+            coarse,
+            fine: fine & 0x00FF_FFFF,
+        }
+    }
+
+    /// Creates a CUC time from a count of 2^-24 s ticks.
+    pub fn from_ticks(ticks: u64) -> Self {
+        CucTime::new((ticks >> 24) as u32, ticks as u32)
+    }
+
+    /// Returns the time as a count of 2^-24 s ticks.
+    pub fn ticks(&self) -> u64 {
+        ((self.coarse as u64) << 24) | self.fine as u64
+    }
+
+    /// Creates a CUC time from a duration since the epoch.
+    pub fn from_duration(duration: Duration) -> Self {
+        let fine = (duration.subsec_nanos() as u128 * FINE_PER_SECOND as u128 / NANOS_PER_SECOND) as u32;
+        CucTime::new(duration.as_secs() as u32, fine)
+    }
+
+    /// Returns the duration since the epoch.
+    pub fn to_duration(&self) -> Duration {
+        let nanos = (self.fine as u128 * NANOS_PER_SECOND / FINE_PER_SECOND as u128) as u32;
+        Duration::new(self.coarse as u64, nanos)
+    }
+
+    /// Encodes the CCSDS T-field: coarse then fine time, most significant octet first.
+    pub fn to_bytes(&self) -> [u8; 7] {
+        let coarse = self.coarse.to_be_bytes();
+        let fine = self.fine.to_be_bytes();
+        [coarse[0], coarse[1], coarse[2], coarse[3], fine[1], fine[2], fine[3]]
+    }
+
+    /// Decodes a CCSDS T-field produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 7 {
+            return None;
+        }
+        let coarse = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let fine = u32::from_be_bytes([0, bytes[4], bytes[5], bytes[6]]);
+        Some(CucTime::new(coarse, fine))
+    }
+
+    /// Encodes the data field of a SCET frame: fine time followed by coarse time.
+    pub fn to_scet_data(&self) -> [u8; 7] {
+        let coarse = self.coarse.to_be_bytes();
+        let fine = self.fine.to_be_bytes();
+        [fine[1], fine[2], fine[3], coarse[0], coarse[1], coarse[2], coarse[3]]
+    }
+
+    /// Decodes the data field of a SCET frame.
+    pub fn from_scet_data(data: &[u8]) -> Option<Self> {
+        if data.len() < 7 {
+            return None;
+        }
+        let fine = u32::from_be_bytes([0, data[0], data[1], data[2]]);
+        let coarse = u32::from_be_bytes([data[3], data[4], data[5], data[6]]);
+        Some(CucTime::new(coarse, fine))
+    }
+}
+
+/// CCSDS Day Segmented Time Code with 16-bit day and 16-bit sub-millisecond fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct CdsTime {
+    /// Days since the epoch.
+    pub day: u16,
+    /// Milliseconds of the day.
+    pub ms_of_day: u32,
+    /// Microseconds of the millisecond.
+    pub sub_ms: u16,
+}
+
+impl CdsTime {
+    /// Creates a CDS time.
+    pub fn new(day: u16, ms_of_day: u32, sub_ms: u16) -> Self {
+        CdsTime { day, ms_of_day, sub_ms }
+    }
+
+    /// Creates a CDS time from a duration since the epoch.
+    pub fn from_duration(duration: Duration) -> Self {
+        let millis = duration.as_millis() as u64;
+        CdsTime {
+            day: (millis / MILLIS_PER_DAY) as u16,
+            ms_of_day: (millis % MILLIS_PER_DAY) as u32,
+            sub_ms: (duration.subsec_micros() % 1000) as u16,
+        }
+    }
+
+    /// Returns the duration since the epoch.
+    pub fn to_duration(&self) -> Duration {
+        Duration::from_millis(self.day as u64 * MILLIS_PER_DAY + self.ms_of_day as u64)
+            + Duration::from_micros(self.sub_ms as u64)
+    }
+
+    /// Encodes the CCSDS T-field: day, milliseconds of day, sub-milliseconds.
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let day = self.day.to_be_bytes();
+        let ms = self.ms_of_day.to_be_bytes();
+        let sub_ms = self.sub_ms.to_be_bytes();
+        [day[0], day[1], ms[0], ms[1], ms[2], ms[3], sub_ms[0], sub_ms[1]]
+    }
+
+    /// Decodes a CCSDS T-field produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 8 {
+            return None;
+        }
+        Some(CdsTime {
+            day: u16::from_be_bytes([bytes[0], bytes[1]]),
+            ms_of_day: u32::from_be_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]),
+            sub_ms: u16::from_be_bytes([bytes[6], bytes[7]]),
+        })
+    }
+
+    /// Encodes the data field of a UTC frame: sub-milliseconds, milliseconds of day, day.
+    pub fn to_utc_data(&self) -> [u8; 8] {
+        let day = self.day.to_be_bytes();
+        let ms = self.ms_of_day.to_be_bytes();
+        let sub_ms = self.sub_ms.to_be_bytes();
+        [sub_ms[0], sub_ms[1], ms[0], ms[1], ms[2], ms[3], day[0], day[1]]
+    }
+
+    /// Decodes the data field of a UTC frame.
+    pub fn from_utc_data(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+        Some(CdsTime {
+            sub_ms: u16::from_be_bytes([data[0], data[1]]),
+            ms_of_day: u32::from_be_bytes([data[2], data[3], data[4], data[5]]),
+            day: u16::from_be_bytes([data[6], data[7]]),
+        })
+    }
+}
+
+/// Time carried by a received time distribution frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFrame {
+    Scet(CucTime),
+    Utc(CdsTime),
+}
+
+/// Builds a SCET time distribution frame.
+pub fn scet_frame(time: &CucTime) -> Result<CanFrame, CanFrameError> {
+    CanFrame::new(ID_SCET, Some(time.to_scet_data().to_vec()))
+}
+
+/// Builds a UTC time distribution frame.
+pub fn utc_frame(time: &CdsTime) -> Result<CanFrame, CanFrameError> {
+    CanFrame::new(ID_UTC, Some(time.to_utc_data().to_vec()))
+}
+
+/// Decodes a SCET or UTC frame. Returns None for other frames or short data.
+pub fn decode_time_frame(can_frame: &CanFrame) -> Option<TimeFrame> {
+    match can_frame.can_id() {
+        ID_SCET => CucTime::from_scet_data(can_frame.data()).map(TimeFrame::Scet),
+        ID_UTC => CdsTime::from_utc_data(can_frame.data()).map(TimeFrame::Utc),
+        _ => None,
+    }
+}
+
+/// Converts a local monotonic duration into 2^-24 s ticks.
+fn duration_to_ticks(duration: Duration) -> u64 {
+    (duration.as_nanos() * FINE_PER_SECOND as u128 / NANOS_PER_SECOND) as u64
+}
+
+/// Responder-side onboard clock disciplined by received time frames.
+///
+/// Between updates the onboard time is extrapolated from the local
+/// monotonic time (for example the uptime). Each received SCET frame steps
+/// the onboard time to the master time and trims the clock rate towards
+/// the master's rate, so extrapolation drifts less over time.
+#[derive(Debug, Clone)]
+pub struct OnboardClock {
+    // Local time and onboard ticks of the last SCET update.
+    reference: Option<(Duration, u64)>,
+    utc_reference: Option<(Duration, CdsTime)>,
+    rate: f64,
+    last_error: Option<f64>,
+}
+
+impl Default for OnboardClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OnboardClock {
+    /// Creates an unsynchronized clock.
+    pub fn new() -> Self {
+        OnboardClock {
+            reference: None,
+            utc_reference: None,
+            rate: 1.0,
+            last_error: None,
+        }
+    }
+
+    /// Returns true once at least one SCET frame was received.
+    pub fn is_synchronized(&self) -> bool {
+        self.reference.is_some()
+    }
+
+    /// Returns the current rate correction in onboard seconds per local second.
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Returns the offset in seconds between received and extrapolated time at the last update.
+    pub fn last_error(&self) -> Option<f64> {
+        self.last_error
+    }
+
+    /// Disciplines the clock with a received SCET time.
+    pub fn discipline(&mut self, time: CucTime, local_now: Duration) {
+        let received = time.ticks();
+        if let Some((reference_local, reference_ticks)) = self.reference {
+            let elapsed = duration_to_ticks(local_now.saturating_sub(reference_local));
+            if elapsed > 0 {
+                let predicted = reference_ticks as f64 + elapsed as f64 * self.rate;
+                self.last_error = Some((received as f64 - predicted) / FINE_PER_SECOND as f64);
+                let measured = (received as f64 - reference_ticks as f64) / elapsed as f64;
+                let rate = self.rate + (measured - self.rate) * RATE_GAIN;
+                self.rate = rate.clamp(1.0 - MAX_RATE_DEVIATION, 1.0 + MAX_RATE_DEVIATION);
+            }
+        }
+        self.reference = Some((local_now, received));
+    }
+
+    /// Disciplines the UTC estimate with a received UTC time.
+    pub fn discipline_utc(&mut self, time: CdsTime, local_now: Duration) {
+        self.utc_reference = Some((local_now, time));
+    }
+
+    /// Returns the onboard SCET at the given local time, if synchronized.
+    pub fn time_at(&self, local_now: Duration) -> Option<CucTime> {
+        let (reference_local, reference_ticks) = self.reference?;
+        let elapsed = duration_to_ticks(local_now.saturating_sub(reference_local));
+        Some(CucTime::from_ticks(reference_ticks + (elapsed as f64 * self.rate) as u64))
+    }
+
+    /// Returns the onboard UTC at the given local time, if a UTC frame was received.
+    pub fn utc_at(&self, local_now: Duration) -> Option<CdsTime> {
+        let (reference_local, reference_time) = self.utc_reference?;
+        let elapsed = local_now.saturating_sub(reference_local).as_secs_f64() * self.rate;
+        Some(CdsTime::from_duration(reference_time.to_duration() + Duration::from_secs_f64(elapsed)))
+    }
+}
+
+/// Controller-side producer of SCET and UTC time distribution frames.
+pub struct TimeProducer {
+    parent: Arc<dyn Parent>,
+    period: Option<Duration>,
+    last_sent: Option<Duration>,
+}
+
+impl TimeProducer {
+    /// Creates a new TimeProducer. Periodic distribution is off until a period is set.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        TimeProducer {
+            parent,
+            period: None,
+            last_sent: None,
+        }
+    }
+
+    /// Sets the distribution period, or None to only send on request.
+    pub fn set_period(&mut self, period: Option<Duration>) {
+        self.period = period;
+        self.last_sent = None;
+    }
+
+    /// Sends a SCET frame.
+    pub fn send_scet(&self, time: &CucTime) -> Result<(), CanFrameError> {
+        self.parent.send(&scet_frame(time)?)
+    }
+
+    /// Sends a UTC frame.
+    pub fn send_utc(&self, time: &CdsTime) -> Result<(), CanFrameError> {
+        self.parent.send(&utc_frame(time)?)
+    }
+
+    /// Sends a SCET frame if the distribution period has elapsed at the given local time.
+    ///
+    /// Returns true if a frame was sent.
+    pub fn poll(&mut self, local_now: Duration, time: &CucTime) -> Result<bool, CanFrameError> {
+        let Some(period) = self.period else {
+            return Ok(false);
+        };
+        if self.last_sent.is_some_and(|last_sent| local_now.saturating_sub(last_sent) < period) {
+            return Ok(false);
+        }
+        self.send_scet(time)?;
+        self.last_sent = Some(local_now);
+        Ok(true)
+    }
+}
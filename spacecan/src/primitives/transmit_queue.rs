@@ -0,0 +1,252 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use super::can_frame::{CanFrame, CanFrameError};
+
+/// Default number of frames a transmit queue holds.
+pub const DEFAULT_TRANSMIT_QUEUE_CAPACITY: usize = 64;
+
+const ID_SYNC: u32 = 0x080;
+const ID_HEARTBEAT: u32 = 0x700;
+
+/// What a full transmit queue does with further frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Refuse the frame with `CanFrameError::QueueFull`, so the sender can retry later.
+    Reject,
+    /// Discard the frame being queued.
+    DropNewest,
+    /// Discard the queued frame with the lowest priority, or the new frame if its priority is not higher.
+    DropLowestPriority,
+}
+
+/// Overflow counters of a transmit queue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OverflowReport {
+    /// Frames refused under `OverflowPolicy::Reject`.
+    pub rejected: u32,
+    /// Frames discarded under one of the drop policies.
+    pub dropped: u32,
+    /// CAN ID of the last discarded frame.
+    pub last_dropped: Option<u32>,
+    /// Most frames queued at once.
+    pub high_water_mark: usize,
+}
+
+// Sync and heartbeat go first, since late heartbeats make responders switch buses
+fn priority_class(can_id: u32) -> u8 {
+    match can_id {
+        ID_SYNC | ID_HEARTBEAT => 0,
+        _ => 1,
+    }
+}
+
+/// Orders frames by priority class, then CAN ID, then arrival.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct QueueKey {
+    class: u8,
+    can_id: u32,
+    sequence: u64,
+}
+
+/// Queued frame with the packet it belongs to.
+#[derive(Debug, Clone)]
+struct QueuedFrame {
+    packet: u64,
+    frame: CanFrame,
+}
+
+/// Bounded queue of frames waiting for transmission.
+///
+/// Frames leave the queue in CAN arbitration order, lowest identifier
+/// first, except that sync and heartbeat frames precede all others.
+/// Frames with the same identifier keep their order, so the frames of a
+/// packet are sent in sequence. Packets are queued and discarded as a
+/// whole, so that no partial packet is sent.
+#[derive(Debug, Clone)]
+pub struct TransmitQueue {
+    frames: BTreeMap<QueueKey, QueuedFrame>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    sequence: u64,
+    packets: u64,
+    /// Packet whose first frames were sent, which must not be discarded.
+    sending: Option<u64>,
+    report: OverflowReport,
+}
+
+impl Default for TransmitQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRANSMIT_QUEUE_CAPACITY, OverflowPolicy::Reject)
+    }
+}
+
+impl TransmitQueue {
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        TransmitQueue {
+            frames: BTreeMap::new(),
+            capacity,
+            policy,
+            sequence: 0,
+            packets: 0,
+            sending: None,
+            report: OverflowReport::default(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: OverflowPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn report(&self) -> OverflowReport {
+        self.report
+    }
+
+    pub fn reset_report(&mut self) {
+        self.report = OverflowReport {
+            high_water_mark: self.frames.len(),
+            ..OverflowReport::default()
+        };
+    }
+
+    /// Queues a single-frame packet. Returns the frames discarded to make room, see `push_all`.
+    pub fn push(&mut self, frame: CanFrame) -> Result<Vec<CanFrame>, CanFrameError> {
+        self.push_all(core::slice::from_ref(&frame))
+    }
+
+    /// Queues the frames of a packet. Returns the frames discarded to make room, if any.
+    ///
+    /// A packet that does not fit is never queued in part. Under
+    /// `OverflowPolicy::Reject` it is refused, and under
+    /// `OverflowPolicy::DropNewest` it is discarded as a whole. Under
+    /// `OverflowPolicy::DropLowestPriority` whole queued packets with a lower
+    /// priority than the packet are discarded to make room for it if that
+    /// frees enough space; otherwise the packet is discarded.
+    pub fn push_all(&mut self, frames: &[CanFrame]) -> Result<Vec<CanFrame>, CanFrameError> {
+        let free = self.capacity.saturating_sub(self.frames.len());
+        let needed = frames.len().saturating_sub(free);
+        let dropped = if needed == 0 {
+            Vec::new()
+        } else {
+            let evicted = match self.policy {
+                OverflowPolicy::Reject => {
+                    self.report.rejected = self.report.rejected.wrapping_add(frames.len() as u32);
+                    return Err(CanFrameError::QueueFull);
+                }
+                OverflowPolicy::DropNewest => None,
+                OverflowPolicy::DropLowestPriority => self.evict_lower_priority(frames, needed),
+            };
+            let Some(evicted) = evicted else {
+                self.record_dropped(frames);
+                return Ok(frames.to_vec());
+            };
+            evicted
+        };
+        self.packets += 1;
+        for frame in frames {
+            let key = self.next_key(frame.can_id());
+            self.insert(key, frame.clone());
+        }
+        self.record_dropped(&dropped);
+        Ok(dropped)
+    }
+
+    /// Returns the frame to be sent next.
+    pub fn front(&self) -> Option<&CanFrame> {
+        self.frames.first_key_value().map(|(_, queued)| &queued.frame)
+    }
+
+    /// Removes and returns the frame to be sent next.
+    pub fn pop(&mut self) -> Option<CanFrame> {
+        let (_, queued) = self.frames.pop_first()?;
+        let rest_queued = self.frames.values().any(|other| other.packet == queued.packet);
+        self.sending = rest_queued.then_some(queued.packet);
+        Some(queued.frame)
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.sending = None;
+    }
+
+    /// Discards whole packets with a lower priority than all of the given
+    /// frames, lowest priority first, until `needed` frames are free.
+    ///
+    /// Returns the discarded frames, or None without discarding anything if
+    /// the lower priority packets do not free enough space.
+    fn evict_lower_priority(&mut self, frames: &[CanFrame], needed: usize) -> Option<Vec<CanFrame>> {
+        let (class, can_id) = frames.iter().map(|frame| (priority_class(frame.can_id()), frame.can_id())).max()?;
+        // Queued frames with the same key were queued earlier and go first
+        let bound = QueueKey {
+            class,
+            can_id,
+            sequence: u64::MAX,
+        };
+        let mut higher_priority = BTreeSet::new();
+        higher_priority.extend(self.sending);
+        for (_, queued) in self.frames.range(..bound) {
+            higher_priority.insert(queued.packet);
+        }
+        let mut victims = BTreeSet::new();
+        let mut freed = 0;
+        for (_, queued) in self.frames.range(bound..).rev() {
+            if freed >= needed {
+                break;
+            }
+            if higher_priority.contains(&queued.packet) || !victims.insert(queued.packet) {
+                continue;
+            }
+            freed += self.frames.values().filter(|other| other.packet == queued.packet).count();
+        }
+        if freed < needed {
+            return None;
+        }
+        let keys: Vec<QueueKey> = self
+            .frames
+            .iter()
+            .filter(|(_, queued)| victims.contains(&queued.packet))
+            .map(|(key, _)| *key)
+            .collect();
+        Some(keys.iter().filter_map(|key| self.frames.remove(key)).map(|queued| queued.frame).collect())
+    }
+
+    fn record_dropped(&mut self, dropped: &[CanFrame]) {
+        if let Some(last) = dropped.last() {
+            self.report.dropped = self.report.dropped.wrapping_add(dropped.len() as u32);
+            self.report.last_dropped = Some(last.can_id());
+        }
+    }
+
+    fn next_key(&mut self, can_id: u32) -> QueueKey {
+        self.sequence += 1;
+        QueueKey {
+            class: priority_class(can_id),
+            can_id,
+            sequence: self.sequence,
+        }
+    }
+
+    fn insert(&mut self, key: QueueKey, frame: CanFrame) {
+        let packet = self.packets;
+        self.frames.insert(key, QueuedFrame { packet, frame });
+        self.report.high_water_mark = self.report.high_water_mark.max(self.frames.len());
+    }
+}
@@ -0,0 +1,397 @@
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use std::sync::Mutex;
+
+/// Node ID of the controller, the destination of all register dump reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// Size of an encoded register value: device ID, register address and value.
+const REGISTER_VALUE_SIZE: usize = 7;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+}
+
+/// Errors raised by device access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceError {
+    /// No device is registered under the device ID.
+    UnknownDevice(u8),
+    /// The device has no register at the address.
+    UnknownRegister(u16),
+    /// The register cannot be written.
+    ReadOnly(u16),
+    /// The device is switched off.
+    Off,
+    /// The transfer on the device bus failed.
+    Bus,
+}
+
+impl fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceError::UnknownDevice(id) => write!(f, "unknown device ID {}", id),
+            DeviceError::UnknownRegister(address) => write!(f, "unknown register 0x{:04X}", address),
+            DeviceError::ReadOnly(address) => write!(f, "register 0x{:04X} is read-only", address),
+            DeviceError::Off => write!(f, "device is switched off"),
+            DeviceError::Bus => write!(f, "device bus error"),
+        }
+    }
+}
+
+/// Logical device the application exposes to the device access service.
+///
+/// Implementations wrap the I2C, SPI or GPIO driver of one device.
+pub trait Device: Send {
+    /// Switches the device on or off.
+    fn set_power(&mut self, on: bool) -> Result<(), DeviceError>;
+    /// Writes a register.
+    fn write_register(&mut self, address: u16, value: u32) -> Result<(), DeviceError>;
+    /// Reads a register.
+    fn read_register(&mut self, address: u16) -> Result<u32, DeviceError>;
+}
+
+#[derive(Debug, Default)]
+struct MockDeviceState {
+    on: bool,
+    registers: BTreeMap<u16, u32>,
+    read_only: Vec<u16>,
+    failing: bool,
+}
+
+/// Device simulating a register file, for host testing.
+///
+/// Clones share their state, so a test can keep a handle to a device it
+/// registered with the responder.
+#[derive(Debug, Clone, Default)]
+pub struct MockDevice {
+    state: Arc<Mutex<MockDeviceState>>,
+}
+
+impl MockDevice {
+    /// Creates a switched off device without registers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a writable register with an initial value.
+    pub fn with_register(self, address: u16, value: u32) -> Self {
+        self.state.lock().unwrap().registers.insert(address, value);
+        self
+    }
+
+    /// Adds a read-only register with a fixed value.
+    pub fn with_read_only_register(self, address: u16, value: u32) -> Self {
+        let mut state = self.state.lock().unwrap();
+        state.registers.insert(address, value);
+        state.read_only.push(address);
+        drop(state);
+        self
+    }
+
+    /// Makes every following access fail with a bus error, or recover.
+    pub fn set_failing(&self, failing: bool) {
+        self.state.lock().unwrap().failing = failing;
+    }
+
+    /// Returns true if the device is switched on.
+    pub fn is_on(&self) -> bool {
+        self.state.lock().unwrap().on
+    }
+
+    /// Returns the value of a register.
+    pub fn register(&self, address: u16) -> Option<u32> {
+        self.state.lock().unwrap().registers.get(&address).copied()
+    }
+}
+
+impl Device for MockDevice {
+    fn set_power(&mut self, on: bool) -> Result<(), DeviceError> {
+        let mut state = self.state.lock().unwrap();
+        if state.failing {
+            return Err(DeviceError::Bus);
+        }
+        state.on = on;
+        Ok(())
+    }
+
+    fn write_register(&mut self, address: u16, value: u32) -> Result<(), DeviceError> {
+        let mut state = self.state.lock().unwrap();
+        if state.failing {
+            return Err(DeviceError::Bus);
+        }
+        if !state.on {
+            return Err(DeviceError::Off);
+        }
+        if state.read_only.contains(&address) {
+            return Err(DeviceError::ReadOnly(address));
+        }
+        let register = state.registers.get_mut(&address).ok_or(DeviceError::UnknownRegister(address))?;
+        *register = value;
+        Ok(())
+    }
+
+    fn read_register(&mut self, address: u16) -> Result<u32, DeviceError> {
+        let state = self.state.lock().unwrap();
+        if state.failing {
+            return Err(DeviceError::Bus);
+        }
+        if !state.on {
+            return Err(DeviceError::Off);
+        }
+        state.registers.get(&address).copied().ok_or(DeviceError::UnknownRegister(address))
+    }
+}
+
+/// Value of a device register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterValue {
+    pub device_id: u8,
+    pub address: u16,
+    pub value: u32,
+}
+
+impl RegisterValue {
+    /// Creates a new register value.
+    pub fn new(device_id: u8, address: u16, value: u32) -> Self {
+        RegisterValue {
+            device_id,
+            address,
+            value,
+        }
+    }
+
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.push(self.device_id);
+        data.extend_from_slice(&self.address.to_be_bytes());
+        data.extend_from_slice(&self.value.to_be_bytes());
+    }
+
+    fn decode(data: &[u8]) -> Self {
+        RegisterValue {
+            device_id: data[0],
+            address: u16::from_be_bytes([data[1], data[2]]),
+            value: u32::from_be_bytes([data[3], data[4], data[5], data[6]]),
+        }
+    }
+}
+
+/// Callback invoked by the controller for every received register dump report.
+pub type RegisterDumpHandler = Arc<dyn Fn(u32, &[RegisterValue]) + Send + Sync>;
+
+/// Decodes a list of register values encoded as count followed by the values.
+fn decode_register_values(data: &[u8]) -> Option<Vec<RegisterValue>> {
+    let (&count, values) = data.split_first()?;
+    if values.len() < count as usize * REGISTER_VALUE_SIZE {
+        return None;
+    }
+    Some(
+        values
+            .chunks_exact(REGISTER_VALUE_SIZE)
+            .take(count as usize)
+            .map(RegisterValue::decode)
+            .collect(),
+    )
+}
+
+/// Controller for the Device Access Service.
+pub struct DeviceAccessServiceController {
+    parent: Arc<dyn Parent>,
+    /// Dumped register values by node ID, device ID and register address.
+    registers: Mutex<BTreeMap<(u32, u8, u16), u32>>,
+    dump_handler: Option<RegisterDumpHandler>,
+}
+
+impl DeviceAccessServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        DeviceAccessServiceController {
+            parent,
+            registers: Mutex::new(BTreeMap::new()),
+            dump_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every received register dump report.
+    pub fn set_dump_handler(&mut self, handler: RegisterDumpHandler) {
+        self.dump_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        if case == (2, 6) {
+            let Some(values) = decode_register_values(&data) else {
+                return;
+            };
+            if let Some(handler) = &self.dump_handler {
+                handler(node_id, &values);
+            }
+            let mut registers = self.registers.lock().unwrap();
+            for value in values {
+                registers.insert((node_id, value.device_id, value.address), value.value);
+            }
+        }
+    }
+
+    /// Returns the last dumped value of a register of a node.
+    pub fn register(&self, node_id: u32, device_id: u8, address: u16) -> Option<u32> {
+        self.registers.lock().unwrap().get(&(node_id, device_id, address)).copied()
+    }
+
+    /// Switches devices of a node on or off (2,1).
+    ///
+    /// At most 255 commands fit into one packet; further commands are dropped.
+    pub fn send_on_off(&self, node_id: u32, commands: &[(u8, bool)]) {
+        let commands = &commands[..commands.len().min(u8::MAX as usize)];
+        let mut packet_data = vec![2, 1, commands.len() as u8];
+        for (device_id, on) in commands {
+            packet_data.push(*device_id);
+            packet_data.push(*on as u8);
+        }
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Writes device registers of a node (2,4).
+    pub fn send_register_load(&self, node_id: u32, values: &[RegisterValue]) {
+        let values = &values[..values.len().min(u8::MAX as usize)];
+        let mut packet_data = vec![2, 4, values.len() as u8];
+        for value in values {
+            value.encode(&mut packet_data);
+        }
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Requests a dump of device registers of a node (2,5).
+    pub fn send_register_dump_request(&self, node_id: u32, registers: &[(u8, u16)]) {
+        let registers = &registers[..registers.len().min(u8::MAX as usize)];
+        let mut packet_data = vec![2, 5, registers.len() as u8];
+        for (device_id, address) in registers {
+            packet_data.push(*device_id);
+            packet_data.extend_from_slice(&address.to_be_bytes());
+        }
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+}
+
+/// Responder for the Device Access Service.
+pub struct DeviceAccessServiceResponder {
+    parent: Arc<dyn Parent>,
+    devices: Mutex<BTreeMap<u8, Box<dyn Device>>>,
+}
+
+impl DeviceAccessServiceResponder {
+    /// Creates a new responder without devices.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        DeviceAccessServiceResponder {
+            parent,
+            devices: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Registers a device under a device ID, replacing any previous one.
+    pub fn register_device(&self, device_id: u8, device: Box<dyn Device>) {
+        self.devices.lock().unwrap().insert(device_id, device);
+    }
+
+    /// Removes a device. Returns true if it was registered.
+    pub fn unregister_device(&self, device_id: u8) -> bool {
+        self.devices.lock().unwrap().remove(&device_id).is_some()
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    ///
+    /// Failing commands are skipped; the remaining commands of the packet
+    /// are still executed.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+        let Some((&count, entries)) = data.split_first() else {
+            return;
+        };
+        let count = count as usize;
+
+        match case {
+            (2, 1) => {
+                for entry in entries.chunks_exact(2).take(count) {
+                    let _ = self.set_power(entry[0], entry[1] != 0);
+                }
+            }
+            (2, 4) => {
+                for entry in entries.chunks_exact(REGISTER_VALUE_SIZE).take(count) {
+                    let value = RegisterValue::decode(entry);
+                    let _ = self.load_register(value.device_id, value.address, value.value);
+                }
+            }
+            (2, 5) => {
+                let registers: Vec<(u8, u16)> = entries
+                    .chunks_exact(3)
+                    .take(count)
+                    .map(|entry| (entry[0], u16::from_be_bytes([entry[1], entry[2]])))
+                    .collect();
+                self.send_register_dump_report(&registers);
+            }
+            _ => {}
+        }
+    }
+
+    /// Switches a device on or off.
+    pub fn set_power(&self, device_id: u8, on: bool) -> Result<(), DeviceError> {
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices.get_mut(&device_id).ok_or(DeviceError::UnknownDevice(device_id))?;
+        device.set_power(on)
+    }
+
+    /// Writes a device register.
+    pub fn load_register(&self, device_id: u8, address: u16, value: u32) -> Result<(), DeviceError> {
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices.get_mut(&device_id).ok_or(DeviceError::UnknownDevice(device_id))?;
+        device.write_register(address, value)
+    }
+
+    /// Reads a device register.
+    pub fn dump_register(&self, device_id: u8, address: u16) -> Result<u32, DeviceError> {
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices.get_mut(&device_id).ok_or(DeviceError::UnknownDevice(device_id))?;
+        device.read_register(address)
+    }
+
+    /// Sends the register dump report (2,6) with the values of the given registers.
+    ///
+    /// Registers that cannot be read are left out of the report.
+    pub fn send_register_dump_report(&self, registers: &[(u8, u16)]) {
+        let values: Vec<RegisterValue> = registers
+            .iter()
+            .filter_map(|&(device_id, address)| {
+                let value = self.dump_register(device_id, address).ok()?;
+                Some(RegisterValue::new(device_id, address, value))
+            })
+            .take(u8::MAX as usize)
+            .collect();
+        let mut packet_data = vec![2, 6, values.len() as u8];
+        for value in &values {
+            value.encode(&mut packet_data);
+        }
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
@@ -13,22 +13,26 @@ use core::result::Result::{Ok, Err};
 use core::time::Duration;
 use cortex_m::interrupt::Mutex;
 use alloc::sync::Arc;
+use alloc::boxed::Box;
+
+use super::encoding;
+use crate::primitives::scheduler::{self, Clock, Scheduler};
 
 /// Represents a packet with data payload.
 #[derive(Debug)]
-struct Packet {
-    data: Vec<u8>,
+pub struct Packet {
+    pub data: Vec<u8>,
 }
 
 impl Packet {
     /// Creates a new packet with the given data.
-    fn new(data: Vec<u8>) -> Self {
+    pub fn new(data: Vec<u8>) -> Self {
         Packet { data }
     }
 }
 
 /// Trait defining the parent interface for sending packets and retrieving parameters.
-trait Parent {
+pub trait Parent: Send + Sync {
     /// Sends a packet.
     fn send(&self, packet: Packet);
     /// Retrieves a parameter by its ID.
@@ -37,15 +41,20 @@ trait Parent {
 
 /// Represents a parameter with encoding information.
 #[derive(Debug)]
-struct Parameter {
-    encoding: String,
+pub struct Parameter {
+    pub encoding: String,
+    pub value: f64,
 }
 
 impl Parameter {
+    /// Creates a parameter whose value is encoded with the given format string, e.g. `"!f"`.
+    pub fn new(encoding: String, value: f64) -> Self {
+        Parameter { encoding, value }
+    }
+
     /// Encodes the parameter into bytes.
     fn encode(&self) -> Vec<u8> {
-        // Implement encoding logic here.
-        vec![] // Placeholder.
+        encoding::encode(&self.encoding, &[self.value]).unwrap_or_default()
     }
 }
 
@@ -74,9 +83,8 @@ impl HousekeepingReport {
     }
 
     /// Decodes data bytes into a vector of f64 values.
-    fn decode(&self, _data: &[u8]) -> Vec<f64> {
-        // Implement decoding logic here.
-        vec![] // Placeholder.
+    fn decode(&self, data: &[u8]) -> Vec<f64> {
+        encoding::decode(&self.encoding, data).unwrap_or_default()
     }
 }
 
@@ -111,6 +119,15 @@ impl HousekeepingService {
     }
 }
 
+/// Builds the (3,25) report with the current values of its parameters.
+fn housekeeping_report_packet(parent: &dyn Parent, report_id: (u32, u32), parameter_ids: &[(u32, u32)]) -> Packet {
+    let mut data = vec![3, 25, report_id.1 as u8];
+    for parameter_id in parameter_ids {
+        data.extend(parent.get_parameter(*parameter_id).encode());
+    }
+    Packet::new(data)
+}
+
 /// Controller for the housekeeping service.
 pub struct HousekeepingServiceController {
     service: HousekeepingService,
@@ -147,3 +164,70 @@ impl HousekeepingServiceController {
         }
     }
 }
+
+/// Responder for the housekeeping service, sending the enabled reports periodically.
+pub struct HousekeepingServiceResponder {
+    service: HousekeepingService,
+    tasks: Vec<usize>,
+}
+
+impl HousekeepingServiceResponder {
+    /// Creates a new responder with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        HousekeepingServiceResponder {
+            service: HousekeepingService::new(parent),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Defines a housekeeping report sent every `interval` seconds while enabled.
+    ///
+    /// A running responder picks up the change when restarted.
+    pub fn define_housekeeping_report(
+        &mut self,
+        report_id: (u32, u32),
+        interval: f64,
+        enabled: bool,
+        parameter_ids: Vec<(u32, u32)>,
+    ) {
+        self.service.define_housekeeping_report(report_id, interval, enabled, parameter_ids);
+    }
+
+    /// Sends a housekeeping report (3,25) once, whether or not it is enabled.
+    pub fn send_housekeeping_report(&self, report_id: (u32, u32)) -> bool {
+        let Some(report) = self.service.get_housekeeping_report(report_id) else {
+            return false;
+        };
+        let parent = &*self.service.parent;
+        parent.send(housekeeping_report_packet(parent, report_id, &report.parameter_ids));
+        true
+    }
+
+    /// Starts sending the enabled reports with their intervals, driven by the scheduler's polls.
+    pub fn start<C: Clock>(&mut self, scheduler: &mut Scheduler<C>) {
+        self.stop(scheduler);
+        for (&report_id, report) in &self.service.housekeeping_reports {
+            if !report.enabled || report.interval <= 0.0 {
+                continue;
+            }
+            let period = scheduler::from_core_duration(Duration::from_secs_f64(report.interval));
+            let parent_clone = Arc::clone(&self.service.parent);
+            let parameter_ids = report.parameter_ids.clone();
+            self.tasks.push(scheduler.add_periodic(period, Box::new(move |_| {
+                parent_clone.send(housekeeping_report_packet(&*parent_clone, report_id, &parameter_ids));
+            })));
+        }
+    }
+
+    /// Stops sending the periodic reports.
+    pub fn stop<C: Clock>(&mut self, scheduler: &mut Scheduler<C>) {
+        for task in self.tasks.drain(..) {
+            scheduler.remove(task);
+        }
+    }
+
+    /// Returns true if reports are sent periodically.
+    pub fn is_running(&self) -> bool {
+        !self.tasks.is_empty()
+    }
+}
@@ -0,0 +1,505 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+use std::sync::Mutex;
+
+/// Node ID of the controller, the destination of all statistics reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// Encoded size of the statistics of one parameter.
+const STATISTICS_SIZE: usize = 40;
+/// Encoded size of a statistics definition.
+const DEFINITION_SIZE: usize = 8;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+    /// Returns the current value of a parameter of this node (ST20).
+    fn get_parameter_value(&self, parameter_id: u32) -> Option<f64>;
+}
+
+/// Parameter whose statistics are accumulated, sampled at a fixed interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatisticsDefinition {
+    pub parameter_id: u32,
+    /// Interval between samples, with millisecond resolution on the bus.
+    pub sampling_interval: Duration,
+}
+
+impl StatisticsDefinition {
+    /// Creates a new definition.
+    pub fn new(parameter_id: u32, sampling_interval: Duration) -> Self {
+        StatisticsDefinition {
+            parameter_id,
+            sampling_interval,
+        }
+    }
+
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.extend_from_slice(&self.parameter_id.to_be_bytes());
+        data.extend_from_slice(&encode_millis(self.sampling_interval).to_be_bytes());
+    }
+
+    fn decode(data: &[u8]) -> Self {
+        StatisticsDefinition {
+            parameter_id: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+            sampling_interval: Duration::from_millis(u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as u64),
+        }
+    }
+}
+
+/// Statistics of one parameter over the current interval.
+///
+/// Without samples, all values are zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParameterStatistics {
+    pub parameter_id: u32,
+    pub samples: u32,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub standard_deviation: f64,
+}
+
+impl ParameterStatistics {
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.extend_from_slice(&self.parameter_id.to_be_bytes());
+        data.extend_from_slice(&self.samples.to_be_bytes());
+        data.extend_from_slice(&self.min.to_be_bytes());
+        data.extend_from_slice(&self.max.to_be_bytes());
+        data.extend_from_slice(&self.mean.to_be_bytes());
+        data.extend_from_slice(&self.standard_deviation.to_be_bytes());
+    }
+
+    fn decode(data: &[u8]) -> Self {
+        let float = |offset: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&data[offset..offset + 8]);
+            f64::from_be_bytes(bytes)
+        };
+        ParameterStatistics {
+            parameter_id: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+            samples: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            min: float(8),
+            max: float(16),
+            mean: float(24),
+            standard_deviation: float(32),
+        }
+    }
+}
+
+/// Statistics report (4,2) of a responder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatisticsReport {
+    /// Local time at which the interval started.
+    pub start: Duration,
+    /// Local time at which the report was generated.
+    pub end: Duration,
+    pub statistics: Vec<ParameterStatistics>,
+}
+
+/// Callback invoked by the controller for every received statistics report.
+pub type StatisticsReportHandler = Arc<dyn Fn(u32, &StatisticsReport) + Send + Sync>;
+
+/// Converts a duration to whole milliseconds, saturating at `u32::MAX`.
+fn encode_millis(duration: Duration) -> u32 {
+    duration.as_millis().min(u32::MAX as u128) as u32
+}
+
+/// Converts a local time to whole milliseconds, saturating at `u64::MAX`.
+fn encode_time(time: Duration) -> u64 {
+    time.as_millis().min(u64::MAX as u128) as u64
+}
+
+/// Decodes a millisecond time written by `encode_time`.
+fn decode_time(data: &[u8]) -> Duration {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&data[..8]);
+    Duration::from_millis(u64::from_be_bytes(bytes))
+}
+
+/// Running statistics of a parameter, updated with Welford's algorithm.
+struct Accumulator {
+    definition: StatisticsDefinition,
+    next_sample: Option<Duration>,
+    samples: u32,
+    min: f64,
+    max: f64,
+    mean: f64,
+    sum_of_squares: f64,
+}
+
+impl Accumulator {
+    fn new(definition: StatisticsDefinition) -> Self {
+        Accumulator {
+            definition,
+            next_sample: None,
+            samples: 0,
+            min: 0.0,
+            max: 0.0,
+            mean: 0.0,
+            sum_of_squares: 0.0,
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Accumulator {
+            next_sample: self.next_sample,
+            ..Accumulator::new(self.definition)
+        };
+    }
+
+    fn add(&mut self, value: f64) {
+        if self.samples == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.samples += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.samples as f64;
+        self.sum_of_squares += delta * (value - self.mean);
+    }
+
+    fn statistics(&self) -> ParameterStatistics {
+        let variance = match self.samples {
+            0 => 0.0,
+            samples => self.sum_of_squares / samples as f64,
+        };
+        ParameterStatistics {
+            parameter_id: self.definition.parameter_id,
+            samples: self.samples,
+            min: self.min,
+            max: self.max,
+            mean: self.mean,
+            standard_deviation: variance.sqrt(),
+        }
+    }
+}
+
+/// Controller for the Parameter Statistics Reporting Service.
+pub struct ParameterStatisticsServiceController {
+    parent: Arc<dyn Parent>,
+    reports: Mutex<BTreeMap<u32, StatisticsReport>>,
+    definitions: Mutex<BTreeMap<u32, Vec<StatisticsDefinition>>>,
+    report_handler: Option<StatisticsReportHandler>,
+}
+
+impl ParameterStatisticsServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        ParameterStatisticsServiceController {
+            parent,
+            reports: Mutex::new(BTreeMap::new()),
+            definitions: Mutex::new(BTreeMap::new()),
+            report_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every received statistics report.
+    pub fn set_report_handler(&mut self, handler: StatisticsReportHandler) {
+        self.report_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        match case {
+            (4, 2) if data.len() >= 17 => {
+                let count = data[16] as usize;
+                let entries = &data[17..];
+                if entries.len() < count * STATISTICS_SIZE {
+                    return;
+                }
+                let report = StatisticsReport {
+                    start: decode_time(&data[0..8]),
+                    end: decode_time(&data[8..16]),
+                    statistics: entries
+                        .chunks_exact(STATISTICS_SIZE)
+                        .take(count)
+                        .map(ParameterStatistics::decode)
+                        .collect(),
+                };
+                if let Some(handler) = &self.report_handler {
+                    handler(node_id, &report);
+                }
+                self.reports.lock().unwrap().insert(node_id, report);
+            }
+            (4, 9) => {
+                let Some((&count, entries)) = data.split_first() else {
+                    return;
+                };
+                let definitions = entries
+                    .chunks_exact(DEFINITION_SIZE)
+                    .take(count as usize)
+                    .map(StatisticsDefinition::decode)
+                    .collect();
+                self.definitions.lock().unwrap().insert(node_id, definitions);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the statistics report last received from a node.
+    pub fn last_report(&self, node_id: u32) -> Option<StatisticsReport> {
+        self.reports.lock().unwrap().get(&node_id).cloned()
+    }
+
+    /// Returns the statistics definitions last reported by a node.
+    pub fn definitions(&self, node_id: u32) -> Option<Vec<StatisticsDefinition>> {
+        self.definitions.lock().unwrap().get(&node_id).cloned()
+    }
+
+    /// Requests a statistics report, optionally resetting the statistics (4,1).
+    pub fn send_report_request(&self, node_id: u32, reset: bool) {
+        self.parent.send(Packet::new(vec![4, 1, reset as u8]), node_id);
+    }
+
+    /// Resets the statistics of a node (4,3).
+    pub fn send_reset(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![4, 3]), node_id);
+    }
+
+    /// Enables periodic statistics reporting with the given interval (4,4).
+    pub fn send_enable_periodic_reporting(&self, node_id: u32, interval: Duration) {
+        let mut packet_data = vec![4, 4];
+        packet_data.extend_from_slice(&encode_millis(interval).to_be_bytes());
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Disables periodic statistics reporting (4,5).
+    pub fn send_disable_periodic_reporting(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![4, 5]), node_id);
+    }
+
+    /// Adds or updates statistics definitions (4,6).
+    ///
+    /// At most 255 definitions fit into one packet; further ones are dropped.
+    pub fn send_add_definitions(&self, node_id: u32, definitions: &[StatisticsDefinition]) {
+        let definitions = &definitions[..definitions.len().min(u8::MAX as usize)];
+        let mut packet_data = vec![4, 6, definitions.len() as u8];
+        for definition in definitions {
+            definition.encode(&mut packet_data);
+        }
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Deletes the statistics definitions of the given parameters (4,7).
+    pub fn send_delete_definitions(&self, node_id: u32, parameter_ids: &[u32]) {
+        let parameter_ids = &parameter_ids[..parameter_ids.len().min(u8::MAX as usize)];
+        let mut packet_data = vec![4, 7, parameter_ids.len() as u8];
+        for parameter_id in parameter_ids {
+            packet_data.extend_from_slice(&parameter_id.to_be_bytes());
+        }
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Requests the statistics definitions of a node (4,8).
+    pub fn send_definitions_request(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![4, 8]), node_id);
+    }
+}
+
+/// Statistics state of the responder.
+struct StatisticsState {
+    accumulators: BTreeMap<u32, Accumulator>,
+    interval_start: Duration,
+    reporting_interval: Option<Duration>,
+}
+
+impl StatisticsState {
+    fn reset(&mut self, now: Duration) {
+        self.accumulators.values_mut().for_each(Accumulator::reset);
+        self.interval_start = now;
+    }
+}
+
+/// Responder for the Parameter Statistics Reporting Service.
+///
+/// The application calls `poll` periodically with its local monotonic
+/// time; due parameters are sampled and, if periodic reporting is enabled,
+/// a report is sent and the statistics are reset when the reporting
+/// interval has elapsed. Telecommands that depend on the time use the time
+/// of the last `poll`.
+pub struct ParameterStatisticsServiceResponder {
+    parent: Arc<dyn Parent>,
+    state: Mutex<StatisticsState>,
+    now: Mutex<Duration>,
+}
+
+impl ParameterStatisticsServiceResponder {
+    /// Creates a new responder without definitions.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        ParameterStatisticsServiceResponder {
+            parent,
+            state: Mutex::new(StatisticsState {
+                accumulators: BTreeMap::new(),
+                interval_start: Duration::ZERO,
+                reporting_interval: None,
+            }),
+            now: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+        let now = *self.now.lock().unwrap();
+
+        match case {
+            (4, 1) => self.send_statistics_report(now, data.first().is_some_and(|&reset| reset != 0)),
+            (4, 3) => self.reset(now),
+            (4, 4) if data.len() >= 4 => {
+                let interval = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+                self.set_periodic_reporting(Some(Duration::from_millis(interval as u64)));
+            }
+            (4, 5) => self.set_periodic_reporting(None),
+            (4, 6) => {
+                let Some((&count, entries)) = data.split_first() else {
+                    return;
+                };
+                for entry in entries.chunks_exact(DEFINITION_SIZE).take(count as usize) {
+                    self.add_definition(StatisticsDefinition::decode(entry));
+                }
+            }
+            (4, 7) => {
+                let Some((&count, entries)) = data.split_first() else {
+                    return;
+                };
+                for entry in entries.chunks_exact(4).take(count as usize) {
+                    self.delete_definition(u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]));
+                }
+            }
+            (4, 8) => self.send_definitions_report(),
+            _ => {}
+        }
+    }
+
+    /// Adds a statistics definition. Redefining a parameter resets its statistics.
+    pub fn add_definition(&self, definition: StatisticsDefinition) {
+        self.state
+            .lock()
+            .unwrap()
+            .accumulators
+            .insert(definition.parameter_id, Accumulator::new(definition));
+    }
+
+    /// Deletes the statistics definition of a parameter. Returns true if it existed.
+    pub fn delete_definition(&self, parameter_id: u32) -> bool {
+        self.state.lock().unwrap().accumulators.remove(&parameter_id).is_some()
+    }
+
+    /// Returns all statistics definitions.
+    pub fn definitions(&self) -> Vec<StatisticsDefinition> {
+        self.state
+            .lock()
+            .unwrap()
+            .accumulators
+            .values()
+            .map(|accumulator| accumulator.definition)
+            .collect()
+    }
+
+    /// Enables periodic reporting with the given interval, or disables it.
+    pub fn set_periodic_reporting(&self, interval: Option<Duration>) {
+        self.state.lock().unwrap().reporting_interval = interval;
+    }
+
+    /// Returns the periodic reporting interval, if periodic reporting is enabled.
+    pub fn periodic_reporting(&self) -> Option<Duration> {
+        self.state.lock().unwrap().reporting_interval
+    }
+
+    /// Returns the statistics of the current interval.
+    pub fn statistics(&self) -> Vec<ParameterStatistics> {
+        self.state
+            .lock()
+            .unwrap()
+            .accumulators
+            .values()
+            .map(Accumulator::statistics)
+            .collect()
+    }
+
+    /// Resets all statistics, starting a new interval at `now`.
+    pub fn reset(&self, now: Duration) {
+        self.state.lock().unwrap().reset(now);
+    }
+
+    /// Samples the parameters that are due and sends the periodic report if it is due.
+    pub fn poll(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+        let report_due = {
+            let mut state = self.state.lock().unwrap();
+            for accumulator in state.accumulators.values_mut() {
+                if accumulator.next_sample.is_some_and(|next_sample| now < next_sample) {
+                    continue;
+                }
+                if let Some(value) = self.parent.get_parameter_value(accumulator.definition.parameter_id) {
+                    accumulator.add(value);
+                }
+                accumulator.next_sample = Some(now + accumulator.definition.sampling_interval);
+            }
+            state
+                .reporting_interval
+                .is_some_and(|interval| now >= state.interval_start + interval)
+        };
+        if report_due {
+            self.send_statistics_report(now, true);
+        }
+    }
+
+    /// Sends the statistics report (4,2), optionally resetting the statistics.
+    pub fn send_statistics_report(&self, now: Duration, reset: bool) {
+        let mut state = self.state.lock().unwrap();
+        let statistics: Vec<ParameterStatistics> = state
+            .accumulators
+            .values()
+            .take(u8::MAX as usize)
+            .map(Accumulator::statistics)
+            .collect();
+        let mut packet_data = vec![4, 2];
+        packet_data.extend_from_slice(&encode_time(state.interval_start).to_be_bytes());
+        packet_data.extend_from_slice(&encode_time(now).to_be_bytes());
+        packet_data.push(statistics.len() as u8);
+        for entry in &statistics {
+            entry.encode(&mut packet_data);
+        }
+        if reset {
+            state.reset(now);
+        }
+        drop(state);
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+
+    /// Sends the statistics definitions report (4,9).
+    pub fn send_definitions_report(&self) {
+        let definitions = self.definitions();
+        let definitions = &definitions[..definitions.len().min(u8::MAX as usize)];
+        let mut packet_data = vec![4, 9, definitions.len() as u8];
+        for definition in definitions {
+            definition.encode(&mut packet_data);
+        }
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
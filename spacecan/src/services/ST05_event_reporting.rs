@@ -0,0 +1,353 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::BufReader;
+#[cfg(feature = "std")]
+use serde_json::Value;
+
+use super::encoding::{self, decode_id_list, encode_id_list};
+
+/// Node ID of the controller, the destination of all event reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+}
+
+/// Severity of an event, which selects the report subtype.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Informative event report (5,1).
+    Informative = 1,
+    /// Low severity anomaly report (5,2).
+    Low = 2,
+    /// Medium severity anomaly report (5,3).
+    Medium = 3,
+    /// High severity anomaly report (5,4).
+    High = 4,
+}
+
+impl Severity {
+    /// Returns the severity reported with the given subtype.
+    pub fn from_subtype(subtype: u8) -> Option<Self> {
+        match subtype {
+            1 => Some(Severity::Informative),
+            2 => Some(Severity::Low),
+            3 => Some(Severity::Medium),
+            4 => Some(Severity::High),
+            _ => None,
+        }
+    }
+
+    /// Returns the severity named in a configuration file.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "informative" => Some(Severity::Informative),
+            "low" => Some(Severity::Low),
+            "medium" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            _ => None,
+        }
+    }
+
+    /// Returns the report subtype of this severity.
+    pub fn subtype(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// Definition of an event and the encoding of its auxiliary data.
+#[derive(Debug, Clone)]
+pub struct EventDefinition {
+    pub event_id: u16,
+    pub event_name: String,
+    pub severity: Severity,
+    pub encoding: String,
+}
+
+impl EventDefinition {
+    /// Creates a new event definition.
+    pub fn new(event_id: u16, event_name: String, severity: Severity, encoding: String) -> Self {
+        EventDefinition {
+            event_id,
+            event_name,
+            severity,
+            encoding,
+        }
+    }
+}
+
+/// Event report decoded by the controller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventReport {
+    pub node_id: u32,
+    pub event_id: u16,
+    pub severity: Severity,
+    /// Name from the event definition, if the event is known to the controller.
+    pub event_name: Option<String>,
+    /// Decoded auxiliary data, empty if the event is unknown or cannot be decoded.
+    pub auxiliary_data: Vec<f64>,
+    /// Raw auxiliary data as received.
+    pub raw_data: Vec<u8>,
+}
+
+/// Callback invoked by the controller for every decoded event report.
+pub type EventHandler = Arc<dyn Fn(&EventReport) + Send + Sync>;
+
+/// Callback invoked by the responder for every raised event, with its auxiliary data.
+pub type EventListener = Arc<dyn Fn(u16, &[u8]) + Send + Sync>;
+
+/// Controller for the Event Reporting Service.
+pub struct EventReportingServiceController {
+    parent: Arc<dyn Parent>,
+    definitions: BTreeMap<(u32, u16), EventDefinition>,
+    event_handler: Option<EventHandler>,
+    disabled_events: Mutex<BTreeMap<u32, Vec<u16>>>,
+}
+
+impl EventReportingServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        EventReportingServiceController {
+            parent,
+            definitions: BTreeMap::new(),
+            event_handler: None,
+            disabled_events: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Adds the definition of an event raised by the given node.
+    pub fn add_event_definition(&mut self, node_id: u32, definition: EventDefinition) {
+        self.definitions.insert((node_id, definition.event_id), definition);
+    }
+
+    /// Adds event definitions from a JSON file.
+    #[cfg(feature = "std")]
+    pub fn add_event_definitions_from_file(&mut self, filepath: &str, node_id: u32) -> Result<(), std::io::Error> {
+        let file = File::open(filepath)?;
+        let reader = BufReader::new(file);
+        let json: Value = serde_json::from_reader(reader)?;
+
+        if let Some(list_of_dicts) = json["events"].as_array() {
+            for event in list_of_dicts {
+                let event_id = event["event_id"].as_u64().unwrap() as u16;
+                let event_name = event["event_name"].as_str().unwrap().into();
+                let severity = Severity::from_name(event["severity"].as_str().unwrap_or("informative"))
+                    .unwrap_or(Severity::Informative);
+                let encoding = event["encoding"].as_str().unwrap_or("").into();
+
+                self.add_event_definition(node_id, EventDefinition::new(event_id, event_name, severity, encoding));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the callback invoked for every decoded event report.
+    pub fn set_event_handler(&mut self, handler: EventHandler) {
+        self.event_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        match case {
+            (5, 1..=4) => {
+                if let Some(report) = self.decode_event_report(subtype, &data, node_id) {
+                    self.received_event_report(&report);
+                }
+            }
+            (5, 8) => {
+                if let Some((event_ids, _)) = decode_id_list(&data) {
+                    self.disabled_events.lock().unwrap().insert(node_id, event_ids);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Decodes the payload of an event report (5,1) to (5,4).
+    pub fn decode_event_report(&self, subtype: u8, data: &[u8], node_id: u32) -> Option<EventReport> {
+        let severity = Severity::from_subtype(subtype)?;
+        if data.len() < 2 {
+            return None;
+        }
+        let event_id = u16::from_be_bytes([data[0], data[1]]);
+        let raw_data = data[2..].to_vec();
+        let definition = self.definitions.get(&(node_id, event_id));
+        let auxiliary_data = definition
+            .and_then(|definition| encoding::decode(&definition.encoding, &raw_data).ok())
+            .unwrap_or_default();
+
+        Some(EventReport {
+            node_id,
+            event_id,
+            severity,
+            event_name: definition.map(|definition| definition.event_name.clone()),
+            auxiliary_data,
+            raw_data,
+        })
+    }
+
+    /// Handler for a received event report.
+    pub fn received_event_report(&self, report: &EventReport) {
+        if let Some(handler) = &self.event_handler {
+            handler(report);
+        }
+    }
+
+    /// Returns the disabled events last reported by a node with (5,8).
+    pub fn disabled_events(&self, node_id: u32) -> Option<Vec<u16>> {
+        self.disabled_events.lock().unwrap().get(&node_id).cloned()
+    }
+
+    /// Enables report generation of the given events on a node (5,5).
+    pub fn send_enable_event_generation(&self, node_id: u32, event_ids: &[u16]) {
+        let mut packet_data = vec![5, 5];
+        packet_data.extend(encode_id_list(event_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Disables report generation of the given events on a node (5,6).
+    pub fn send_disable_event_generation(&self, node_id: u32, event_ids: &[u16]) {
+        let mut packet_data = vec![5, 6];
+        packet_data.extend(encode_id_list(event_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Requests the list of disabled events from a node (5,7).
+    pub fn send_report_disabled_events(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![5, 7]), node_id);
+    }
+}
+
+/// Responder for the Event Reporting Service.
+pub struct EventReportingServiceResponder {
+    parent: Arc<dyn Parent>,
+    definitions: BTreeMap<u16, EventDefinition>,
+    disabled: Mutex<BTreeSet<u16>>,
+    event_listener: Option<EventListener>,
+}
+
+impl EventReportingServiceResponder {
+    /// Creates a new responder with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        EventReportingServiceResponder {
+            parent,
+            definitions: BTreeMap::new(),
+            disabled: Mutex::new(BTreeSet::new()),
+            event_listener: None,
+        }
+    }
+
+    /// Sets the callback invoked for every raised event, e.g. to trigger event-actions.
+    ///
+    /// The listener is called whether or not report generation is enabled for the event.
+    pub fn set_event_listener(&mut self, listener: EventListener) {
+        self.event_listener = Some(listener);
+    }
+
+    /// Adds an event this node can raise.
+    pub fn add_event_definition(&mut self, definition: EventDefinition) {
+        self.definitions.insert(definition.event_id, definition);
+    }
+
+    /// Returns the definition of an event.
+    pub fn get_event_definition(&self, event_id: u16) -> Option<&EventDefinition> {
+        self.definitions.get(&event_id)
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+
+        match case {
+            (5, 5) => {
+                if let Some((event_ids, _)) = decode_id_list(&data) {
+                    let mut disabled = self.disabled.lock().unwrap();
+                    for event_id in event_ids {
+                        disabled.remove(&event_id);
+                    }
+                }
+            }
+            (5, 6) => {
+                if let Some((event_ids, _)) = decode_id_list(&data) {
+                    let mut disabled = self.disabled.lock().unwrap();
+                    for event_id in event_ids.into_iter().filter(|id| self.definitions.contains_key(id)) {
+                        disabled.insert(event_id);
+                    }
+                }
+            }
+            (5, 7) => self.send_disabled_events_report(),
+            _ => {}
+        }
+    }
+
+    /// Returns true if report generation is enabled for an event.
+    pub fn is_enabled(&self, event_id: u16) -> bool {
+        !self.disabled.lock().unwrap().contains(&event_id)
+    }
+
+    /// Raises an event, encoding the auxiliary values with the event's encoding.
+    ///
+    /// Returns true if a report was sent, false if the event is unknown,
+    /// disabled or the values do not match the encoding.
+    pub fn raise_event(&self, event_id: u16, auxiliary_data: &[f64]) -> bool {
+        let Some(definition) = self.definitions.get(&event_id) else {
+            return false;
+        };
+        encoding::encode(&definition.encoding, auxiliary_data)
+            .map(|data| self.raise_event_raw(event_id, &data))
+            .unwrap_or(false)
+    }
+
+    /// Raises an event with pre-encoded auxiliary data.
+    pub fn raise_event_raw(&self, event_id: u16, auxiliary_data: &[u8]) -> bool {
+        let Some(definition) = self.definitions.get(&event_id) else {
+            return false;
+        };
+        if let Some(listener) = &self.event_listener {
+            listener(event_id, auxiliary_data);
+        }
+        if !self.is_enabled(event_id) {
+            return false;
+        }
+        let mut packet_data = vec![5, definition.severity.subtype()];
+        packet_data.extend_from_slice(&event_id.to_be_bytes());
+        packet_data.extend_from_slice(auxiliary_data);
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+        true
+    }
+
+    /// Sends the disabled event definitions list report (5,8).
+    pub fn send_disabled_events_report(&self) {
+        let event_ids: Vec<u16> = self.disabled.lock().unwrap().iter().copied().collect();
+        let mut packet_data = vec![5, 8];
+        packet_data.extend(encode_id_list(&event_ids));
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
@@ -0,0 +1,579 @@
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use std::sync::Mutex;
+
+use crate::primitives::crc::{crc16, crc16_update};
+
+/// Largest number of bytes loaded or dumped with one packet.
+pub const MAX_TRANSFER_LENGTH: usize = 1024;
+/// Node ID of the controller, the destination of all memory reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// Size of the memory ID, start address and length fields.
+const HEADER_SIZE: usize = 7;
+/// Number of bytes read at a time while computing a checksum.
+const CHECKSUM_BLOCK_SIZE: usize = 256;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets and verification reports.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+    /// Returns the request verification service (ST01) of this node.
+    fn request_verification(&self) -> &dyn RequestVerification;
+}
+
+/// Verification reports sent by the responder, see ST01.
+pub trait RequestVerification {
+    fn send_success_acceptance_report(&self, args: &[u8]);
+    fn send_success_completion_report(&self, args: &[u8]);
+    fn send_fail_acceptance_report(&self, args: &[u8]);
+    fn send_fail_completion_report(&self, args: &[u8]);
+}
+
+/// Errors raised by memory operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryError {
+    /// No region is registered under the memory ID.
+    UnknownMemoryId(u8),
+    /// The requested area is not fully inside the region.
+    OutOfRange,
+    /// The region does not permit the operation.
+    AccessDenied,
+    /// The checksum of loaded data does not match.
+    ChecksumMismatch,
+    /// The region failed to read or write.
+    Device,
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryError::UnknownMemoryId(id) => write!(f, "unknown memory ID {}", id),
+            MemoryError::OutOfRange => write!(f, "memory area out of range"),
+            MemoryError::AccessDenied => write!(f, "memory access denied"),
+            MemoryError::ChecksumMismatch => write!(f, "checksum mismatch"),
+            MemoryError::Device => write!(f, "memory device error"),
+        }
+    }
+}
+
+/// Operations permitted on a memory region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+impl Access {
+    fn can_read(&self) -> bool {
+        matches!(self, Access::ReadOnly | Access::ReadWrite)
+    }
+
+    fn can_write(&self) -> bool {
+        matches!(self, Access::WriteOnly | Access::ReadWrite)
+    }
+}
+
+/// Memory the application exposes to the memory management service.
+///
+/// Offsets are relative to the start address the region is registered with.
+pub trait MemoryRegion: Send {
+    /// Returns the size of the region in bytes.
+    fn size(&self) -> u32;
+    /// Reads `buffer.len()` bytes starting at `offset`.
+    fn read(&self, offset: u32, buffer: &mut [u8]) -> Result<(), MemoryError>;
+    /// Writes `data` starting at `offset`.
+    fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), MemoryError>;
+}
+
+/// Memory region backed by a byte vector.
+#[derive(Debug, Clone, Default)]
+pub struct RamRegion {
+    data: Vec<u8>,
+}
+
+impl RamRegion {
+    /// Creates a region holding the given bytes.
+    pub fn new(data: Vec<u8>) -> Self {
+        RamRegion { data }
+    }
+
+    /// Returns the contents of the region.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl MemoryRegion for RamRegion {
+    fn size(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    fn read(&self, offset: u32, buffer: &mut [u8]) -> Result<(), MemoryError> {
+        let start = offset as usize;
+        let source = self.data.get(start..start + buffer.len()).ok_or(MemoryError::OutOfRange)?;
+        buffer.copy_from_slice(source);
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, data: &[u8]) -> Result<(), MemoryError> {
+        let start = offset as usize;
+        let target = self.data.get_mut(start..start + data.len()).ok_or(MemoryError::OutOfRange)?;
+        target.copy_from_slice(data);
+        Ok(())
+    }
+}
+
+/// Memory area dumped from a responder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryDump {
+    pub memory_id: u8,
+    pub start_address: u32,
+    pub data: Vec<u8>,
+}
+
+/// Checksum of a memory area reported with (6,10).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryChecksum {
+    pub memory_id: u8,
+    pub start_address: u32,
+    pub length: u32,
+    /// CRC-16-CCITT of the area.
+    pub checksum: u16,
+}
+
+/// Callback invoked by the controller when a dump is complete.
+pub type DumpHandler = Arc<dyn Fn(u32, &MemoryDump) + Send + Sync>;
+
+/// Encodes memory ID, start address and length (u16).
+fn encode_header(memory_id: u8, start_address: u32, length: u16) -> Vec<u8> {
+    let mut data = vec![memory_id];
+    data.extend_from_slice(&start_address.to_be_bytes());
+    data.extend_from_slice(&length.to_be_bytes());
+    data
+}
+
+/// Decodes a header encoded by `encode_header`, returning it and the remaining data.
+fn decode_header(data: &[u8]) -> Option<(u8, u32, u16, &[u8])> {
+    if data.len() < HEADER_SIZE {
+        return None;
+    }
+    let start_address = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+    let length = u16::from_be_bytes([data[5], data[6]]);
+    Some((data[0], start_address, length, &data[HEADER_SIZE..]))
+}
+
+/// Checks the length and CRC of the data of a load request, returning the data.
+fn verified_chunk(length: u16, rest: &[u8]) -> Result<&[u8], MemoryError> {
+    if length as usize > MAX_TRANSFER_LENGTH || rest.len() < length as usize + 2 {
+        return Err(MemoryError::OutOfRange);
+    }
+    let (chunk, checksum) = rest.split_at(length as usize);
+    if crc16(chunk) != u16::from_be_bytes([checksum[0], checksum[1]]) {
+        return Err(MemoryError::ChecksumMismatch);
+    }
+    Ok(chunk)
+}
+
+/// Request accepted by the responder.
+enum MemoryRequest<'a> {
+    Load(u8, u32, &'a [u8]),
+    Dump(u8, u32, u16),
+    Check(u8, u32, u32),
+}
+
+/// Splits an area into chunks of at most `MAX_TRANSFER_LENGTH` bytes.
+///
+/// Returns None if the area extends past the end of the 32-bit address space.
+fn chunks(start_address: u32, length: usize) -> Option<Vec<(u32, u16)>> {
+    (0..length)
+        .step_by(MAX_TRANSFER_LENGTH)
+        .map(|offset| {
+            let chunk_length = (length - offset).min(MAX_TRANSFER_LENGTH);
+            let address = start_address.checked_add(u32::try_from(offset).ok()?)?;
+            address.checked_add(chunk_length as u32 - 1)?;
+            Some((address, chunk_length as u16))
+        })
+        .collect()
+}
+
+/// Dump assembled from several dump reports.
+#[derive(Debug)]
+struct DumpTransfer {
+    dump: MemoryDump,
+    // Start addresses and lengths of chunks not yet received.
+    pending: BTreeMap<u32, u16>,
+}
+
+/// Controller for the Memory Management Service.
+pub struct MemoryManagementServiceController {
+    parent: Arc<dyn Parent>,
+    transfers: Mutex<BTreeMap<(u32, u8), DumpTransfer>>,
+    dumps: Mutex<BTreeMap<(u32, u8), MemoryDump>>,
+    checksums: Mutex<BTreeMap<u32, MemoryChecksum>>,
+    dump_handler: Option<DumpHandler>,
+}
+
+impl MemoryManagementServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        MemoryManagementServiceController {
+            parent,
+            transfers: Mutex::new(BTreeMap::new()),
+            dumps: Mutex::new(BTreeMap::new()),
+            checksums: Mutex::new(BTreeMap::new()),
+            dump_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked when a dump is complete.
+    pub fn set_dump_handler(&mut self, handler: DumpHandler) {
+        self.dump_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        match case {
+            (6, 6) => self.received_dump_report(&data, node_id),
+            (6, 10) if data.len() >= 11 => {
+                let checksum = MemoryChecksum {
+                    memory_id: data[0],
+                    start_address: u32::from_be_bytes([data[1], data[2], data[3], data[4]]),
+                    length: u32::from_be_bytes([data[5], data[6], data[7], data[8]]),
+                    checksum: u16::from_be_bytes([data[9], data[10]]),
+                };
+                self.checksums.lock().unwrap().insert(node_id, checksum);
+            }
+            _ => {}
+        }
+    }
+
+    /// Copies a dump report into its transfer, completing the dump once all chunks arrived.
+    ///
+    /// Reports with a wrong checksum or not belonging to a transfer are dropped.
+    fn received_dump_report(&self, data: &[u8], node_id: u32) {
+        let Some((memory_id, start_address, length, rest)) = decode_header(data) else {
+            return;
+        };
+        let length = length as usize;
+        if rest.len() < length + 2 {
+            return;
+        }
+        let (chunk, checksum) = rest.split_at(length);
+        if crc16(chunk) != u16::from_be_bytes([checksum[0], checksum[1]]) {
+            return;
+        }
+
+        let mut transfers = self.transfers.lock().unwrap();
+        let Some(transfer) = transfers.get_mut(&(node_id, memory_id)) else {
+            return;
+        };
+        if transfer.pending.get(&start_address) != Some(&(length as u16)) {
+            return;
+        }
+        transfer.pending.remove(&start_address);
+        let offset = (start_address - transfer.dump.start_address) as usize;
+        transfer.dump.data[offset..offset + length].copy_from_slice(chunk);
+        if !transfer.pending.is_empty() {
+            return;
+        }
+        let dump = transfers.remove(&(node_id, memory_id)).unwrap().dump;
+        drop(transfers);
+        self.completed_dump(node_id, dump);
+    }
+
+    fn completed_dump(&self, node_id: u32, dump: MemoryDump) {
+        if let Some(handler) = &self.dump_handler {
+            handler(node_id, &dump);
+        }
+        self.dumps.lock().unwrap().insert((node_id, dump.memory_id), dump);
+    }
+
+    /// Loads data into the memory of a node (6,2), split into packets of at most
+    /// `MAX_TRANSFER_LENGTH` bytes. Each packet carries the CRC of its data.
+    ///
+    /// Nothing is sent if the area extends past the end of the address space.
+    pub fn send_load(&self, node_id: u32, memory_id: u8, start_address: u32, data: &[u8]) -> Result<(), MemoryError> {
+        for (address, length) in chunks(start_address, data.len()).ok_or(MemoryError::OutOfRange)? {
+            let offset = (address - start_address) as usize;
+            let chunk = &data[offset..offset + length as usize];
+            let mut packet_data = vec![6, 2];
+            packet_data.extend(encode_header(memory_id, address, length));
+            packet_data.extend_from_slice(chunk);
+            packet_data.extend_from_slice(&crc16(chunk).to_be_bytes());
+            self.parent.send(Packet::new(packet_data), node_id);
+        }
+        Ok(())
+    }
+
+    /// Requests a dump of a memory area of a node (6,5), split into packets of at most
+    /// `MAX_TRANSFER_LENGTH` bytes. The reports (6,6) are reassembled into one dump.
+    ///
+    /// A dump request replaces an unfinished dump of the same memory. Nothing
+    /// is sent if the area extends past the end of the address space, and a
+    /// dump of zero bytes completes at once.
+    pub fn send_dump_request(
+        &self,
+        node_id: u32,
+        memory_id: u8,
+        start_address: u32,
+        length: u32,
+    ) -> Result<(), MemoryError> {
+        let chunks = chunks(start_address, length as usize).ok_or(MemoryError::OutOfRange)?;
+        let dump = MemoryDump {
+            memory_id,
+            start_address,
+            data: vec![0; length as usize],
+        };
+        if chunks.is_empty() {
+            self.transfers.lock().unwrap().remove(&(node_id, memory_id));
+            self.completed_dump(node_id, dump);
+            return Ok(());
+        }
+        let transfer = DumpTransfer {
+            dump,
+            pending: chunks.iter().copied().collect(),
+        };
+        self.transfers.lock().unwrap().insert((node_id, memory_id), transfer);
+        for (address, length) in chunks {
+            let mut packet_data = vec![6, 5];
+            packet_data.extend(encode_header(memory_id, address, length));
+            self.parent.send(Packet::new(packet_data), node_id);
+        }
+        Ok(())
+    }
+
+    /// Requests the checksum of a memory area of a node (6,9).
+    pub fn send_check_request(&self, node_id: u32, memory_id: u8, start_address: u32, length: u32) {
+        let mut packet_data = vec![6, 9, memory_id];
+        packet_data.extend_from_slice(&start_address.to_be_bytes());
+        packet_data.extend_from_slice(&length.to_be_bytes());
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Returns true while a dump of the given memory of a node is incomplete.
+    pub fn is_dump_pending(&self, node_id: u32, memory_id: u8) -> bool {
+        self.transfers.lock().unwrap().contains_key(&(node_id, memory_id))
+    }
+
+    /// Abandons an incomplete dump, e.g. after a failure report (1,2) or (1,8) for its request.
+    pub fn cancel_dump(&self, node_id: u32, memory_id: u8) -> bool {
+        self.transfers.lock().unwrap().remove(&(node_id, memory_id)).is_some()
+    }
+
+    /// Removes and returns the last completed dump of the given memory of a node.
+    pub fn take_dump(&self, node_id: u32, memory_id: u8) -> Option<MemoryDump> {
+        self.dumps.lock().unwrap().remove(&(node_id, memory_id))
+    }
+
+    /// Returns the last checksum reported by a node.
+    pub fn last_checksum(&self, node_id: u32) -> Option<MemoryChecksum> {
+        self.checksums.lock().unwrap().get(&node_id).copied()
+    }
+}
+
+/// Memory region registered with the responder.
+struct RegisteredRegion {
+    start_address: u32,
+    access: Access,
+    region: Box<dyn MemoryRegion>,
+}
+
+impl RegisteredRegion {
+    /// Returns the offset of an area inside the region.
+    fn offset(&self, start_address: u32, length: usize) -> Result<u32, MemoryError> {
+        let offset = start_address.checked_sub(self.start_address).ok_or(MemoryError::OutOfRange)?;
+        if offset as u64 + length as u64 > self.region.size() as u64 {
+            return Err(MemoryError::OutOfRange);
+        }
+        Ok(offset)
+    }
+}
+
+/// Responder for the Memory Management Service.
+///
+/// Requests are verified with ST01: malformed requests and requests for
+/// areas that are unknown, out of range, not accessible or longer than
+/// `MAX_TRANSFER_LENGTH` fail acceptance, and device errors fail completion.
+pub struct MemoryManagementServiceResponder {
+    parent: Arc<dyn Parent>,
+    regions: Mutex<BTreeMap<u8, RegisteredRegion>>,
+}
+
+impl MemoryManagementServiceResponder {
+    /// Creates a new responder with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        MemoryManagementServiceResponder {
+            parent,
+            regions: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Registers a memory region under a memory ID, replacing any previous region.
+    pub fn register_region(&self, memory_id: u8, start_address: u32, access: Access, region: Box<dyn MemoryRegion>) {
+        self.regions.lock().unwrap().insert(
+            memory_id,
+            RegisteredRegion {
+                start_address,
+                access,
+                region,
+            },
+        );
+    }
+
+    /// Removes the region registered under a memory ID.
+    pub fn unregister_region(&self, memory_id: u8) -> bool {
+        self.regions.lock().unwrap().remove(&memory_id).is_some()
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+        let verification = self.parent.request_verification();
+
+        let accepted = match case {
+            (6, 2) => decode_header(&data).and_then(|(memory_id, start_address, length, rest)| {
+                let chunk = verified_chunk(length, rest).ok()?;
+                self.check_area(memory_id, start_address, chunk.len(), Access::can_write).ok()?;
+                Some(MemoryRequest::Load(memory_id, start_address, chunk))
+            }),
+            (6, 5) => decode_header(&data).and_then(|(memory_id, start_address, length, _)| {
+                if length as usize > MAX_TRANSFER_LENGTH {
+                    return None;
+                }
+                self.check_area(memory_id, start_address, length as usize, Access::can_read).ok()?;
+                Some(MemoryRequest::Dump(memory_id, start_address, length))
+            }),
+            (6, 9) if data.len() >= 9 => {
+                let start_address = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+                let length = u32::from_be_bytes([data[5], data[6], data[7], data[8]]);
+                self.check_area(data[0], start_address, length as usize, Access::can_read)
+                    .ok()
+                    .map(|_| MemoryRequest::Check(data[0], start_address, length))
+            }
+            (6, 9) => None,
+            _ => return,
+        };
+        let Some(request) = accepted else {
+            verification.send_fail_acceptance_report(&[service, subtype]);
+            return;
+        };
+        verification.send_success_acceptance_report(&[service, subtype]);
+        let result = match request {
+            MemoryRequest::Load(memory_id, start_address, chunk) => self.load(memory_id, start_address, chunk),
+            MemoryRequest::Dump(memory_id, start_address, length) => {
+                self.send_dump_report(memory_id, start_address, length)
+            }
+            MemoryRequest::Check(memory_id, start_address, length) => {
+                self.send_check_report(memory_id, start_address, length)
+            }
+        };
+        match result {
+            Ok(()) => verification.send_success_completion_report(&[service, subtype]),
+            Err(_) => verification.send_fail_completion_report(&[service, subtype]),
+        }
+    }
+
+    /// Checks that an area lies inside a registered region that permits the access.
+    fn check_area(
+        &self,
+        memory_id: u8,
+        start_address: u32,
+        length: usize,
+        permits: fn(&Access) -> bool,
+    ) -> Result<u32, MemoryError> {
+        let regions = self.regions.lock().unwrap();
+        let region = regions.get(&memory_id).ok_or(MemoryError::UnknownMemoryId(memory_id))?;
+        if !permits(&region.access) {
+            return Err(MemoryError::AccessDenied);
+        }
+        region.offset(start_address, length)
+    }
+
+    /// Writes the data of a load request (6,2) after checking its length and CRC.
+    pub fn received_load(&self, memory_id: u8, start_address: u32, length: u16, rest: &[u8]) -> Result<(), MemoryError> {
+        let chunk = verified_chunk(length, rest)?;
+        self.load(memory_id, start_address, chunk)
+    }
+
+    /// Writes data into a region.
+    pub fn load(&self, memory_id: u8, start_address: u32, data: &[u8]) -> Result<(), MemoryError> {
+        let offset = self.check_area(memory_id, start_address, data.len(), Access::can_write)?;
+        let mut regions = self.regions.lock().unwrap();
+        let region = regions.get_mut(&memory_id).ok_or(MemoryError::UnknownMemoryId(memory_id))?;
+        region.region.write(offset, data)
+    }
+
+    /// Reads an area of a region.
+    pub fn dump(&self, memory_id: u8, start_address: u32, length: usize) -> Result<Vec<u8>, MemoryError> {
+        let offset = self.check_area(memory_id, start_address, length, Access::can_read)?;
+        let regions = self.regions.lock().unwrap();
+        let region = regions.get(&memory_id).ok_or(MemoryError::UnknownMemoryId(memory_id))?;
+        let mut data = vec![0; length];
+        region.region.read(offset, &mut data)?;
+        Ok(data)
+    }
+
+    /// Computes the CRC-16-CCITT of an area of a region.
+    pub fn checksum(&self, memory_id: u8, start_address: u32, length: u32) -> Result<u16, MemoryError> {
+        let offset = self.check_area(memory_id, start_address, length as usize, Access::can_read)?;
+        let regions = self.regions.lock().unwrap();
+        let region = regions.get(&memory_id).ok_or(MemoryError::UnknownMemoryId(memory_id))?;
+        let mut crc = crc16(&[]);
+        let mut buffer = [0; CHECKSUM_BLOCK_SIZE];
+        for block_offset in (0..length).step_by(CHECKSUM_BLOCK_SIZE) {
+            let block = &mut buffer[..(length - block_offset).min(CHECKSUM_BLOCK_SIZE as u32) as usize];
+            region.region.read(offset + block_offset, block)?;
+            crc = crc16_update(crc, block);
+        }
+        Ok(crc)
+    }
+
+    /// Sends a dump report (6,6) of an area, followed by the CRC of the data.
+    ///
+    /// Areas longer than `MAX_TRANSFER_LENGTH` are rejected without reading memory.
+    pub fn send_dump_report(&self, memory_id: u8, start_address: u32, length: u16) -> Result<(), MemoryError> {
+        if length as usize > MAX_TRANSFER_LENGTH {
+            return Err(MemoryError::OutOfRange);
+        }
+        let data = self.dump(memory_id, start_address, length as usize)?;
+        let mut packet_data = vec![6, 6];
+        packet_data.extend(encode_header(memory_id, start_address, length));
+        packet_data.extend_from_slice(&data);
+        packet_data.extend_from_slice(&crc16(&data).to_be_bytes());
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+        Ok(())
+    }
+
+    /// Sends a check report (6,10) with the checksum of an area.
+    pub fn send_check_report(&self, memory_id: u8, start_address: u32, length: u32) -> Result<(), MemoryError> {
+        let checksum = self.checksum(memory_id, start_address, length)?;
+        let mut packet_data = vec![6, 10, memory_id];
+        packet_data.extend_from_slice(&start_address.to_be_bytes());
+        packet_data.extend_from_slice(&length.to_be_bytes());
+        packet_data.extend_from_slice(&checksum.to_be_bytes());
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+        Ok(())
+    }
+}
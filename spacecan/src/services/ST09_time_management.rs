@@ -0,0 +1,221 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+use std::sync::Mutex;
+
+use crate::primitives::time::{CdsTime, CucTime, OnboardClock};
+
+/// Rate exponent that switches time reporting off.
+pub const TIME_REPORTS_DISABLED: u8 = 0xFF;
+/// Largest rate exponent, i.e. one report every 2^15 seconds.
+const MAX_RATE_EXPONENT: u8 = 15;
+/// Node ID of the controller, the destination of all time reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+}
+
+/// Time report received from a responder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeReport {
+    /// CUC time report (9,2) with the current rate exponent.
+    Cuc { rate_exponent: u8, time: CucTime },
+    /// CDS time report (9,3) with the current rate exponent.
+    Cds { rate_exponent: u8, time: CdsTime },
+}
+
+/// Callback invoked by the controller for every received time report.
+pub type TimeReportHandler = Arc<dyn Fn(u32, &TimeReport) + Send + Sync>;
+
+/// Controller for the Time Management Service.
+pub struct TimeManagementServiceController {
+    parent: Arc<dyn Parent>,
+    reports: Mutex<BTreeMap<u32, TimeReport>>,
+    report_handler: Option<TimeReportHandler>,
+}
+
+impl TimeManagementServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        TimeManagementServiceController {
+            parent,
+            reports: Mutex::new(BTreeMap::new()),
+            report_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every received time report.
+    pub fn set_report_handler(&mut self, handler: TimeReportHandler) {
+        self.report_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        let report = match case {
+            (9, 2) if !data.is_empty() => CucTime::from_bytes(&data[1..]).map(|time| TimeReport::Cuc {
+                rate_exponent: data[0],
+                time,
+            }),
+            (9, 3) if !data.is_empty() => CdsTime::from_bytes(&data[1..]).map(|time| TimeReport::Cds {
+                rate_exponent: data[0],
+                time,
+            }),
+            _ => None,
+        };
+        if let Some(report) = report {
+            self.reports.lock().unwrap().insert(node_id, report);
+            self.received_time_report(node_id, &report);
+        }
+    }
+
+    /// Sets the time report generation rate of a node to one report every 2^exponent seconds (9,1).
+    pub fn send_set_time_report_rate(&self, node_id: u32, rate_exponent: u8) {
+        self.parent.send(Packet::new(vec![9, 1, rate_exponent]), node_id);
+    }
+
+    /// Switches time report generation of a node off.
+    pub fn send_disable_time_reports(&self, node_id: u32) {
+        self.send_set_time_report_rate(node_id, TIME_REPORTS_DISABLED);
+    }
+
+    /// Returns the last time report received from a node.
+    pub fn last_time_report(&self, node_id: u32) -> Option<TimeReport> {
+        self.reports.lock().unwrap().get(&node_id).copied()
+    }
+
+    /// Handler for a received time report.
+    pub fn received_time_report(&self, node_id: u32, report: &TimeReport) {
+        if let Some(handler) = &self.report_handler {
+            handler(node_id, report);
+        }
+    }
+}
+
+/// Responder for the Time Management Service.
+///
+/// Keeps the onboard clock disciplined by the SCET and UTC frames the node
+/// receives, and reports the onboard time at the commanded rate.
+pub struct TimeManagementServiceResponder {
+    parent: Arc<dyn Parent>,
+    clock: Mutex<OnboardClock>,
+    rate_exponent: Mutex<u8>,
+    last_report: Mutex<Option<Duration>>,
+}
+
+impl TimeManagementServiceResponder {
+    /// Creates a new responder with the given parent. Time reporting starts disabled.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        TimeManagementServiceResponder {
+            parent,
+            clock: Mutex::new(OnboardClock::new()),
+            rate_exponent: Mutex::new(TIME_REPORTS_DISABLED),
+            last_report: Mutex::new(None),
+        }
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+
+        if case == (9, 1) && !data.is_empty() {
+            self.set_rate_exponent(data[0]);
+        }
+    }
+
+    /// Sets the report rate exponent. Values above 15 disable reporting.
+    pub fn set_rate_exponent(&self, rate_exponent: u8) {
+        let rate_exponent = if rate_exponent > MAX_RATE_EXPONENT {
+            TIME_REPORTS_DISABLED
+        } else {
+            rate_exponent
+        };
+        *self.rate_exponent.lock().unwrap() = rate_exponent;
+        *self.last_report.lock().unwrap() = None;
+    }
+
+    /// Returns the current report rate exponent.
+    pub fn rate_exponent(&self) -> u8 {
+        *self.rate_exponent.lock().unwrap()
+    }
+
+    /// Feeds a received SCET frame into the onboard clock.
+    pub fn received_scet(&self, time: CucTime, local_now: Duration) {
+        self.clock.lock().unwrap().discipline(time, local_now);
+    }
+
+    /// Feeds a received UTC frame into the onboard clock.
+    pub fn received_utc(&self, time: CdsTime, local_now: Duration) {
+        self.clock.lock().unwrap().discipline_utc(time, local_now);
+    }
+
+    /// Returns a copy of the onboard clock.
+    pub fn clock(&self) -> OnboardClock {
+        self.clock.lock().unwrap().clone()
+    }
+
+    /// Returns the onboard SCET at the given local time.
+    pub fn onboard_time(&self, local_now: Duration) -> Option<CucTime> {
+        self.clock.lock().unwrap().time_at(local_now)
+    }
+
+    /// Sends time reports if the report period has elapsed. Returns true if a report was sent.
+    pub fn poll(&self, local_now: Duration) -> bool {
+        let rate_exponent = self.rate_exponent();
+        if rate_exponent == TIME_REPORTS_DISABLED {
+            return false;
+        }
+        let period = Duration::from_secs(1 << rate_exponent);
+        let mut last_report = self.last_report.lock().unwrap();
+        if last_report.is_some_and(|last| local_now.saturating_sub(last) < period) {
+            return false;
+        }
+        if !self.send_time_report(local_now) {
+            return false;
+        }
+        *last_report = Some(local_now);
+        true
+    }
+
+    /// Sends the CUC time report (9,2) and, if UTC is known, the CDS time report (9,3).
+    ///
+    /// Returns false if the onboard clock is not synchronized yet.
+    pub fn send_time_report(&self, local_now: Duration) -> bool {
+        let clock = self.clock.lock().unwrap().clone();
+        let Some(time) = clock.time_at(local_now) else {
+            return false;
+        };
+        let rate_exponent = self.rate_exponent();
+        let mut packet_data = vec![9, 2, rate_exponent];
+        packet_data.extend_from_slice(&time.to_bytes());
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+
+        if let Some(utc) = clock.utc_at(local_now) {
+            let mut packet_data = vec![9, 3, rate_exponent];
+            packet_data.extend_from_slice(&utc.to_bytes());
+            self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+        }
+        true
+    }
+}
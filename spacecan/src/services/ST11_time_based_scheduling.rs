@@ -0,0 +1,524 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use std::sync::Mutex;
+
+use crate::primitives::time::CucTime;
+use super::encoding::{decode_id_list, encode_id_list};
+
+/// Default number of activities a responder's schedule can hold.
+pub const DEFAULT_SCHEDULE_CAPACITY: usize = 64;
+/// Node ID of the controller, the destination of all schedule reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// CUC fine time units per second.
+const TICKS_PER_SECOND: i64 = 1 << 24;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+    /// Hands a released telecommand to the packet utilization dispatcher.
+    fn dispatch(&self, data: Vec<u8>, node_id: u32);
+    /// Returns the request verification service (ST01) of this node.
+    fn request_verification(&self) -> &dyn RequestVerification;
+}
+
+/// Verification reports sent by the responder, see ST01.
+pub trait RequestVerification {
+    fn send_success_acceptance_report(&self, args: &[u8]);
+    fn send_success_completion_report(&self, args: &[u8]);
+    fn send_fail_acceptance_report(&self, args: &[u8]);
+    fn send_fail_completion_report(&self, args: &[u8]);
+}
+
+/// Telecommand stored in the schedule until its release time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledActivity {
+    /// Identifier chosen by the controller, unique within a node's schedule.
+    pub request_id: u16,
+    /// Onboard time at which the telecommand is released.
+    pub release_time: CucTime,
+    /// Telecommand packet data, starting with service and subtype.
+    pub data: Vec<u8>,
+}
+
+impl ScheduledActivity {
+    /// Creates a new scheduled activity.
+    pub fn new(request_id: u16, release_time: CucTime, data: Vec<u8>) -> Self {
+        ScheduledActivity {
+            request_id,
+            release_time,
+            data,
+        }
+    }
+}
+
+/// Entry of a schedule summary report (11,13).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleSummaryEntry {
+    pub request_id: u16,
+    pub release_time: CucTime,
+}
+
+/// Errors raised when changing a schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// The schedule holds its maximum number of activities.
+    Full,
+    /// An activity with the same request ID is already scheduled.
+    DuplicateRequestId(u16),
+    /// No activity with the given request ID is scheduled.
+    UnknownRequestId(u16),
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::Full => write!(f, "schedule is full"),
+            ScheduleError::DuplicateRequestId(id) => write!(f, "request {} is already scheduled", id),
+            ScheduleError::UnknownRequestId(id) => write!(f, "request {} is not scheduled", id),
+        }
+    }
+}
+
+/// Callback invoked by the controller for every received schedule summary.
+pub type ScheduleSummaryHandler = Arc<dyn Fn(u32, &[ScheduleSummaryEntry]) + Send + Sync>;
+
+/// Encodes activities for an insert request (11,4).
+///
+/// Each activity is its request ID, release time (CUC), telecommand length
+/// (u16) and telecommand data.
+fn encode_activities(activities: &[ScheduledActivity]) -> Vec<u8> {
+    let activities = &activities[..activities.len().min(u8::MAX as usize)];
+    let mut data = vec![activities.len() as u8];
+    for activity in activities {
+        data.extend_from_slice(&activity.request_id.to_be_bytes());
+        data.extend_from_slice(&activity.release_time.to_bytes());
+        data.extend_from_slice(&(activity.data.len() as u16).to_be_bytes());
+        data.extend_from_slice(&activity.data);
+    }
+    data
+}
+
+/// Decodes activities encoded by `encode_activities`.
+fn decode_activities(data: &[u8]) -> Option<Vec<ScheduledActivity>> {
+    let (&count, mut rest) = data.split_first()?;
+    let mut activities = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        if rest.len() < 11 {
+            return None;
+        }
+        let request_id = u16::from_be_bytes([rest[0], rest[1]]);
+        let release_time = CucTime::from_bytes(&rest[2..9])?;
+        let length = u16::from_be_bytes([rest[9], rest[10]]) as usize;
+        rest = &rest[11..];
+        if rest.len() < length {
+            return None;
+        }
+        activities.push(ScheduledActivity::new(request_id, release_time, rest[..length].to_vec()));
+        rest = &rest[length..];
+    }
+    Some(activities)
+}
+
+/// Converts a time shift in milliseconds to CUC ticks.
+fn offset_to_ticks(offset_ms: i32) -> i64 {
+    offset_ms as i64 * TICKS_PER_SECOND / 1000
+}
+
+/// Bounded list of activities ordered by release time.
+///
+/// Activities with equal release time keep their insertion order.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    activities: Vec<ScheduledActivity>,
+    capacity: usize,
+}
+
+impl Schedule {
+    /// Creates an empty schedule holding at most `capacity` activities.
+    pub fn new(capacity: usize) -> Self {
+        Schedule {
+            activities: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the maximum number of activities.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of scheduled activities.
+    pub fn len(&self) -> usize {
+        self.activities.len()
+    }
+
+    /// Returns true if no activity is scheduled.
+    pub fn is_empty(&self) -> bool {
+        self.activities.is_empty()
+    }
+
+    /// Returns the scheduled activities in release order.
+    pub fn activities(&self) -> &[ScheduledActivity] {
+        &self.activities
+    }
+
+    /// Returns the activity with the given request ID.
+    pub fn get(&self, request_id: u16) -> Option<&ScheduledActivity> {
+        self.activities.iter().find(|activity| activity.request_id == request_id)
+    }
+
+    /// Returns the release time of the next activity.
+    pub fn next_release_time(&self) -> Option<CucTime> {
+        self.activities.first().map(|activity| activity.release_time)
+    }
+
+    /// Inserts an activity at its place in release order.
+    pub fn insert(&mut self, activity: ScheduledActivity) -> Result<(), ScheduleError> {
+        if self.get(activity.request_id).is_some() {
+            return Err(ScheduleError::DuplicateRequestId(activity.request_id));
+        }
+        if self.activities.len() >= self.capacity {
+            return Err(ScheduleError::Full);
+        }
+        let index = self
+            .activities
+            .partition_point(|scheduled| scheduled.release_time <= activity.release_time);
+        self.activities.insert(index, activity);
+        Ok(())
+    }
+
+    /// Removes the activity with the given request ID.
+    pub fn remove(&mut self, request_id: u16) -> Result<ScheduledActivity, ScheduleError> {
+        let index = self
+            .activities
+            .iter()
+            .position(|activity| activity.request_id == request_id)
+            .ok_or(ScheduleError::UnknownRequestId(request_id))?;
+        Ok(self.activities.remove(index))
+    }
+
+    /// Removes all activities.
+    pub fn clear(&mut self) {
+        self.activities.clear();
+    }
+
+    /// Moves the release time of an activity by a signed number of milliseconds.
+    pub fn time_shift(&mut self, request_id: u16, offset_ms: i32) -> Result<(), ScheduleError> {
+        let mut activity = self.remove(request_id)?;
+        activity.release_time = shift(activity.release_time, offset_ms);
+        self.insert(activity)
+    }
+
+    /// Moves the release times of all activities by a signed number of milliseconds.
+    pub fn time_shift_all(&mut self, offset_ms: i32) {
+        for activity in &mut self.activities {
+            activity.release_time = shift(activity.release_time, offset_ms);
+        }
+    }
+
+    /// Removes and returns all activities due at the given onboard time.
+    pub fn take_due(&mut self, now: CucTime) -> Vec<ScheduledActivity> {
+        let due = self.activities.partition_point(|activity| activity.release_time <= now);
+        self.activities.drain(..due).collect()
+    }
+}
+
+/// Shifts a release time, saturating at the limits of the CUC range.
+fn shift(time: CucTime, offset_ms: i32) -> CucTime {
+    let ticks = time.ticks() as i64 + offset_to_ticks(offset_ms);
+    CucTime::from_ticks(ticks.clamp(0, CucTime::new(u32::MAX, 0x00FF_FFFF).ticks() as i64) as u64)
+}
+
+/// Controller for the Time-Based Scheduling Service.
+pub struct TimeBasedSchedulingServiceController {
+    parent: Arc<dyn Parent>,
+    summaries: Mutex<BTreeMap<u32, Vec<ScheduleSummaryEntry>>>,
+    summary_handler: Option<ScheduleSummaryHandler>,
+}
+
+impl TimeBasedSchedulingServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        TimeBasedSchedulingServiceController {
+            parent,
+            summaries: Mutex::new(BTreeMap::new()),
+            summary_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every received schedule summary.
+    pub fn set_summary_handler(&mut self, handler: ScheduleSummaryHandler) {
+        self.summary_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        if case == (11, 13) {
+            let Some((&count, entries)) = data.split_first() else {
+                return;
+            };
+            if entries.len() < count as usize * 9 {
+                return;
+            }
+            let summary: Vec<ScheduleSummaryEntry> = entries
+                .chunks_exact(9)
+                .take(count as usize)
+                .filter_map(|entry| {
+                    Some(ScheduleSummaryEntry {
+                        request_id: u16::from_be_bytes([entry[0], entry[1]]),
+                        release_time: CucTime::from_bytes(&entry[2..])?,
+                    })
+                })
+                .collect();
+            self.received_schedule_summary(node_id, &summary);
+            self.summaries.lock().unwrap().insert(node_id, summary);
+        }
+    }
+
+    /// Handler for a received schedule summary report.
+    pub fn received_schedule_summary(&self, node_id: u32, summary: &[ScheduleSummaryEntry]) {
+        if let Some(handler) = &self.summary_handler {
+            handler(node_id, summary);
+        }
+    }
+
+    /// Returns the last schedule summary received from a node.
+    pub fn schedule_summary(&self, node_id: u32) -> Option<Vec<ScheduleSummaryEntry>> {
+        self.summaries.lock().unwrap().get(&node_id).cloned()
+    }
+
+    /// Enables release of scheduled activities on a node (11,1).
+    pub fn send_enable_schedule(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![11, 1]), node_id);
+    }
+
+    /// Disables release of scheduled activities on a node (11,2).
+    pub fn send_disable_schedule(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![11, 2]), node_id);
+    }
+
+    /// Deletes all scheduled activities on a node (11,3).
+    pub fn send_reset_schedule(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![11, 3]), node_id);
+    }
+
+    /// Inserts time-tagged telecommands into the schedule of a node (11,4).
+    pub fn send_insert_activities(&self, node_id: u32, activities: &[ScheduledActivity]) {
+        let mut packet_data = vec![11, 4];
+        packet_data.extend(encode_activities(activities));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Deletes the activities with the given request IDs on a node (11,5).
+    pub fn send_delete_activities(&self, node_id: u32, request_ids: &[u16]) {
+        let mut packet_data = vec![11, 5];
+        packet_data.extend(encode_id_list(request_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Shifts the activities with the given request IDs on a node by `offset_ms` (11,7).
+    pub fn send_time_shift_activities(&self, node_id: u32, offset_ms: i32, request_ids: &[u16]) {
+        let mut packet_data = vec![11, 7];
+        packet_data.extend_from_slice(&offset_ms.to_be_bytes());
+        packet_data.extend(encode_id_list(request_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Shifts all activities on a node by `offset_ms` (11,15).
+    pub fn send_time_shift_all(&self, node_id: u32, offset_ms: i32) {
+        let mut packet_data = vec![11, 15];
+        packet_data.extend_from_slice(&offset_ms.to_be_bytes());
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Requests a summary of the activities with the given request IDs (11,12).
+    pub fn send_summary_report_request(&self, node_id: u32, request_ids: &[u16]) {
+        let mut packet_data = vec![11, 12];
+        packet_data.extend(encode_id_list(request_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Requests a summary of all scheduled activities (11,17).
+    pub fn send_summary_report_all(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![11, 17]), node_id);
+    }
+}
+
+/// Responder for the Time-Based Scheduling Service.
+///
+/// Time-tagged telecommands are kept in a bounded schedule and handed to the
+/// packet utilization dispatcher once the onboard time passed by to `poll`
+/// reaches their release time. Insert, delete and time-shift requests are
+/// verified with ST01: malformed requests fail acceptance, and a request
+/// fails completion if any of its activities cannot be changed.
+pub struct TimeBasedSchedulingServiceResponder {
+    parent: Arc<dyn Parent>,
+    schedule: Mutex<Schedule>,
+    enabled: Mutex<bool>,
+}
+
+impl TimeBasedSchedulingServiceResponder {
+    /// Creates a new responder with the default schedule capacity.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        Self::with_capacity(parent, DEFAULT_SCHEDULE_CAPACITY)
+    }
+
+    /// Creates a new responder whose schedule holds at most `capacity` activities.
+    pub fn with_capacity(parent: Arc<dyn Parent>, capacity: usize) -> Self {
+        TimeBasedSchedulingServiceResponder {
+            parent,
+            schedule: Mutex::new(Schedule::new(capacity)),
+            enabled: Mutex::new(true),
+        }
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+
+        match case {
+            (11, 1) => self.set_enabled(true),
+            (11, 2) => self.set_enabled(false),
+            (11, 3) => self.schedule.lock().unwrap().clear(),
+            (11, 4) => {
+                let Some(activities) = decode_activities(&data) else {
+                    self.parent.request_verification().send_fail_acceptance_report(&[service, subtype]);
+                    return;
+                };
+                self.parent.request_verification().send_success_acceptance_report(&[service, subtype]);
+                let errors: Vec<ScheduleError> =
+                    activities.into_iter().filter_map(|activity| self.insert(activity).err()).collect();
+                self.send_completion_report(service, subtype, &errors);
+            }
+            (11, 5) => {
+                let Some((request_ids, _)) = decode_id_list(&data) else {
+                    self.parent.request_verification().send_fail_acceptance_report(&[service, subtype]);
+                    return;
+                };
+                self.parent.request_verification().send_success_acceptance_report(&[service, subtype]);
+                let mut schedule = self.schedule.lock().unwrap();
+                let errors: Vec<ScheduleError> =
+                    request_ids.into_iter().filter_map(|id| schedule.remove(id).err()).collect();
+                drop(schedule);
+                self.send_completion_report(service, subtype, &errors);
+            }
+            (11, 7) => {
+                let Some((offset, ids)) = data.split_first_chunk::<4>() else {
+                    self.parent.request_verification().send_fail_acceptance_report(&[service, subtype]);
+                    return;
+                };
+                let Some((request_ids, _)) = decode_id_list(ids) else {
+                    self.parent.request_verification().send_fail_acceptance_report(&[service, subtype]);
+                    return;
+                };
+                self.parent.request_verification().send_success_acceptance_report(&[service, subtype]);
+                let offset_ms = i32::from_be_bytes(*offset);
+                let mut schedule = self.schedule.lock().unwrap();
+                let errors: Vec<ScheduleError> = request_ids
+                    .into_iter()
+                    .filter_map(|id| schedule.time_shift(id, offset_ms).err())
+                    .collect();
+                drop(schedule);
+                self.send_completion_report(service, subtype, &errors);
+            }
+            (11, 15) if data.len() >= 4 => {
+                let offset_ms = i32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+                self.schedule.lock().unwrap().time_shift_all(offset_ms);
+            }
+            (11, 12) => {
+                if let Some((request_ids, _)) = decode_id_list(&data) {
+                    self.send_summary_report(Some(&request_ids));
+                }
+            }
+            (11, 17) => self.send_summary_report(None),
+            _ => {}
+        }
+    }
+
+    /// Enables or disables release of scheduled activities.
+    ///
+    /// While disabled, activities stay in the schedule; overdue activities
+    /// are released on the first poll after enabling.
+    pub fn set_enabled(&self, enabled: bool) {
+        *self.enabled.lock().unwrap() = enabled;
+    }
+
+    /// Returns true if scheduled activities are released.
+    pub fn is_enabled(&self) -> bool {
+        *self.enabled.lock().unwrap()
+    }
+
+    /// Inserts an activity into the schedule.
+    pub fn insert(&self, activity: ScheduledActivity) -> Result<(), ScheduleError> {
+        self.schedule.lock().unwrap().insert(activity)
+    }
+
+    /// Returns a copy of the schedule.
+    pub fn schedule(&self) -> Schedule {
+        self.schedule.lock().unwrap().clone()
+    }
+
+    /// Releases all activities due at the given onboard time to the dispatcher.
+    ///
+    /// Returns the number of released activities.
+    pub fn poll(&self, now: CucTime) -> usize {
+        if !self.is_enabled() {
+            return 0;
+        }
+        // Release outside the lock: a scheduled telecommand may target this service.
+        let due = self.schedule.lock().unwrap().take_due(now);
+        let released = due.len();
+        for activity in due {
+            self.parent.dispatch(activity.data, CONTROLLER_NODE_ID);
+        }
+        released
+    }
+
+    /// Sends a schedule summary report (11,13) of the given or all activities.
+    pub fn send_summary_report(&self, request_ids: Option<&[u16]>) {
+        let schedule = self.schedule.lock().unwrap();
+        let entries: Vec<&ScheduledActivity> = schedule
+            .activities()
+            .iter()
+            .filter(|activity| request_ids.is_none_or(|ids| ids.contains(&activity.request_id)))
+            .take(u8::MAX as usize)
+            .collect();
+        let mut packet_data = vec![11, 13, entries.len() as u8];
+        for activity in entries {
+            packet_data.extend_from_slice(&activity.request_id.to_be_bytes());
+            packet_data.extend_from_slice(&activity.release_time.to_bytes());
+        }
+        drop(schedule);
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+
+    /// Sends the completion report of a request, failed if any activity could not be changed.
+    fn send_completion_report(&self, service: u8, subtype: u8, errors: &[ScheduleError]) {
+        if errors.is_empty() {
+            self.parent.request_verification().send_success_completion_report(&[service, subtype]);
+        } else {
+            self.parent.request_verification().send_fail_completion_report(&[service, subtype]);
+        }
+    }
+}
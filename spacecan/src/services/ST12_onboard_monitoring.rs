@@ -0,0 +1,631 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+use super::encoding::{decode_id_list, encode_id_list};
+use super::ST05_event_reporting::EventReportingServiceResponder;
+
+/// Node ID of the controller, the destination of all monitoring reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// Encoded size of a monitoring definition.
+const DEFINITION_SIZE: usize = 27;
+/// Encoded size of a check transition.
+const TRANSITION_SIZE: usize = 16;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+    /// Returns the current value of a parameter of this node (ST20).
+    fn get_parameter_value(&self, parameter_id: u32) -> Option<f64>;
+}
+
+/// Check applied to the monitored parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Check {
+    /// The value must lie within `low..=high`.
+    Limit { low: f64, high: f64 },
+    /// The value, masked with `mask`, must equal `expected`.
+    ExpectedValue { mask: u64, expected: u64 },
+    /// The change since the previous sample must lie within `low..=high`.
+    Delta { low: f64, high: f64 },
+}
+
+impl Check {
+    fn type_code(&self) -> u8 {
+        match self {
+            Check::Limit { .. } => 1,
+            Check::ExpectedValue { .. } => 2,
+            Check::Delta { .. } => 3,
+        }
+    }
+}
+
+/// Checking status of a monitoring definition, with the ECSS status codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    Unchecked = 0,
+    /// The parameter value could not be obtained.
+    Invalid = 1,
+    ExpectedValue = 2,
+    UnexpectedValue = 3,
+    WithinLimits = 4,
+    BelowLowLimit = 5,
+    AboveHighLimit = 6,
+    WithinThresholds = 7,
+    BelowLowThreshold = 8,
+    AboveHighThreshold = 9,
+}
+
+impl CheckStatus {
+    /// Returns the status with the given code.
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(CheckStatus::Unchecked),
+            1 => Some(CheckStatus::Invalid),
+            2 => Some(CheckStatus::ExpectedValue),
+            3 => Some(CheckStatus::UnexpectedValue),
+            4 => Some(CheckStatus::WithinLimits),
+            5 => Some(CheckStatus::BelowLowLimit),
+            6 => Some(CheckStatus::AboveHighLimit),
+            7 => Some(CheckStatus::WithinThresholds),
+            8 => Some(CheckStatus::BelowLowThreshold),
+            9 => Some(CheckStatus::AboveHighThreshold),
+            _ => None,
+        }
+    }
+
+    /// Returns the status code.
+    pub fn code(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Returns true if the status reports a violated check.
+    pub fn is_violation(&self) -> bool {
+        matches!(
+            self,
+            CheckStatus::UnexpectedValue
+                | CheckStatus::BelowLowLimit
+                | CheckStatus::AboveHighLimit
+                | CheckStatus::BelowLowThreshold
+                | CheckStatus::AboveHighThreshold
+        )
+    }
+}
+
+/// Parameter monitoring definition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonitoringDefinition {
+    pub monitoring_id: u16,
+    /// ST20 parameter ID on the monitoring node.
+    pub parameter_id: u32,
+    /// Number of consecutive samples with a new result before the status changes.
+    pub repetition: u8,
+    pub check: Check,
+    /// ST05 event raised when the check becomes violated.
+    pub event_id: Option<u16>,
+}
+
+impl MonitoringDefinition {
+    /// Creates a new monitoring definition without event generation.
+    pub fn new(monitoring_id: u16, parameter_id: u32, repetition: u8, check: Check) -> Self {
+        MonitoringDefinition {
+            monitoring_id,
+            parameter_id,
+            repetition,
+            check,
+            event_id: None,
+        }
+    }
+
+    /// Raises the given ST05 event when the check becomes violated.
+    pub fn with_event(mut self, event_id: u16) -> Self {
+        self.event_id = Some(event_id);
+        self
+    }
+
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.extend_from_slice(&self.monitoring_id.to_be_bytes());
+        data.extend_from_slice(&self.parameter_id.to_be_bytes());
+        data.push(self.repetition);
+        match self.event_id {
+            Some(event_id) => {
+                data.push(1);
+                data.extend_from_slice(&event_id.to_be_bytes());
+            }
+            None => data.extend_from_slice(&[0, 0, 0]),
+        }
+        data.push(self.check.type_code());
+        match self.check {
+            Check::Limit { low, high } | Check::Delta { low, high } => {
+                data.extend_from_slice(&low.to_be_bytes());
+                data.extend_from_slice(&high.to_be_bytes());
+            }
+            Check::ExpectedValue { mask, expected } => {
+                data.extend_from_slice(&mask.to_be_bytes());
+                data.extend_from_slice(&expected.to_be_bytes());
+            }
+        }
+    }
+
+    /// Decodes a definition, returning it and the remaining data.
+    fn decode(data: &[u8]) -> Option<(Self, &[u8])> {
+        if data.len() < DEFINITION_SIZE {
+            return None;
+        }
+        let first = u64::from_be_bytes(data[11..19].try_into().ok()?);
+        let second = u64::from_be_bytes(data[19..27].try_into().ok()?);
+        let check = match data[10] {
+            1 => Check::Limit {
+                low: f64::from_bits(first),
+                high: f64::from_bits(second),
+            },
+            2 => Check::ExpectedValue {
+                mask: first,
+                expected: second,
+            },
+            3 => Check::Delta {
+                low: f64::from_bits(first),
+                high: f64::from_bits(second),
+            },
+            _ => return None,
+        };
+        let definition = MonitoringDefinition {
+            monitoring_id: u16::from_be_bytes([data[0], data[1]]),
+            parameter_id: u32::from_be_bytes([data[2], data[3], data[4], data[5]]),
+            repetition: data[6],
+            check,
+            event_id: (data[7] != 0).then(|| u16::from_be_bytes([data[8], data[9]])),
+        };
+        Some((definition, &data[DEFINITION_SIZE..]))
+    }
+}
+
+/// Change of the checking status of a monitoring definition, reported with (12,12).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CheckTransition {
+    pub monitoring_id: u16,
+    pub parameter_id: u32,
+    /// Parameter value that caused the transition.
+    pub value: f64,
+    pub previous_status: CheckStatus,
+    pub current_status: CheckStatus,
+}
+
+impl CheckTransition {
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.extend_from_slice(&self.monitoring_id.to_be_bytes());
+        data.extend_from_slice(&self.parameter_id.to_be_bytes());
+        data.extend_from_slice(&self.value.to_be_bytes());
+        data.push(self.previous_status.code());
+        data.push(self.current_status.code());
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < TRANSITION_SIZE {
+            return None;
+        }
+        Some(CheckTransition {
+            monitoring_id: u16::from_be_bytes([data[0], data[1]]),
+            parameter_id: u32::from_be_bytes([data[2], data[3], data[4], data[5]]),
+            value: f64::from_be_bytes(data[6..14].try_into().ok()?),
+            previous_status: CheckStatus::from_code(data[14])?,
+            current_status: CheckStatus::from_code(data[15])?,
+        })
+    }
+}
+
+/// Callback invoked by the controller for every received check transition.
+pub type CheckTransitionHandler = Arc<dyn Fn(u32, &CheckTransition) + Send + Sync>;
+
+/// Encodes definitions for an add (12,5) or modify (12,7) request.
+fn encode_definitions(definitions: &[MonitoringDefinition]) -> Vec<u8> {
+    let definitions = &definitions[..definitions.len().min(u8::MAX as usize)];
+    let mut data = vec![definitions.len() as u8];
+    for definition in definitions {
+        definition.encode(&mut data);
+    }
+    data
+}
+
+/// Decodes definitions encoded by `encode_definitions`.
+fn decode_definitions(data: &[u8]) -> Option<Vec<MonitoringDefinition>> {
+    let (&count, mut rest) = data.split_first()?;
+    let mut definitions = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let (definition, remaining) = MonitoringDefinition::decode(rest)?;
+        definitions.push(definition);
+        rest = remaining;
+    }
+    Some(definitions)
+}
+
+/// Monitoring definition together with its checking state.
+#[derive(Debug, Clone)]
+struct Monitor {
+    definition: MonitoringDefinition,
+    enabled: bool,
+    status: CheckStatus,
+    // Result waiting for the repetition count to be reached.
+    candidate: CheckStatus,
+    repetitions: u8,
+    previous_value: Option<f64>,
+}
+
+impl Monitor {
+    fn new(definition: MonitoringDefinition) -> Self {
+        Monitor {
+            definition,
+            enabled: true,
+            status: CheckStatus::Unchecked,
+            candidate: CheckStatus::Unchecked,
+            repetitions: 0,
+            previous_value: None,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.status = CheckStatus::Unchecked;
+        self.candidate = CheckStatus::Unchecked;
+        self.repetitions = 0;
+        self.previous_value = None;
+    }
+
+    /// Evaluates the check for one sample. Returns None if no result is available yet.
+    fn evaluate(&mut self, value: f64) -> Option<CheckStatus> {
+        match self.definition.check {
+            Check::Limit { low, high } => Some(if value < low {
+                CheckStatus::BelowLowLimit
+            } else if value > high {
+                CheckStatus::AboveHighLimit
+            } else {
+                CheckStatus::WithinLimits
+            }),
+            Check::ExpectedValue { mask, expected } => Some(if (value as u64) & mask == expected {
+                CheckStatus::ExpectedValue
+            } else {
+                CheckStatus::UnexpectedValue
+            }),
+            Check::Delta { low, high } => {
+                let previous = self.previous_value.replace(value)?;
+                let delta = value - previous;
+                Some(if delta < low {
+                    CheckStatus::BelowLowThreshold
+                } else if delta > high {
+                    CheckStatus::AboveHighThreshold
+                } else {
+                    CheckStatus::WithinThresholds
+                })
+            }
+        }
+    }
+
+    /// Feeds one sample into the monitor. Returns the transition if the status changed.
+    fn sample(&mut self, value: Option<f64>) -> Option<CheckTransition> {
+        let result = match value {
+            Some(value) => self.evaluate(value)?,
+            None => CheckStatus::Invalid,
+        };
+        if result == self.status {
+            self.repetitions = 0;
+            return None;
+        }
+        if result == self.candidate {
+            self.repetitions = self.repetitions.saturating_add(1);
+        } else {
+            self.candidate = result;
+            self.repetitions = 1;
+        }
+        if self.repetitions < self.definition.repetition.max(1) {
+            return None;
+        }
+        let transition = CheckTransition {
+            monitoring_id: self.definition.monitoring_id,
+            parameter_id: self.definition.parameter_id,
+            value: value.unwrap_or(f64::NAN),
+            previous_status: self.status,
+            current_status: result,
+        };
+        self.status = result;
+        self.repetitions = 0;
+        Some(transition)
+    }
+}
+
+/// Controller for the On-Board Monitoring Service.
+pub struct OnboardMonitoringServiceController {
+    parent: Arc<dyn Parent>,
+    statuses: Mutex<BTreeMap<(u32, u16), CheckStatus>>,
+    transition_handler: Option<CheckTransitionHandler>,
+}
+
+impl OnboardMonitoringServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        OnboardMonitoringServiceController {
+            parent,
+            statuses: Mutex::new(BTreeMap::new()),
+            transition_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every received check transition.
+    pub fn set_transition_handler(&mut self, handler: CheckTransitionHandler) {
+        self.transition_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        if case == (12, 12) {
+            let Some((&count, transitions)) = data.split_first() else {
+                return;
+            };
+            for transition in transitions
+                .chunks_exact(TRANSITION_SIZE)
+                .take(count as usize)
+                .filter_map(CheckTransition::decode)
+            {
+                self.statuses
+                    .lock()
+                    .unwrap()
+                    .insert((node_id, transition.monitoring_id), transition.current_status);
+                self.received_check_transition(node_id, &transition);
+            }
+        }
+    }
+
+    /// Handler for a received check transition.
+    pub fn received_check_transition(&self, node_id: u32, transition: &CheckTransition) {
+        if let Some(handler) = &self.transition_handler {
+            handler(node_id, transition);
+        }
+    }
+
+    /// Returns the last reported status of a monitoring definition on a node.
+    pub fn check_status(&self, node_id: u32, monitoring_id: u16) -> Option<CheckStatus> {
+        self.statuses.lock().unwrap().get(&(node_id, monitoring_id)).copied()
+    }
+
+    /// Enables the given monitoring definitions on a node (12,1).
+    pub fn send_enable_definitions(&self, node_id: u32, monitoring_ids: &[u16]) {
+        let mut packet_data = vec![12, 1];
+        packet_data.extend(encode_id_list(monitoring_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Disables the given monitoring definitions on a node (12,2).
+    pub fn send_disable_definitions(&self, node_id: u32, monitoring_ids: &[u16]) {
+        let mut packet_data = vec![12, 2];
+        packet_data.extend(encode_id_list(monitoring_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Adds monitoring definitions to a node (12,5).
+    pub fn send_add_definitions(&self, node_id: u32, definitions: &[MonitoringDefinition]) {
+        let mut packet_data = vec![12, 5];
+        packet_data.extend(encode_definitions(definitions));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Deletes the given monitoring definitions from a node (12,6).
+    pub fn send_delete_definitions(&self, node_id: u32, monitoring_ids: &[u16]) {
+        let mut packet_data = vec![12, 6];
+        packet_data.extend(encode_id_list(monitoring_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Replaces existing monitoring definitions on a node (12,7).
+    pub fn send_modify_definitions(&self, node_id: u32, definitions: &[MonitoringDefinition]) {
+        let mut packet_data = vec![12, 7];
+        packet_data.extend(encode_definitions(definitions));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Enables the parameter monitoring function of a node (12,15).
+    pub fn send_enable_monitoring(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![12, 15]), node_id);
+    }
+
+    /// Disables the parameter monitoring function of a node (12,16).
+    pub fn send_disable_monitoring(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![12, 16]), node_id);
+    }
+}
+
+/// Responder for the On-Board Monitoring Service.
+///
+/// Each `poll` samples the monitored ST20 parameters through the parent,
+/// updates the checking status of every enabled definition and reports
+/// status changes in one check transition report (12,12).
+pub struct OnboardMonitoringServiceResponder {
+    parent: Arc<dyn Parent>,
+    monitors: Mutex<BTreeMap<u16, Monitor>>,
+    enabled: Mutex<bool>,
+    event_reporting: Option<Arc<EventReportingServiceResponder>>,
+}
+
+impl OnboardMonitoringServiceResponder {
+    /// Creates a new responder with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        OnboardMonitoringServiceResponder {
+            parent,
+            monitors: Mutex::new(BTreeMap::new()),
+            enabled: Mutex::new(true),
+            event_reporting: None,
+        }
+    }
+
+    /// Raises the definitions' ST05 events through the given event reporting responder.
+    ///
+    /// The auxiliary data of such events is the monitoring ID and the
+    /// parameter value, encoded as `!Hd`.
+    pub fn set_event_reporting(&mut self, event_reporting: Arc<EventReportingServiceResponder>) {
+        self.event_reporting = Some(event_reporting);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+
+        match case {
+            (12, 1) | (12, 2) => {
+                if let Some((monitoring_ids, _)) = decode_id_list(&data) {
+                    for monitoring_id in monitoring_ids {
+                        self.set_definition_enabled(monitoring_id, subtype == 1);
+                    }
+                }
+            }
+            (12, 5) => {
+                if let Some(definitions) = decode_definitions(&data) {
+                    for definition in definitions {
+                        self.add_definition(definition);
+                    }
+                }
+            }
+            (12, 6) => {
+                if let Some((monitoring_ids, _)) = decode_id_list(&data) {
+                    for monitoring_id in monitoring_ids {
+                        self.delete_definition(monitoring_id);
+                    }
+                }
+            }
+            (12, 7) => {
+                if let Some(definitions) = decode_definitions(&data) {
+                    for definition in definitions {
+                        self.modify_definition(definition);
+                    }
+                }
+            }
+            (12, 15) => self.set_enabled(true),
+            (12, 16) => self.set_enabled(false),
+            _ => {}
+        }
+    }
+
+    /// Adds a monitoring definition, unless one with the same ID exists.
+    ///
+    /// Returns true if the definition was added.
+    pub fn add_definition(&self, definition: MonitoringDefinition) -> bool {
+        let mut monitors = self.monitors.lock().unwrap();
+        if monitors.contains_key(&definition.monitoring_id) {
+            return false;
+        }
+        monitors.insert(definition.monitoring_id, Monitor::new(definition));
+        true
+    }
+
+    /// Replaces an existing monitoring definition and restarts its checking.
+    ///
+    /// Returns true if the definition existed.
+    pub fn modify_definition(&self, definition: MonitoringDefinition) -> bool {
+        let mut monitors = self.monitors.lock().unwrap();
+        let Some(monitor) = monitors.get_mut(&definition.monitoring_id) else {
+            return false;
+        };
+        monitor.definition = definition;
+        monitor.reset();
+        true
+    }
+
+    /// Deletes a monitoring definition. Returns true if it existed.
+    pub fn delete_definition(&self, monitoring_id: u16) -> bool {
+        self.monitors.lock().unwrap().remove(&monitoring_id).is_some()
+    }
+
+    /// Returns a monitoring definition.
+    pub fn get_definition(&self, monitoring_id: u16) -> Option<MonitoringDefinition> {
+        self.monitors.lock().unwrap().get(&monitoring_id).map(|monitor| monitor.definition)
+    }
+
+    /// Enables or disables a monitoring definition. Disabling resets its status to unchecked.
+    pub fn set_definition_enabled(&self, monitoring_id: u16, enabled: bool) {
+        if let Some(monitor) = self.monitors.lock().unwrap().get_mut(&monitoring_id) {
+            monitor.enabled = enabled;
+            if !enabled {
+                monitor.reset();
+            }
+        }
+    }
+
+    /// Enables or disables the parameter monitoring function as a whole.
+    pub fn set_enabled(&self, enabled: bool) {
+        *self.enabled.lock().unwrap() = enabled;
+    }
+
+    /// Returns true if the parameter monitoring function is enabled.
+    pub fn is_enabled(&self) -> bool {
+        *self.enabled.lock().unwrap()
+    }
+
+    /// Returns the checking status of a monitoring definition.
+    pub fn check_status(&self, monitoring_id: u16) -> Option<CheckStatus> {
+        self.monitors.lock().unwrap().get(&monitoring_id).map(|monitor| monitor.status)
+    }
+
+    /// Samples all enabled definitions and reports status changes.
+    ///
+    /// Returns the check transitions of this poll.
+    pub fn poll(&self) -> Vec<CheckTransition> {
+        if !self.is_enabled() {
+            return Vec::new();
+        }
+        let mut transitions = Vec::new();
+        let mut events = Vec::new();
+        {
+            let mut monitors = self.monitors.lock().unwrap();
+            for monitor in monitors.values_mut().filter(|monitor| monitor.enabled) {
+                let value = self.parent.get_parameter_value(monitor.definition.parameter_id);
+                if let Some(transition) = monitor.sample(value) {
+                    if let Some(event_id) = monitor.definition.event_id
+                        && transition.current_status.is_violation()
+                    {
+                        events.push((event_id, transition));
+                    }
+                    transitions.push(transition);
+                }
+            }
+        }
+        if !transitions.is_empty() {
+            self.send_check_transition_report(&transitions);
+        }
+        if let Some(event_reporting) = &self.event_reporting {
+            for (event_id, transition) in events {
+                let mut auxiliary_data = transition.monitoring_id.to_be_bytes().to_vec();
+                auxiliary_data.extend_from_slice(&transition.value.to_be_bytes());
+                event_reporting.raise_event_raw(event_id, &auxiliary_data);
+            }
+        }
+        transitions
+    }
+
+    /// Sends a check transition report (12,12).
+    ///
+    /// At most 255 transitions fit into one report; further transitions are dropped.
+    pub fn send_check_transition_report(&self, transitions: &[CheckTransition]) {
+        let transitions = &transitions[..transitions.len().min(u8::MAX as usize)];
+        let mut packet_data = vec![12, 12, transitions.len() as u8];
+        for transition in transitions {
+            transition.encode(&mut packet_data);
+        }
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
@@ -0,0 +1,473 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+use std::sync::Mutex;
+
+use crate::primitives::packet::MAX_PACKET_LENGTH;
+
+/// Default number of message bytes carried by one part.
+pub const DEFAULT_PART_SIZE: usize = 1024;
+/// Default time allowed between two parts before the reception is aborted.
+pub const DEFAULT_RECEPTION_TIMEOUT: Duration = Duration::from_secs(10);
+/// Default largest message accepted by a receiver.
+pub const DEFAULT_MAX_MESSAGE_LENGTH: usize = 64 * 1024;
+/// Node ID of the controller, the destination of all down-link parts.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// Size of the service header, transaction ID and part sequence number.
+const PART_HEADER_SIZE: usize = 6;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+    /// Hands a reassembled up-link message to the packet utilization dispatcher.
+    fn dispatch(&self, data: Vec<u8>, node_id: u32);
+}
+
+/// Position of a part within a large message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartKind {
+    First,
+    Intermediate,
+    Last,
+}
+
+impl PartKind {
+    /// Returns the down-link part subtype, (13,1) to (13,3).
+    fn downlink_subtype(&self) -> u8 {
+        match self {
+            PartKind::First => 1,
+            PartKind::Intermediate => 2,
+            PartKind::Last => 3,
+        }
+    }
+
+    /// Returns the up-link part subtype, (13,9) to (13,11).
+    fn uplink_subtype(&self) -> u8 {
+        self.downlink_subtype() + 8
+    }
+
+    fn from_downlink_subtype(subtype: u8) -> Option<Self> {
+        match subtype {
+            1 => Some(PartKind::First),
+            2 => Some(PartKind::Intermediate),
+            3 => Some(PartKind::Last),
+            _ => None,
+        }
+    }
+
+    fn from_uplink_subtype(subtype: u8) -> Option<Self> {
+        Self::from_downlink_subtype(subtype.checked_sub(8)?)
+    }
+}
+
+/// Reason a large message reception was aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbortReason {
+    /// No part arrived within the reception timeout.
+    Timeout = 1,
+    /// A part arrived out of sequence.
+    SequenceError = 2,
+    /// An intermediate or last part arrived for an unknown transaction.
+    UnknownTransaction = 3,
+    /// The message exceeds the largest accepted length.
+    TooLarge = 4,
+}
+
+impl AbortReason {
+    /// Returns the reason with the given code.
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(AbortReason::Timeout),
+            2 => Some(AbortReason::SequenceError),
+            3 => Some(AbortReason::UnknownTransaction),
+            4 => Some(AbortReason::TooLarge),
+            _ => None,
+        }
+    }
+}
+
+/// Aborted reception of a large message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Abort {
+    pub node_id: u32,
+    pub transaction_id: u16,
+    pub reason: AbortReason,
+}
+
+/// Reassembled large message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LargeMessage {
+    pub transaction_id: u16,
+    pub data: Vec<u8>,
+}
+
+/// Callback invoked by the controller for every reassembled down-link message.
+pub type LargeMessageHandler = Arc<dyn Fn(u32, u16, &[u8]) + Send + Sync>;
+
+/// Splits a message into part packets: service header, transaction ID,
+/// part sequence number and part data.
+///
+/// A message that fits into one part is still sent as a first and a last part.
+pub fn split_message(
+    message: &[u8],
+    transaction_id: u16,
+    part_size: usize,
+    subtype: impl Fn(PartKind) -> u8,
+) -> Vec<Vec<u8>> {
+    let mut parts: Vec<&[u8]> = message.chunks(part_size).collect();
+    while parts.len() < 2 {
+        parts.push(&[]);
+    }
+    let last = parts.len() - 1;
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(sequence, part)| {
+            let kind = match sequence {
+                0 => PartKind::First,
+                n if n == last => PartKind::Last,
+                _ => PartKind::Intermediate,
+            };
+            let mut packet_data = vec![13, subtype(kind)];
+            packet_data.extend_from_slice(&transaction_id.to_be_bytes());
+            packet_data.extend_from_slice(&(sequence as u16).to_be_bytes());
+            packet_data.extend_from_slice(part);
+            packet_data
+        })
+        .collect()
+}
+
+/// Message being reassembled.
+#[derive(Debug)]
+struct Reception {
+    data: Vec<u8>,
+    next_sequence: u16,
+    last_part: Duration,
+}
+
+/// Reassembles large messages from their parts.
+///
+/// Receptions are keyed by sending node and transaction ID. A reception is
+/// aborted if a part is missing or out of order, if the message grows
+/// beyond the maximum length, or if no part arrives within the timeout.
+#[derive(Debug)]
+pub struct LargeMessageReceiver {
+    timeout: Duration,
+    max_message_length: usize,
+    receptions: BTreeMap<(u32, u16), Reception>,
+}
+
+impl Default for LargeMessageReceiver {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECEPTION_TIMEOUT, DEFAULT_MAX_MESSAGE_LENGTH)
+    }
+}
+
+impl LargeMessageReceiver {
+    /// Creates a receiver with the given part timeout and maximum message length.
+    pub fn new(timeout: Duration, max_message_length: usize) -> Self {
+        LargeMessageReceiver {
+            timeout,
+            max_message_length,
+            receptions: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of receptions in progress.
+    pub fn pending(&self) -> usize {
+        self.receptions.len()
+    }
+
+    /// Processes one part. Returns the message once its last part arrived.
+    ///
+    /// `data` is the part packet without the service header.
+    pub fn receive_part(
+        &mut self,
+        node_id: u32,
+        kind: PartKind,
+        data: &[u8],
+        now: Duration,
+    ) -> Result<Option<Vec<u8>>, Abort> {
+        if data.len() < PART_HEADER_SIZE - 2 {
+            return Ok(None);
+        }
+        let transaction_id = u16::from_be_bytes([data[0], data[1]]);
+        let sequence = u16::from_be_bytes([data[2], data[3]]);
+        let part = &data[PART_HEADER_SIZE - 2..];
+        let key = (node_id, transaction_id);
+        let abort = |reason| Abort {
+            node_id,
+            transaction_id,
+            reason,
+        };
+
+        if kind == PartKind::First {
+            // A new first part restarts the transaction.
+            self.receptions.insert(
+                key,
+                Reception {
+                    data: Vec::new(),
+                    next_sequence: 0,
+                    last_part: now,
+                },
+            );
+        }
+        let Some(reception) = self.receptions.get_mut(&key) else {
+            return Err(abort(AbortReason::UnknownTransaction));
+        };
+        if sequence != reception.next_sequence {
+            self.receptions.remove(&key);
+            return Err(abort(AbortReason::SequenceError));
+        }
+        if reception.data.len() + part.len() > self.max_message_length {
+            self.receptions.remove(&key);
+            return Err(abort(AbortReason::TooLarge));
+        }
+        reception.data.extend_from_slice(part);
+        reception.next_sequence = reception.next_sequence.wrapping_add(1);
+        reception.last_part = now;
+
+        if kind == PartKind::Last {
+            return Ok(self.receptions.remove(&key).map(|reception| reception.data));
+        }
+        Ok(None)
+    }
+
+    /// Aborts all receptions without a part within the timeout.
+    pub fn check_timeouts(&mut self, now: Duration) -> Vec<Abort> {
+        let timeout = self.timeout;
+        let expired: Vec<(u32, u16)> = self
+            .receptions
+            .iter()
+            .filter(|(_, reception)| now.saturating_sub(reception.last_part) > timeout)
+            .map(|(key, _)| *key)
+            .collect();
+        expired
+            .into_iter()
+            .map(|(node_id, transaction_id)| {
+                self.receptions.remove(&(node_id, transaction_id));
+                Abort {
+                    node_id,
+                    transaction_id,
+                    reason: AbortReason::Timeout,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Checks that parts of the given size fit into a packet.
+fn checked_part_size(part_size: usize) -> usize {
+    part_size.clamp(1, MAX_PACKET_LENGTH - PART_HEADER_SIZE)
+}
+
+/// Controller for the Large Packet Transfer Service.
+pub struct LargePacketTransferServiceController {
+    parent: Arc<dyn Parent>,
+    part_size: usize,
+    next_transaction_id: Mutex<u16>,
+    receiver: Mutex<LargeMessageReceiver>,
+    messages: Mutex<BTreeMap<u32, Vec<LargeMessage>>>,
+    aborts: Mutex<Vec<Abort>>,
+    message_handler: Option<LargeMessageHandler>,
+}
+
+impl LargePacketTransferServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        LargePacketTransferServiceController {
+            parent,
+            part_size: DEFAULT_PART_SIZE,
+            next_transaction_id: Mutex::new(0),
+            receiver: Mutex::new(LargeMessageReceiver::default()),
+            messages: Mutex::new(BTreeMap::new()),
+            aborts: Mutex::new(Vec::new()),
+            message_handler: None,
+        }
+    }
+
+    /// Sets the number of message bytes per up-link part, limited to what fits into a packet.
+    pub fn set_part_size(&mut self, part_size: usize) {
+        self.part_size = checked_part_size(part_size);
+    }
+
+    /// Replaces the down-link receiver, e.g. to change its timeout.
+    pub fn set_receiver(&mut self, receiver: LargeMessageReceiver) {
+        self.receiver = Mutex::new(receiver);
+    }
+
+    /// Sets the callback invoked for every reassembled down-link message.
+    pub fn set_message_handler(&mut self, handler: LargeMessageHandler) {
+        self.message_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    ///
+    /// `now` is the local time used for reception timeouts.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32, now: Duration) {
+        let case = (service, subtype);
+
+        match case {
+            (13, 1..=3) => {
+                let kind = PartKind::from_downlink_subtype(subtype).unwrap();
+                let result = self.receiver.lock().unwrap().receive_part(node_id, kind, &data, now);
+                match result {
+                    Ok(Some(message_data)) => {
+                        let message = LargeMessage {
+                            transaction_id: u16::from_be_bytes([data[0], data[1]]),
+                            data: message_data,
+                        };
+                        if let Some(handler) = &self.message_handler {
+                            handler(node_id, message.transaction_id, &message.data);
+                        }
+                        self.messages.lock().unwrap().entry(node_id).or_default().push(message);
+                    }
+                    Ok(None) => {}
+                    Err(abort) => self.aborts.lock().unwrap().push(abort),
+                }
+            }
+            (13, 16) if data.len() >= 3 => {
+                if let Some(reason) = AbortReason::from_code(data[2]) {
+                    self.aborts.lock().unwrap().push(Abort {
+                        node_id,
+                        transaction_id: u16::from_be_bytes([data[0], data[1]]),
+                        reason,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Aborts down-link receptions that timed out.
+    pub fn poll(&self, now: Duration) {
+        let aborts = self.receiver.lock().unwrap().check_timeouts(now);
+        self.aborts.lock().unwrap().extend(aborts);
+    }
+
+    /// Sends a large message to a node as up-link parts (13,9) to (13,11).
+    ///
+    /// Returns the transaction ID of the transfer.
+    pub fn send_large_message(&self, node_id: u32, message: &[u8]) -> u16 {
+        let transaction_id = {
+            let mut next = self.next_transaction_id.lock().unwrap();
+            let transaction_id = *next;
+            *next = next.wrapping_add(1);
+            transaction_id
+        };
+        for part in split_message(message, transaction_id, self.part_size, |kind| kind.uplink_subtype()) {
+            self.parent.send(Packet::new(part), node_id);
+        }
+        transaction_id
+    }
+
+    /// Removes and returns the messages received from a node.
+    pub fn take_messages(&self, node_id: u32) -> Vec<LargeMessage> {
+        self.messages.lock().unwrap().remove(&node_id).unwrap_or_default()
+    }
+
+    /// Removes and returns the aborted transfers in both directions.
+    pub fn take_aborts(&self) -> Vec<Abort> {
+        core::mem::take(&mut *self.aborts.lock().unwrap())
+    }
+}
+
+/// Responder for the Large Packet Transfer Service.
+///
+/// Reassembled up-link messages are handed to the packet utilization
+/// dispatcher; failed receptions are reported with (13,16).
+pub struct LargePacketTransferServiceResponder {
+    parent: Arc<dyn Parent>,
+    part_size: usize,
+    next_transaction_id: Mutex<u16>,
+    receiver: Mutex<LargeMessageReceiver>,
+}
+
+impl LargePacketTransferServiceResponder {
+    /// Creates a new responder with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        LargePacketTransferServiceResponder {
+            parent,
+            part_size: DEFAULT_PART_SIZE,
+            next_transaction_id: Mutex::new(0),
+            receiver: Mutex::new(LargeMessageReceiver::default()),
+        }
+    }
+
+    /// Sets the number of message bytes per down-link part, limited to what fits into a packet.
+    pub fn set_part_size(&mut self, part_size: usize) {
+        self.part_size = checked_part_size(part_size);
+    }
+
+    /// Replaces the up-link receiver, e.g. to change its timeout.
+    pub fn set_receiver(&mut self, receiver: LargeMessageReceiver) {
+        self.receiver = Mutex::new(receiver);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    ///
+    /// `now` is the local time used for reception timeouts.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32, now: Duration) {
+        let case = (service, subtype);
+
+        if let (13, 9..=11) = case {
+            let kind = PartKind::from_uplink_subtype(subtype).unwrap();
+            let result = self.receiver.lock().unwrap().receive_part(node_id, kind, &data, now);
+            match result {
+                Ok(Some(message)) => self.parent.dispatch(message, node_id),
+                Ok(None) => {}
+                Err(abort) => self.send_abort_report(&abort),
+            }
+        }
+    }
+
+    /// Aborts up-link receptions that timed out and reports them.
+    pub fn poll(&self, now: Duration) {
+        let aborts = self.receiver.lock().unwrap().check_timeouts(now);
+        for abort in aborts {
+            self.send_abort_report(&abort);
+        }
+    }
+
+    /// Sends a large message to the controller as down-link parts (13,1) to (13,3).
+    ///
+    /// Returns the transaction ID of the transfer.
+    pub fn send_large_message(&self, message: &[u8]) -> u16 {
+        let transaction_id = {
+            let mut next = self.next_transaction_id.lock().unwrap();
+            let transaction_id = *next;
+            *next = next.wrapping_add(1);
+            transaction_id
+        };
+        for part in split_message(message, transaction_id, self.part_size, |kind| kind.downlink_subtype()) {
+            self.parent.send(Packet::new(part), CONTROLLER_NODE_ID);
+        }
+        transaction_id
+    }
+
+    /// Sends an up-link abort report (13,16).
+    pub fn send_abort_report(&self, abort: &Abort) {
+        let mut packet_data = vec![13, 16];
+        packet_data.extend_from_slice(&abort.transaction_id.to_be_bytes());
+        packet_data.push(abort.reason as u8);
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
@@ -0,0 +1,314 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+use super::encoding::{decode_id_list, encode_id_list};
+
+/// Node ID of the controller, the destination of all forwarded reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// Subtype matching every subtype of a service in a report type.
+pub const ALL_SUBTYPES: u8 = 0;
+
+/// Report type as a (service, subtype) pair.
+pub type ReportType = (u8, u8);
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+}
+
+/// Forward-control configuration of a responder.
+///
+/// Everything is forwarded unless disabled. A report type is a
+/// (service, subtype) pair; `ALL_SUBTYPES` disables a whole service.
+/// Housekeeping reports (3,25) are additionally filtered by report ID and
+/// event reports (5,1) to (5,4) by event ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardControl {
+    pub disabled_report_types: BTreeSet<ReportType>,
+    pub disabled_housekeeping_reports: BTreeSet<u8>,
+    pub disabled_events: BTreeSet<u16>,
+}
+
+impl ForwardControl {
+    /// Creates a configuration forwarding every report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the report with the given packet data is forwarded.
+    pub fn is_forwarded(&self, data: &[u8]) -> bool {
+        let [service, subtype, ref rest @ ..] = *data else {
+            return true;
+        };
+        if self.disabled_report_types.contains(&(service, ALL_SUBTYPES))
+            || self.disabled_report_types.contains(&(service, subtype))
+        {
+            return false;
+        }
+        match (service, subtype, rest) {
+            (3, 25, [report_id, ..]) => !self.disabled_housekeeping_reports.contains(report_id),
+            (5, 1..=4, [high, low, ..]) => !self.disabled_events.contains(&u16::from_be_bytes([*high, *low])),
+            _ => true,
+        }
+    }
+
+    /// Returns true if nothing is disabled.
+    pub fn is_empty(&self) -> bool {
+        self.disabled_report_types.is_empty()
+            && self.disabled_housekeeping_reports.is_empty()
+            && self.disabled_events.is_empty()
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut data = encode_report_types(&self.disabled_report_types.iter().copied().collect::<Vec<_>>());
+        data.extend(encode_id_list(&self.disabled_housekeeping_reports.iter().copied().collect::<Vec<_>>()));
+        data.extend(encode_id_list(&self.disabled_events.iter().copied().collect::<Vec<_>>()));
+        data
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        let (report_types, data) = decode_report_types(data)?;
+        let (report_ids, data) = decode_id_list(data)?;
+        let (event_ids, _) = decode_id_list(data)?;
+        Some(ForwardControl {
+            disabled_report_types: report_types.into_iter().collect(),
+            disabled_housekeeping_reports: report_ids.into_iter().collect(),
+            disabled_events: event_ids.into_iter().collect(),
+        })
+    }
+}
+
+/// Callback invoked by the controller for every received configuration report.
+pub type ConfigurationReportHandler = Arc<dyn Fn(u32, &ForwardControl) + Send + Sync>;
+
+/// Encodes a list of report types as count followed by (service, subtype) pairs.
+///
+/// At most 255 entries fit into one list; further entries are dropped.
+fn encode_report_types(report_types: &[ReportType]) -> Vec<u8> {
+    let report_types = &report_types[..report_types.len().min(u8::MAX as usize)];
+    let mut data = vec![report_types.len() as u8];
+    for (service, subtype) in report_types {
+        data.push(*service);
+        data.push(*subtype);
+    }
+    data
+}
+
+/// Decodes a list of report types, returning it and the remaining data.
+fn decode_report_types(data: &[u8]) -> Option<(Vec<ReportType>, &[u8])> {
+    let (&count, data) = data.split_first()?;
+    let length = count as usize * 2;
+    if data.len() < length {
+        return None;
+    }
+    let report_types = data[..length].chunks_exact(2).map(|pair| (pair[0], pair[1])).collect();
+    Some((report_types, &data[length..]))
+}
+
+/// Controller for the Real-time Forwarding Control Service.
+pub struct ForwardingControlServiceController {
+    parent: Arc<dyn Parent>,
+    configurations: Mutex<BTreeMap<u32, ForwardControl>>,
+    configuration_handler: Option<ConfigurationReportHandler>,
+}
+
+impl ForwardingControlServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        ForwardingControlServiceController {
+            parent,
+            configurations: Mutex::new(BTreeMap::new()),
+            configuration_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every received configuration report.
+    pub fn set_configuration_handler(&mut self, handler: ConfigurationReportHandler) {
+        self.configuration_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        if case == (14, 4) {
+            let Some(configuration) = ForwardControl::decode(&data) else {
+                return;
+            };
+            if let Some(handler) = &self.configuration_handler {
+                handler(node_id, &configuration);
+            }
+            self.configurations.lock().unwrap().insert(node_id, configuration);
+        }
+    }
+
+    /// Returns the configuration last reported by a node.
+    pub fn configuration(&self, node_id: u32) -> Option<ForwardControl> {
+        self.configurations.lock().unwrap().get(&node_id).cloned()
+    }
+
+    /// Enables forwarding of the given report types (14,1).
+    pub fn send_enable_report_types(&self, node_id: u32, report_types: &[ReportType]) {
+        let mut packet_data = vec![14, 1];
+        packet_data.extend(encode_report_types(report_types));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Disables forwarding of the given report types (14,2).
+    pub fn send_disable_report_types(&self, node_id: u32, report_types: &[ReportType]) {
+        let mut packet_data = vec![14, 2];
+        packet_data.extend(encode_report_types(report_types));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Requests the forward-control configuration of a node (14,3).
+    pub fn send_configuration_request(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![14, 3]), node_id);
+    }
+
+    /// Enables forwarding of the given housekeeping reports (14,5).
+    pub fn send_enable_housekeeping_reports(&self, node_id: u32, report_ids: &[u8]) {
+        let mut packet_data = vec![14, 5];
+        packet_data.extend(encode_id_list(report_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Disables forwarding of the given housekeeping reports (14,6).
+    pub fn send_disable_housekeeping_reports(&self, node_id: u32, report_ids: &[u8]) {
+        let mut packet_data = vec![14, 6];
+        packet_data.extend(encode_id_list(report_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Enables forwarding of the given event reports (14,9).
+    pub fn send_enable_events(&self, node_id: u32, event_ids: &[u16]) {
+        let mut packet_data = vec![14, 9];
+        packet_data.extend(encode_id_list(event_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Disables forwarding of the given event reports (14,10).
+    pub fn send_disable_events(&self, node_id: u32, event_ids: &[u16]) {
+        let mut packet_data = vec![14, 10];
+        packet_data.extend(encode_id_list(event_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+}
+
+/// Responder for the Real-time Forwarding Control Service.
+///
+/// Services hand their reports to `forward`, which sends them to the
+/// controller unless the configuration disables them.
+pub struct ForwardingControlServiceResponder {
+    parent: Arc<dyn Parent>,
+    configuration: Mutex<ForwardControl>,
+}
+
+impl ForwardingControlServiceResponder {
+    /// Creates a new responder forwarding every report.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        ForwardingControlServiceResponder {
+            parent,
+            configuration: Mutex::new(ForwardControl::new()),
+        }
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+        let mut configuration = self.configuration.lock().unwrap();
+
+        match case {
+            (14, 1) | (14, 2) => {
+                if let Some((report_types, _)) = decode_report_types(&data) {
+                    for report_type in report_types {
+                        if subtype == 1 {
+                            configuration.disabled_report_types.remove(&report_type);
+                        } else {
+                            configuration.disabled_report_types.insert(report_type);
+                        }
+                    }
+                }
+            }
+            (14, 3) => {
+                drop(configuration);
+                self.send_configuration_report();
+            }
+            (14, 5) | (14, 6) => {
+                if let Some((report_ids, _)) = decode_id_list(&data) {
+                    for report_id in report_ids {
+                        if subtype == 5 {
+                            configuration.disabled_housekeeping_reports.remove(&report_id);
+                        } else {
+                            configuration.disabled_housekeeping_reports.insert(report_id);
+                        }
+                    }
+                }
+            }
+            (14, 9) | (14, 10) => {
+                if let Some((event_ids, _)) = decode_id_list(&data) {
+                    for event_id in event_ids {
+                        if subtype == 9 {
+                            configuration.disabled_events.remove(&event_id);
+                        } else {
+                            configuration.disabled_events.insert(event_id);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns a copy of the forward-control configuration.
+    pub fn configuration(&self) -> ForwardControl {
+        self.configuration.lock().unwrap().clone()
+    }
+
+    /// Replaces the forward-control configuration.
+    pub fn set_configuration(&self, configuration: ForwardControl) {
+        *self.configuration.lock().unwrap() = configuration;
+    }
+
+    /// Returns true if the report with the given packet data is forwarded.
+    pub fn is_forwarded(&self, data: &[u8]) -> bool {
+        self.configuration.lock().unwrap().is_forwarded(data)
+    }
+
+    /// Sends a report to the controller unless forwarding of it is disabled.
+    ///
+    /// Returns true if the report was sent.
+    pub fn forward(&self, packet: Packet) -> bool {
+        if !self.is_forwarded(&packet.data) {
+            return false;
+        }
+        self.parent.send(packet, CONTROLLER_NODE_ID);
+        true
+    }
+
+    /// Sends the forward-control configuration report (14,4).
+    pub fn send_configuration_report(&self) {
+        let mut packet_data = vec![14, 4];
+        packet_data.extend(self.configuration.lock().unwrap().encode());
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
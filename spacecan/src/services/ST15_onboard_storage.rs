@@ -0,0 +1,458 @@
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+use crate::primitives::time::CucTime;
+use crate::storage::packets::{PacketStore, StoredPacket};
+use crate::storage::StoreError;
+use super::encoding::{decode_id_list, encode_id_list};
+
+/// Node ID of the controller, the destination of all storage reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// Encoded size of one packet store summary.
+const SUMMARY_SIZE: usize = 16;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+}
+
+/// What a full packet store does with a new packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Ring store: the oldest packet is dropped.
+    Circular,
+    /// Linear store: the new packet is rejected.
+    Bounded,
+}
+
+/// Status of a packet store reported with (15,13).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketStoreSummary {
+    pub store_id: u8,
+    pub enabled: bool,
+    pub circular: bool,
+    pub packet_count: u32,
+    pub capacity: u32,
+    /// Sequence number of the oldest stored packet.
+    pub first_sequence: u32,
+}
+
+impl PacketStoreSummary {
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.push(self.store_id);
+        data.push(self.enabled as u8);
+        data.push(self.circular as u8);
+        data.push(0);
+        data.extend_from_slice(&self.packet_count.to_be_bytes());
+        data.extend_from_slice(&self.capacity.to_be_bytes());
+        data.extend_from_slice(&self.first_sequence.to_be_bytes());
+    }
+
+    fn decode(data: &[u8]) -> Self {
+        PacketStoreSummary {
+            store_id: data[0],
+            enabled: data[1] != 0,
+            circular: data[2] != 0,
+            packet_count: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            capacity: u32::from_be_bytes([data[8], data[9], data[10], data[11]]),
+            first_sequence: u32::from_be_bytes([data[12], data[13], data[14], data[15]]),
+        }
+    }
+}
+
+/// Packet retrieved from a responder's packet store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetrievedPacket {
+    pub store_id: u8,
+    pub packet: StoredPacket,
+}
+
+/// Callback invoked by the controller for every retrieved packet.
+pub type RetrievedPacketHandler = Arc<dyn Fn(u32, &RetrievedPacket) + Send + Sync>;
+
+/// Encodes a store ID followed by a list of (service, subtype) pairs.
+fn encode_routing(store_id: u8, packet_types: &[(u8, u8)]) -> Vec<u8> {
+    let packet_types = &packet_types[..packet_types.len().min(u8::MAX as usize)];
+    let mut data = vec![store_id, packet_types.len() as u8];
+    for (service, subtype) in packet_types {
+        data.push(*service);
+        data.push(*subtype);
+    }
+    data
+}
+
+/// Decodes a routing list encoded by `encode_routing`.
+fn decode_routing(data: &[u8]) -> Option<(u8, Vec<(u8, u8)>)> {
+    let (&store_id, rest) = data.split_first()?;
+    let (&count, pairs) = rest.split_first()?;
+    if pairs.len() < count as usize * 2 {
+        return None;
+    }
+    let packet_types = pairs.chunks_exact(2).take(count as usize).map(|pair| (pair[0], pair[1])).collect();
+    Some((store_id, packet_types))
+}
+
+/// Controller for the On-Board Storage and Retrieval Service.
+pub struct OnboardStorageServiceController {
+    parent: Arc<dyn Parent>,
+    summaries: Mutex<BTreeMap<u32, Vec<PacketStoreSummary>>>,
+    retrieved: Mutex<BTreeMap<u32, Vec<RetrievedPacket>>>,
+    retrieved_handler: Option<RetrievedPacketHandler>,
+}
+
+impl OnboardStorageServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        OnboardStorageServiceController {
+            parent,
+            summaries: Mutex::new(BTreeMap::new()),
+            retrieved: Mutex::new(BTreeMap::new()),
+            retrieved_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every retrieved packet.
+    pub fn set_retrieved_handler(&mut self, handler: RetrievedPacketHandler) {
+        self.retrieved_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        match case {
+            (15, 13) => {
+                let Some((&count, summaries)) = data.split_first() else {
+                    return;
+                };
+                let summaries: Vec<PacketStoreSummary> = summaries
+                    .chunks_exact(SUMMARY_SIZE)
+                    .take(count as usize)
+                    .map(PacketStoreSummary::decode)
+                    .collect();
+                self.summaries.lock().unwrap().insert(node_id, summaries);
+            }
+            (15, 19) if data.len() >= 12 => {
+                let Some(time) = CucTime::from_bytes(&data[5..12]) else {
+                    return;
+                };
+                let retrieved = RetrievedPacket {
+                    store_id: data[0],
+                    packet: StoredPacket::new(
+                        u32::from_be_bytes([data[1], data[2], data[3], data[4]]),
+                        time,
+                        data[12..].to_vec(),
+                    ),
+                };
+                if let Some(handler) = &self.retrieved_handler {
+                    handler(node_id, &retrieved);
+                }
+                self.retrieved.lock().unwrap().entry(node_id).or_default().push(retrieved);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the packet store summaries last reported by a node.
+    pub fn summaries(&self, node_id: u32) -> Option<Vec<PacketStoreSummary>> {
+        self.summaries.lock().unwrap().get(&node_id).cloned()
+    }
+
+    /// Removes and returns the packets retrieved from a node.
+    pub fn take_retrieved(&self, node_id: u32) -> Vec<RetrievedPacket> {
+        self.retrieved.lock().unwrap().remove(&node_id).unwrap_or_default()
+    }
+
+    /// Enables storage in the given packet stores of a node (15,1).
+    pub fn send_enable_storage(&self, node_id: u32, store_ids: &[u8]) {
+        let mut packet_data = vec![15, 1];
+        packet_data.extend(encode_id_list(store_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Disables storage in the given packet stores of a node (15,2).
+    pub fn send_disable_storage(&self, node_id: u32, store_ids: &[u8]) {
+        let mut packet_data = vec![15, 2];
+        packet_data.extend(encode_id_list(store_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Routes packets of the given (service, subtype) types into a packet store (15,3).
+    pub fn send_add_routing(&self, node_id: u32, store_id: u8, packet_types: &[(u8, u8)]) {
+        let mut packet_data = vec![15, 3];
+        packet_data.extend(encode_routing(store_id, packet_types));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Stops routing packets of the given types into a packet store (15,4).
+    pub fn send_delete_routing(&self, node_id: u32, store_id: u8, packet_types: &[(u8, u8)]) {
+        let mut packet_data = vec![15, 4];
+        packet_data.extend(encode_routing(store_id, packet_types));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Retrieves the packets stored within a time range, inclusive (15,9).
+    pub fn send_retrieve_by_time(&self, node_id: u32, store_id: u8, start: CucTime, end: CucTime) {
+        let mut packet_data = vec![15, 9, store_id];
+        packet_data.extend_from_slice(&start.to_bytes());
+        packet_data.extend_from_slice(&end.to_bytes());
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Retrieves the packets within a sequence number range, inclusive (15,10).
+    pub fn send_retrieve_by_sequence(&self, node_id: u32, store_id: u8, first: u32, last: u32) {
+        let mut packet_data = vec![15, 10, store_id];
+        packet_data.extend_from_slice(&first.to_be_bytes());
+        packet_data.extend_from_slice(&last.to_be_bytes());
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Deletes the packets stored up to the given time, inclusive (15,11).
+    pub fn send_delete_content(&self, node_id: u32, store_id: u8, up_to: CucTime) {
+        let mut packet_data = vec![15, 11, store_id];
+        packet_data.extend_from_slice(&up_to.to_bytes());
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Requests the summary of all packet stores of a node (15,12).
+    pub fn send_summary_request(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![15, 12]), node_id);
+    }
+}
+
+/// Packet store with its overwrite policy and storage state.
+struct RegisteredStore {
+    store: Box<dyn PacketStore>,
+    policy: OverwritePolicy,
+    enabled: bool,
+    next_sequence: u32,
+}
+
+impl RegisteredStore {
+    fn store(&mut self, time: CucTime, data: &[u8]) -> Result<(), StoreError> {
+        if self.store.len() >= self.store.capacity() && self.policy == OverwritePolicy::Circular {
+            self.store.pop_oldest()?;
+        }
+        self.store.push(StoredPacket::new(self.next_sequence, time, data.to_vec()))?;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        Ok(())
+    }
+}
+
+/// Responder for the On-Board Storage and Retrieval Service.
+///
+/// The application passes every telemetry packet it generates to `store`;
+/// the routing rules select the packet stores it is written to. Retrieved
+/// packets are sent as (15,19) reports carrying store ID, sequence number,
+/// storage time and the original packet.
+pub struct OnboardStorageServiceResponder {
+    parent: Arc<dyn Parent>,
+    stores: Mutex<BTreeMap<u8, RegisteredStore>>,
+    routing: Mutex<BTreeMap<(u8, u8), BTreeSet<u8>>>,
+}
+
+impl OnboardStorageServiceResponder {
+    /// Creates a new responder with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        OnboardStorageServiceResponder {
+            parent,
+            stores: Mutex::new(BTreeMap::new()),
+            routing: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Adds a packet store. Storage is enabled; sequence numbers continue after
+    /// the packets the store already holds.
+    pub fn add_store(&self, store_id: u8, store: Box<dyn PacketStore>, policy: OverwritePolicy) -> Result<(), StoreError> {
+        let next_sequence = store
+            .packets()?
+            .last()
+            .map(|packet| packet.sequence.wrapping_add(1))
+            .unwrap_or(0);
+        self.stores.lock().unwrap().insert(
+            store_id,
+            RegisteredStore {
+                store,
+                policy,
+                enabled: true,
+                next_sequence,
+            },
+        );
+        Ok(())
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+
+        match case {
+            (15, 1) | (15, 2) => {
+                if let Some((store_ids, _)) = decode_id_list(&data) {
+                    for store_id in store_ids {
+                        self.set_storage_enabled(store_id, subtype == 1);
+                    }
+                }
+            }
+            (15, 3) => {
+                if let Some((store_id, packet_types)) = decode_routing(&data) {
+                    for packet_type in packet_types {
+                        self.add_routing(store_id, packet_type);
+                    }
+                }
+            }
+            (15, 4) => {
+                if let Some((store_id, packet_types)) = decode_routing(&data) {
+                    for packet_type in packet_types {
+                        self.delete_routing(store_id, packet_type);
+                    }
+                }
+            }
+            (15, 9) if data.len() >= 15 => {
+                if let (Some(start), Some(end)) = (CucTime::from_bytes(&data[1..8]), CucTime::from_bytes(&data[8..15])) {
+                    let _ = self.retrieve(data[0], |packet| packet.time >= start && packet.time <= end);
+                }
+            }
+            (15, 10) if data.len() >= 9 => {
+                let first = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+                let last = u32::from_be_bytes([data[5], data[6], data[7], data[8]]);
+                let _ = self.retrieve(data[0], |packet| packet.sequence >= first && packet.sequence <= last);
+            }
+            (15, 11) if data.len() >= 8 => {
+                if let Some(up_to) = CucTime::from_bytes(&data[1..8]) {
+                    let _ = self.delete_content(data[0], up_to);
+                }
+            }
+            (15, 12) => self.send_summary_report(),
+            _ => {}
+        }
+    }
+
+    /// Enables or disables storage in a packet store.
+    pub fn set_storage_enabled(&self, store_id: u8, enabled: bool) {
+        if let Some(store) = self.stores.lock().unwrap().get_mut(&store_id) {
+            store.enabled = enabled;
+        }
+    }
+
+    /// Routes packets of a (service, subtype) type into a packet store.
+    pub fn add_routing(&self, store_id: u8, packet_type: (u8, u8)) {
+        self.routing.lock().unwrap().entry(packet_type).or_default().insert(store_id);
+    }
+
+    /// Stops routing packets of a type into a packet store.
+    pub fn delete_routing(&self, store_id: u8, packet_type: (u8, u8)) {
+        let mut routing = self.routing.lock().unwrap();
+        if let Some(store_ids) = routing.get_mut(&packet_type) {
+            store_ids.remove(&store_id);
+            if store_ids.is_empty() {
+                routing.remove(&packet_type);
+            }
+        }
+    }
+
+    /// Stores a telemetry packet in every enabled packet store it is routed to.
+    ///
+    /// Returns the number of stores that accepted the packet.
+    pub fn store(&self, data: &[u8], time: CucTime) -> usize {
+        let [service, subtype, ..] = *data else {
+            return 0;
+        };
+        let Some(store_ids) = self.routing.lock().unwrap().get(&(service, subtype)).cloned() else {
+            return 0;
+        };
+        let mut stores = self.stores.lock().unwrap();
+        stores
+            .iter_mut()
+            .filter(|(store_id, store)| store.enabled && store_ids.contains(store_id))
+            .map(|(_, store)| store.store(time, data))
+            .filter(Result::is_ok)
+            .count()
+    }
+
+    /// Returns the packets held by a packet store.
+    pub fn packets(&self, store_id: u8) -> Result<Vec<StoredPacket>, StoreError> {
+        match self.stores.lock().unwrap().get(&store_id) {
+            Some(store) => store.store.packets(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Sends the packets of a store matching the filter as (15,19) reports, oldest first.
+    ///
+    /// Returns the number of retrieved packets.
+    pub fn retrieve(&self, store_id: u8, filter: impl Fn(&StoredPacket) -> bool) -> Result<usize, StoreError> {
+        let packets = self.packets(store_id)?;
+        let mut count = 0;
+        for packet in packets.iter().filter(|packet| filter(packet)) {
+            let mut packet_data = vec![15, 19, store_id];
+            packet_data.extend_from_slice(&packet.sequence.to_be_bytes());
+            packet_data.extend_from_slice(&packet.time.to_bytes());
+            packet_data.extend_from_slice(&packet.data);
+            self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Deletes the packets of a store stored up to the given time.
+    pub fn delete_content(&self, store_id: u8, up_to: CucTime) -> Result<(), StoreError> {
+        let mut stores = self.stores.lock().unwrap();
+        let Some(store) = stores.get_mut(&store_id) else {
+            return Ok(());
+        };
+        // Packets are stored in time order, so the deleted ones are the oldest.
+        let count = store.store.packets()?.iter().take_while(|packet| packet.time <= up_to).count();
+        for _ in 0..count {
+            store.store.pop_oldest()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the summaries of all packet stores.
+    pub fn summaries(&self) -> Vec<PacketStoreSummary> {
+        self.stores
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(store_id, store)| PacketStoreSummary {
+                store_id: *store_id,
+                enabled: store.enabled,
+                circular: store.policy == OverwritePolicy::Circular,
+                packet_count: store.store.len() as u32,
+                capacity: store.store.capacity() as u32,
+                first_sequence: store.next_sequence.wrapping_sub(store.store.len() as u32),
+            })
+            .collect()
+    }
+
+    /// Sends the packet store summary report (15,13).
+    pub fn send_summary_report(&self) {
+        let summaries = self.summaries();
+        let summaries = &summaries[..summaries.len().min(u8::MAX as usize)];
+        let mut packet_data = vec![15, 13, summaries.len() as u8];
+        for summary in summaries {
+            summary.encode(&mut packet_data);
+        }
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
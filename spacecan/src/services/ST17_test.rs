@@ -4,6 +4,11 @@ use alloc::vec;
 use alloc::vec::Vec;
 use alloc::boxed::Box;
 use alloc::sync::Arc;
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use std::sync::Mutex;
+
+use crate::primitives::scheduler::{self, Instant, SharedClock, StdClock};
 
 // Cannot import private traits and structs from ST01_request_verification, so redefine minimal needed here:
 
@@ -22,7 +27,8 @@ impl Packet {
 
 /// Trait defining the parent interface for sending packets.
 pub trait Parent: Send + Sync {
-    fn send(&self, packet: Packet);
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
 }
 
 /// Mock or placeholder for RequestVerificationServiceResponder interface
@@ -49,15 +55,68 @@ impl RequestVerification for RequestVerificationServiceResponder {
     fn send_fail_completion_report(&self, _args: &[u8]) {}
 }
 
+/// Default time to wait for a connection test report.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Outcome of a connection test to one node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reachability {
+    /// The test was sent and no report has arrived yet.
+    Pending,
+    /// The report arrived after the given round-trip time.
+    Reachable(Duration),
+    /// No report arrived within the timeout.
+    TimedOut,
+}
+
+/// Result of a connection test, as listed in a sweep table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionTestResult {
+    /// Node that was tested.
+    pub node_id: u32,
+    /// Application process ID for (17,3) tests, None for (17,1) tests.
+    pub apid: Option<u8>,
+    /// Current state of the test.
+    pub reachability: Reachability,
+}
+
 /// Controller for the Test Service.
 pub struct TestServiceController {
     parent: Arc<dyn Parent>,
+    timeout: Duration,
+    clock: SharedClock,
+    nodes: Vec<u32>,
+    // Send time of unanswered tests, keyed by (node_id, apid).
+    pending: Mutex<BTreeMap<(u32, Option<u8>), Instant>>,
+    results: Mutex<BTreeMap<(u32, Option<u8>), Reachability>>,
 }
 
 impl TestServiceController {
     /// Creates a new controller with the given parent.
     pub fn new(parent: Arc<dyn Parent>) -> Self {
-        TestServiceController { parent }
+        TestServiceController {
+            parent,
+            timeout: DEFAULT_TIMEOUT,
+            clock: Arc::new(StdClock::new()),
+            nodes: Vec::new(),
+            pending: Mutex::new(BTreeMap::new()),
+            results: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Sets how long to wait for a report before a test times out.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sets the clock timing the tests, e.g. a simulated clock in tests.
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
+
+    /// Sets the nodes included in a ping sweep.
+    pub fn set_nodes(&mut self, nodes: Vec<u32>) {
+        self.nodes = nodes;
     }
 
     /// Processes incoming packets based on service and subtype.
@@ -65,9 +124,15 @@ impl TestServiceController {
         let case = (service, subtype);
 
         match case {
-            (17, 2) => self.received_connection_test_report(node_id),
+            (17, 2) => {
+                self.complete_test(node_id, None);
+                self.received_connection_test_report(node_id);
+            }
             (17, 4) => {
-                let apid = data[0]; // Assuming data is at least 1 byte
+                let Some(&apid) = data.first() else {
+                    return;
+                };
+                self.complete_test(node_id, Some(apid));
                 self.received_application_connection_test_report(node_id, apid);
             }
             _ => {}
@@ -76,14 +141,69 @@ impl TestServiceController {
 
     /// Sends a connection test packet.
     pub fn send_connection_test(&self, node_id: u32) {
-        self.parent.send(Packet::new(vec![17, 1]));
+        self.start_test(node_id, None);
+        self.parent.send(Packet::new(vec![17, 1]), node_id);
     }
 
     /// Sends an application connection test packet with APID.
     pub fn send_application_connection_test(&self, node_id: u32, apid: u8) {
+        self.start_test(node_id, Some(apid));
         let mut packet_data = vec![17, 3];
         packet_data.push(apid);
-        self.parent.send(Packet::new(packet_data));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Sends a connection test to every configured node.
+    ///
+    /// The sweep is complete once every test is answered or expired by
+    /// `check_timeouts`; `sweep_results` then returns the table.
+    pub fn ping_all(&self) {
+        for node_id in &self.nodes {
+            self.send_connection_test(*node_id);
+        }
+    }
+
+    /// Marks unanswered tests older than the timeout as timed out and returns them.
+    pub fn check_timeouts(&self) -> Vec<(u32, Option<u8>)> {
+        let now = self.clock.now();
+        let mut pending = self.pending.lock().unwrap();
+        let expired: Vec<(u32, Option<u8>)> = pending
+            .iter()
+            .filter(|(_, sent)| {
+                now.checked_duration_since(**sent)
+                    .is_some_and(|elapsed| scheduler::to_core_duration(elapsed) >= self.timeout)
+            })
+            .map(|(key, _)| *key)
+            .collect();
+        let mut results = self.results.lock().unwrap();
+        for key in &expired {
+            pending.remove(key);
+            results.insert(*key, Reachability::TimedOut);
+        }
+        expired
+    }
+
+    /// Returns the state of the last connection test to a node.
+    pub fn reachability(&self, node_id: u32, apid: Option<u8>) -> Option<Reachability> {
+        self.results.lock().unwrap().get(&(node_id, apid)).copied()
+    }
+
+    /// Returns the last (17,1) result for each configured node, ordered by node ID.
+    pub fn sweep_results(&self) -> Vec<ConnectionTestResult> {
+        let results = self.results.lock().unwrap();
+        let mut nodes = self.nodes.clone();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+            .into_iter()
+            .filter_map(|node_id| {
+                results.get(&(node_id, None)).map(|reachability| ConnectionTestResult {
+                    node_id,
+                    apid: None,
+                    reachability: *reachability,
+                })
+            })
+            .collect()
     }
 
     /// Handler for received connection test report.
@@ -95,6 +215,27 @@ impl TestServiceController {
     pub fn received_application_connection_test_report(&self, _node_id: u32, _apid: u8) {
         // To be overwritten.
     }
+
+    fn start_test(&self, node_id: u32, apid: Option<u8>) {
+        self.pending.lock().unwrap().insert((node_id, apid), self.clock.now());
+        self.results.lock().unwrap().insert((node_id, apid), Reachability::Pending);
+    }
+
+    fn complete_test(&self, node_id: u32, apid: Option<u8>) {
+        // Reports arriving after a timeout or without a request are ignored.
+        if let Some(sent) = self.pending.lock().unwrap().remove(&(node_id, apid)) {
+            // A clock set back while the test was pending yields a zero round trip.
+            let round_trip = self
+                .clock
+                .now()
+                .checked_duration_since(sent)
+                .map_or(Duration::ZERO, scheduler::to_core_duration);
+            self.results
+                .lock()
+                .unwrap()
+                .insert((node_id, apid), Reachability::Reachable(round_trip));
+        }
+    }
 }
 
 /// Responder for the Test Service.
@@ -151,12 +292,12 @@ impl TestServiceResponder {
 
     /// Sends a connection test report.
     pub fn send_connection_test_report(&self, node_id: u32) {
-        self.parent.send(Packet::new(vec![17, 2]));
+        self.parent.send(Packet::new(vec![17, 2]), node_id);
     }
 
     /// Sends an application connection test report.
     pub fn send_application_connection_test_report(&self, node_id: u32, apid: u8) {
-        self.parent.send(Packet::new(vec![17, 4, apid]));
+        self.parent.send(Packet::new(vec![17, 4, apid]), node_id);
     }
 
     /// Handles an application connection test.
@@ -170,7 +311,7 @@ impl TestServiceResponder {
 struct MockParent;
 
 impl Parent for MockParent {
-    fn send(&self, packet: Packet) {
+    fn send(&self, _packet: Packet, _node_id: u32) {
         // Mock send: print or log the packet
         // e.g. println!("Sending packet: {:?}", packet);
     }
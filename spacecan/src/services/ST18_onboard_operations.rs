@@ -0,0 +1,744 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use crate::primitives::packet::MAX_PACKET_LENGTH;
+
+/// Node ID of the controller, the destination of all procedure reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// Largest number of procedures a responder holds.
+pub const MAX_PROCEDURES: usize = 16;
+/// Largest number of instructions a procedure executes per tick.
+///
+/// Bounds the time a tick takes when a procedure loops without waiting.
+pub const MAX_INSTRUCTIONS_PER_TICK: usize = 32;
+/// Encoded size of a procedure status.
+const STATUS_SIZE: usize = 4;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+    /// Hands a telecommand sent by a procedure to the packet utilization dispatcher.
+    fn dispatch(&self, data: Vec<u8>, node_id: u32);
+    /// Returns the current value of a parameter of this node (ST20).
+    fn get_parameter_value(&self, parameter_id: u32) -> Option<f64>;
+    /// Returns the request verification service (ST01) of this node.
+    fn request_verification(&self) -> &dyn RequestVerification;
+}
+
+/// Verification reports sent by the responder, see ST01.
+pub trait RequestVerification {
+    fn send_success_acceptance_report(&self, args: &[u8]);
+    fn send_success_completion_report(&self, args: &[u8]);
+    fn send_fail_acceptance_report(&self, args: &[u8]);
+    fn send_fail_completion_report(&self, args: &[u8]);
+}
+
+/// Errors raised by procedure management.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObcpError {
+    /// The procedure code or definition is malformed.
+    InvalidProcedure,
+    /// The encoded procedure does not fit into one packet.
+    TooLarge,
+    /// No procedure is loaded under the ID.
+    UnknownProcedure(u8),
+    /// A procedure is already loaded under the ID.
+    AlreadyLoaded(u8),
+    /// The maximum number of procedures is loaded.
+    Full,
+    /// The procedure is not in a state permitting the request.
+    InvalidState(u8),
+}
+
+impl fmt::Display for ObcpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObcpError::InvalidProcedure => write!(f, "invalid procedure"),
+            ObcpError::TooLarge => write!(f, "procedure too large"),
+            ObcpError::UnknownProcedure(id) => write!(f, "unknown procedure {}", id),
+            ObcpError::AlreadyLoaded(id) => write!(f, "procedure {} already loaded", id),
+            ObcpError::Full => write!(f, "procedure store is full"),
+            ObcpError::InvalidState(id) => write!(f, "procedure {} is in the wrong state", id),
+        }
+    }
+}
+
+/// Comparison of a parameter value against a constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn code(&self) -> u8 {
+        *self as u8
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Comparison::Equal),
+            1 => Some(Comparison::NotEqual),
+            2 => Some(Comparison::Less),
+            3 => Some(Comparison::LessOrEqual),
+            4 => Some(Comparison::Greater),
+            5 => Some(Comparison::GreaterOrEqual),
+            _ => None,
+        }
+    }
+
+    /// Returns the comparison written with the given operator.
+    pub fn from_operator(operator: &str) -> Option<Self> {
+        match operator {
+            "==" => Some(Comparison::Equal),
+            "!=" => Some(Comparison::NotEqual),
+            "<" => Some(Comparison::Less),
+            "<=" => Some(Comparison::LessOrEqual),
+            ">" => Some(Comparison::Greater),
+            ">=" => Some(Comparison::GreaterOrEqual),
+            _ => None,
+        }
+    }
+
+    fn evaluate(&self, value: f64, reference: f64) -> bool {
+        match self {
+            Comparison::Equal => value == reference,
+            Comparison::NotEqual => value != reference,
+            Comparison::Less => value < reference,
+            Comparison::LessOrEqual => value <= reference,
+            Comparison::Greater => value > reference,
+            Comparison::GreaterOrEqual => value >= reference,
+        }
+    }
+}
+
+/// Instruction of an on-board control procedure.
+///
+/// Jump targets are instruction indices.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    /// Ends the procedure.
+    End,
+    /// Suspends execution for the given number of ticks.
+    Wait { ticks: u16 },
+    /// Sends a telecommand to the own node.
+    Send { data: Vec<u8> },
+    /// Sets the condition flag to the result of comparing a parameter.
+    Check {
+        parameter_id: u32,
+        comparison: Comparison,
+        value: f64,
+    },
+    /// Continues at the target.
+    Jump { target: u16 },
+    /// Continues at the target if the condition flag is set.
+    JumpIf { target: u16 },
+    /// Continues at the target if the condition flag is clear.
+    JumpUnless { target: u16 },
+}
+
+impl Instruction {
+    fn encode(&self, code: &mut Vec<u8>) {
+        match self {
+            Instruction::End => code.push(0x00),
+            Instruction::Wait { ticks } => {
+                code.push(0x01);
+                code.extend_from_slice(&ticks.to_be_bytes());
+            }
+            Instruction::Send { data } => {
+                code.push(0x02);
+                code.extend_from_slice(&(data.len() as u16).to_be_bytes());
+                code.extend_from_slice(data);
+            }
+            Instruction::Check {
+                parameter_id,
+                comparison,
+                value,
+            } => {
+                code.push(0x03);
+                code.extend_from_slice(&parameter_id.to_be_bytes());
+                code.push(comparison.code());
+                code.extend_from_slice(&value.to_be_bytes());
+            }
+            Instruction::Jump { target } => {
+                code.push(0x04);
+                code.extend_from_slice(&target.to_be_bytes());
+            }
+            Instruction::JumpIf { target } => {
+                code.push(0x05);
+                code.extend_from_slice(&target.to_be_bytes());
+            }
+            Instruction::JumpUnless { target } => {
+                code.push(0x06);
+                code.extend_from_slice(&target.to_be_bytes());
+            }
+        }
+    }
+
+    /// Decodes the instruction at the start of `code`, returning it and its length.
+    fn decode(code: &[u8]) -> Option<(Self, usize)> {
+        let (&opcode, operands) = code.split_first()?;
+        let u16_at = |offset: usize| Some(u16::from_be_bytes([*operands.get(offset)?, *operands.get(offset + 1)?]));
+        match opcode {
+            0x00 => Some((Instruction::End, 1)),
+            0x01 => Some((Instruction::Wait { ticks: u16_at(0)? }, 3)),
+            0x02 => {
+                let length = u16_at(0)? as usize;
+                let data = operands.get(2..2 + length)?.to_vec();
+                Some((Instruction::Send { data }, 3 + length))
+            }
+            0x03 => {
+                let operands = operands.get(..13)?;
+                let mut value = [0; 8];
+                value.copy_from_slice(&operands[5..13]);
+                let instruction = Instruction::Check {
+                    parameter_id: u32::from_be_bytes([operands[0], operands[1], operands[2], operands[3]]),
+                    comparison: Comparison::from_code(operands[4])?,
+                    value: f64::from_be_bytes(value),
+                };
+                Some((instruction, 14))
+            }
+            0x04 => Some((Instruction::Jump { target: u16_at(0)? }, 3)),
+            0x05 => Some((Instruction::JumpIf { target: u16_at(0)? }, 3)),
+            0x06 => Some((Instruction::JumpUnless { target: u16_at(0)? }, 3)),
+            _ => None,
+        }
+    }
+
+    fn from_json(step: &Value) -> Option<Self> {
+        if step.as_str() == Some("end") {
+            return Some(Instruction::End);
+        }
+        let (name, argument) = step.as_object()?.iter().next()?;
+        let target = || argument.as_u64().and_then(|target| u16::try_from(target).ok());
+        match name.as_str() {
+            "wait" => Some(Instruction::Wait { ticks: target()? }),
+            "send" => Some(Instruction::Send {
+                data: argument
+                    .as_array()?
+                    .iter()
+                    .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                    .collect::<Option<Vec<u8>>>()?,
+            }),
+            "check" => Some(Instruction::Check {
+                parameter_id: u32::try_from(argument["parameter_id"].as_u64()?).ok()?,
+                comparison: Comparison::from_operator(argument["comparison"].as_str()?)?,
+                value: argument["value"].as_f64()?,
+            }),
+            "jump" => Some(Instruction::Jump { target: target()? }),
+            "jump_if" => Some(Instruction::JumpIf { target: target()? }),
+            "jump_unless" => Some(Instruction::JumpUnless { target: target()? }),
+            _ => None,
+        }
+    }
+}
+
+/// On-board control procedure: a list of instructions.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Procedure {
+    pub instructions: Vec<Instruction>,
+}
+
+impl Procedure {
+    /// Creates a procedure from instructions, checking its jump targets.
+    pub fn new(instructions: Vec<Instruction>) -> Result<Self, ObcpError> {
+        let procedure = Procedure { instructions };
+        procedure.validate()?;
+        Ok(procedure)
+    }
+
+    /// Encodes the procedure as bytecode.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut code = Vec::new();
+        for instruction in &self.instructions {
+            instruction.encode(&mut code);
+        }
+        code
+    }
+
+    /// Decodes bytecode written by `encode`.
+    pub fn decode(mut code: &[u8]) -> Result<Self, ObcpError> {
+        let mut instructions = Vec::new();
+        while !code.is_empty() {
+            let (instruction, length) = Instruction::decode(code).ok_or(ObcpError::InvalidProcedure)?;
+            instructions.push(instruction);
+            code = &code[length..];
+        }
+        Procedure::new(instructions)
+    }
+
+    /// Parses a procedure defined in JSON.
+    ///
+    /// The definition holds a list of steps, e.g.
+    /// `{"steps": [{"check": {"parameter_id": 1, "comparison": ">", "value": 5.0}},
+    /// {"jump_unless": 3}, {"send": [8, 1, 2]}, {"wait": 10}, "end"]}`.
+    pub fn from_json(json: &str) -> Result<Self, ObcpError> {
+        let json: Value = serde_json::from_str(json).map_err(|_| ObcpError::InvalidProcedure)?;
+        let instructions = json["steps"]
+            .as_array()
+            .ok_or(ObcpError::InvalidProcedure)?
+            .iter()
+            .map(Instruction::from_json)
+            .collect::<Option<Vec<Instruction>>>()
+            .ok_or(ObcpError::InvalidProcedure)?;
+        Procedure::new(instructions)
+    }
+
+    fn validate(&self) -> Result<(), ObcpError> {
+        // The program counter is a u16 and moves past the last instruction.
+        if self.instructions.len() > u16::MAX as usize {
+            return Err(ObcpError::InvalidProcedure);
+        }
+        let valid = self.instructions.iter().all(|instruction| match instruction {
+            Instruction::Jump { target } | Instruction::JumpIf { target } | Instruction::JumpUnless { target } => {
+                (*target as usize) < self.instructions.len()
+            }
+            Instruction::Send { data } => data.len() <= u16::MAX as usize,
+            _ => true,
+        });
+        match valid {
+            true => Ok(()),
+            false => Err(ObcpError::InvalidProcedure),
+        }
+    }
+}
+
+/// Execution state of a loaded procedure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcedureState {
+    /// Loaded and not running.
+    Inactive = 0,
+    Running = 1,
+    Suspended = 2,
+    /// Stopped by a check of an unknown parameter.
+    Failed = 3,
+}
+
+impl ProcedureState {
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(ProcedureState::Inactive),
+            1 => Some(ProcedureState::Running),
+            2 => Some(ProcedureState::Suspended),
+            3 => Some(ProcedureState::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Status of a loaded procedure, reported with (18,21).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcedureStatus {
+    pub procedure_id: u8,
+    pub state: ProcedureState,
+    /// Index of the next instruction.
+    pub program_counter: u16,
+}
+
+impl ProcedureStatus {
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.push(self.procedure_id);
+        data.push(self.state as u8);
+        data.extend_from_slice(&self.program_counter.to_be_bytes());
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        Some(ProcedureStatus {
+            procedure_id: data[0],
+            state: ProcedureState::from_code(data[1])?,
+            program_counter: u16::from_be_bytes([data[2], data[3]]),
+        })
+    }
+}
+
+/// Callback invoked by the controller for every received status report.
+pub type StatusReportHandler = Arc<dyn Fn(u32, &[ProcedureStatus]) + Send + Sync>;
+
+/// Controller for the On-board Operations Procedure Service.
+pub struct OnboardOperationsServiceController {
+    parent: Arc<dyn Parent>,
+    statuses: Mutex<BTreeMap<u32, Vec<ProcedureStatus>>>,
+    status_handler: Option<StatusReportHandler>,
+}
+
+impl OnboardOperationsServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        OnboardOperationsServiceController {
+            parent,
+            statuses: Mutex::new(BTreeMap::new()),
+            status_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every received status report.
+    pub fn set_status_handler(&mut self, handler: StatusReportHandler) {
+        self.status_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        if case == (18, 21) {
+            let Some((&count, entries)) = data.split_first() else {
+                return;
+            };
+            let statuses: Vec<ProcedureStatus> = entries
+                .chunks_exact(STATUS_SIZE)
+                .take(count as usize)
+                .filter_map(ProcedureStatus::decode)
+                .collect();
+            if let Some(handler) = &self.status_handler {
+                handler(node_id, &statuses);
+            }
+            self.statuses.lock().unwrap().insert(node_id, statuses);
+        }
+    }
+
+    /// Returns the procedure statuses last reported by a node.
+    pub fn statuses(&self, node_id: u32) -> Option<Vec<ProcedureStatus>> {
+        self.statuses.lock().unwrap().get(&node_id).cloned()
+    }
+
+    /// Loads a procedure (18,1). Fails if its bytecode does not fit into one packet.
+    pub fn send_load(&self, node_id: u32, procedure_id: u8, procedure: &Procedure) -> Result<(), ObcpError> {
+        let mut packet_data = vec![18, 1, procedure_id];
+        packet_data.extend(procedure.encode());
+        if packet_data.len() > MAX_PACKET_LENGTH {
+            return Err(ObcpError::TooLarge);
+        }
+        self.parent.send(Packet::new(packet_data), node_id);
+        Ok(())
+    }
+
+    /// Unloads a procedure (18,2).
+    pub fn send_unload(&self, node_id: u32, procedure_id: u8) {
+        self.parent.send(Packet::new(vec![18, 2, procedure_id]), node_id);
+    }
+
+    /// Starts a procedure from its first instruction (18,3).
+    pub fn send_start(&self, node_id: u32, procedure_id: u8) {
+        self.parent.send(Packet::new(vec![18, 3, procedure_id]), node_id);
+    }
+
+    /// Stops a procedure (18,4).
+    pub fn send_stop(&self, node_id: u32, procedure_id: u8) {
+        self.parent.send(Packet::new(vec![18, 4, procedure_id]), node_id);
+    }
+
+    /// Suspends a running procedure (18,5).
+    pub fn send_suspend(&self, node_id: u32, procedure_id: u8) {
+        self.parent.send(Packet::new(vec![18, 5, procedure_id]), node_id);
+    }
+
+    /// Resumes a suspended procedure (18,6).
+    pub fn send_resume(&self, node_id: u32, procedure_id: u8) {
+        self.parent.send(Packet::new(vec![18, 6, procedure_id]), node_id);
+    }
+
+    /// Requests the status of all procedures of a node (18,20).
+    pub fn send_status_request(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![18, 20]), node_id);
+    }
+}
+
+/// Loaded procedure with its execution state.
+struct Execution {
+    procedure: Procedure,
+    state: ProcedureState,
+    program_counter: u16,
+    condition: bool,
+    wait_remaining: u16,
+}
+
+impl Execution {
+    /// Executes instructions until the procedure waits, ends or uses up its budget.
+    fn step(&mut self, parent: &dyn Parent, telecommands: &mut Vec<Vec<u8>>) {
+        if self.wait_remaining > 0 {
+            self.wait_remaining -= 1;
+            if self.wait_remaining > 0 {
+                return;
+            }
+        }
+        for _ in 0..MAX_INSTRUCTIONS_PER_TICK {
+            let Some(instruction) = self.procedure.instructions.get(self.program_counter as usize) else {
+                self.state = ProcedureState::Inactive;
+                return;
+            };
+            self.program_counter += 1;
+            match instruction {
+                Instruction::End => {
+                    self.state = ProcedureState::Inactive;
+                    return;
+                }
+                Instruction::Wait { ticks } => {
+                    self.wait_remaining = *ticks;
+                    if *ticks > 0 {
+                        return;
+                    }
+                }
+                Instruction::Send { data } => telecommands.push(data.clone()),
+                Instruction::Check {
+                    parameter_id,
+                    comparison,
+                    value,
+                } => {
+                    let Some(current) = parent.get_parameter_value(*parameter_id) else {
+                        self.program_counter -= 1;
+                        self.state = ProcedureState::Failed;
+                        return;
+                    };
+                    self.condition = comparison.evaluate(current, *value);
+                }
+                Instruction::Jump { target } => self.program_counter = *target,
+                Instruction::JumpIf { target } => {
+                    if self.condition {
+                        self.program_counter = *target;
+                    }
+                }
+                Instruction::JumpUnless { target } => {
+                    if !self.condition {
+                        self.program_counter = *target;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Responder for the On-board Operations Procedure Service.
+///
+/// The application calls `tick` on every sync tick; each running
+/// procedure executes until it waits or ends. Telecommands sent by
+/// procedures are handed to the dispatcher after all procedures stepped.
+/// Requests are verified with ST01: malformed requests fail acceptance, and
+/// requests the procedures cannot carry out fail completion.
+pub struct OnboardOperationsServiceResponder {
+    parent: Arc<dyn Parent>,
+    node_id: u32,
+    executions: Mutex<BTreeMap<u8, Execution>>,
+}
+
+impl OnboardOperationsServiceResponder {
+    /// Creates a new responder. Procedures send telecommands to `node_id`.
+    pub fn new(parent: Arc<dyn Parent>, node_id: u32) -> Self {
+        OnboardOperationsServiceResponder {
+            parent,
+            node_id,
+            executions: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    ///
+    /// Failed requests leave the procedures unchanged.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+        if case == (18, 20) {
+            self.send_status_report();
+            return;
+        }
+        if !matches!(case, (18, 1..=6)) {
+            return;
+        }
+        let verification = self.parent.request_verification();
+        let Some((&procedure_id, code)) = data.split_first() else {
+            verification.send_fail_acceptance_report(&[service, subtype]);
+            return;
+        };
+        let procedure = match case {
+            (18, 1) => match Procedure::decode(code) {
+                Ok(procedure) => Some(procedure),
+                Err(_) => {
+                    verification.send_fail_acceptance_report(&[service, subtype]);
+                    return;
+                }
+            },
+            _ => None,
+        };
+        verification.send_success_acceptance_report(&[service, subtype]);
+
+        let result = match case {
+            (18, 1) => procedure.map_or(Err(ObcpError::InvalidProcedure), |procedure| {
+                self.load(procedure_id, procedure)
+            }),
+            (18, 2) => self.unload(procedure_id),
+            (18, 3) => self.start(procedure_id),
+            (18, 4) => self.stop(procedure_id),
+            (18, 5) => self.suspend(procedure_id),
+            (18, 6) => self.resume(procedure_id),
+            _ => return,
+        };
+        match result {
+            Ok(()) => verification.send_success_completion_report(&[service, subtype]),
+            Err(_) => verification.send_fail_completion_report(&[service, subtype]),
+        }
+    }
+
+    /// Loads a procedure in the inactive state.
+    pub fn load(&self, procedure_id: u8, procedure: Procedure) -> Result<(), ObcpError> {
+        procedure.validate()?;
+        let mut executions = self.executions.lock().unwrap();
+        if executions.contains_key(&procedure_id) {
+            return Err(ObcpError::AlreadyLoaded(procedure_id));
+        }
+        if executions.len() >= MAX_PROCEDURES {
+            return Err(ObcpError::Full);
+        }
+        executions.insert(
+            procedure_id,
+            Execution {
+                procedure,
+                state: ProcedureState::Inactive,
+                program_counter: 0,
+                condition: false,
+                wait_remaining: 0,
+            },
+        );
+        Ok(())
+    }
+
+    /// Unloads a procedure that is not running or suspended.
+    pub fn unload(&self, procedure_id: u8) -> Result<(), ObcpError> {
+        let mut executions = self.executions.lock().unwrap();
+        let execution = executions.get(&procedure_id).ok_or(ObcpError::UnknownProcedure(procedure_id))?;
+        if matches!(execution.state, ProcedureState::Running | ProcedureState::Suspended) {
+            return Err(ObcpError::InvalidState(procedure_id));
+        }
+        executions.remove(&procedure_id);
+        Ok(())
+    }
+
+    /// Applies a state transition to a procedure.
+    fn transition(
+        &self,
+        procedure_id: u8,
+        transition: impl FnOnce(&mut Execution) -> Result<(), ObcpError>,
+    ) -> Result<(), ObcpError> {
+        let mut executions = self.executions.lock().unwrap();
+        let execution = executions.get_mut(&procedure_id).ok_or(ObcpError::UnknownProcedure(procedure_id))?;
+        transition(execution)
+    }
+
+    /// Starts an inactive or failed procedure from its first instruction.
+    pub fn start(&self, procedure_id: u8) -> Result<(), ObcpError> {
+        self.transition(procedure_id, |execution| {
+            if matches!(execution.state, ProcedureState::Running | ProcedureState::Suspended) {
+                return Err(ObcpError::InvalidState(procedure_id));
+            }
+            execution.state = ProcedureState::Running;
+            execution.program_counter = 0;
+            execution.condition = false;
+            execution.wait_remaining = 0;
+            Ok(())
+        })
+    }
+
+    /// Stops a running or suspended procedure.
+    pub fn stop(&self, procedure_id: u8) -> Result<(), ObcpError> {
+        self.transition(procedure_id, |execution| {
+            if !matches!(execution.state, ProcedureState::Running | ProcedureState::Suspended) {
+                return Err(ObcpError::InvalidState(procedure_id));
+            }
+            execution.state = ProcedureState::Inactive;
+            Ok(())
+        })
+    }
+
+    /// Suspends a running procedure.
+    pub fn suspend(&self, procedure_id: u8) -> Result<(), ObcpError> {
+        self.transition(procedure_id, |execution| {
+            if execution.state != ProcedureState::Running {
+                return Err(ObcpError::InvalidState(procedure_id));
+            }
+            execution.state = ProcedureState::Suspended;
+            Ok(())
+        })
+    }
+
+    /// Resumes a suspended procedure where it was suspended.
+    pub fn resume(&self, procedure_id: u8) -> Result<(), ObcpError> {
+        self.transition(procedure_id, |execution| {
+            if execution.state != ProcedureState::Suspended {
+                return Err(ObcpError::InvalidState(procedure_id));
+            }
+            execution.state = ProcedureState::Running;
+            Ok(())
+        })
+    }
+
+    /// Returns the status of a procedure.
+    pub fn status(&self, procedure_id: u8) -> Option<ProcedureStatus> {
+        self.statuses().into_iter().find(|status| status.procedure_id == procedure_id)
+    }
+
+    /// Returns the status of all procedures.
+    pub fn statuses(&self) -> Vec<ProcedureStatus> {
+        self.executions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(procedure_id, execution)| ProcedureStatus {
+                procedure_id: *procedure_id,
+                state: execution.state,
+                program_counter: execution.program_counter,
+            })
+            .collect()
+    }
+
+    /// Steps all running procedures. Returns the number of telecommands sent.
+    pub fn tick(&self) -> usize {
+        let mut telecommands = Vec::new();
+        {
+            let mut executions = self.executions.lock().unwrap();
+            for execution in executions
+                .values_mut()
+                .filter(|execution| execution.state == ProcedureState::Running)
+            {
+                execution.step(self.parent.as_ref(), &mut telecommands);
+            }
+        }
+        let count = telecommands.len();
+        for data in telecommands {
+            self.parent.dispatch(data, self.node_id);
+        }
+        count
+    }
+
+    /// Sends the status report (18,21) of all procedures.
+    pub fn send_status_report(&self) {
+        let statuses = self.statuses();
+        let mut packet_data = vec![18, 21, statuses.len() as u8];
+        for status in &statuses {
+            status.encode(&mut packet_data);
+        }
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
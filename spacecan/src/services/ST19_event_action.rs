@@ -0,0 +1,323 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+use super::encoding::{decode_id_list, encode_id_list};
+
+/// Node ID of the controller, the destination of all event-action reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+    /// Hands a triggered telecommand to the packet utilization dispatcher.
+    fn dispatch(&self, data: Vec<u8>, node_id: u32);
+}
+
+/// Telecommand executed when an ST05 event is raised.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventActionDefinition {
+    pub event_id: u16,
+    /// Telecommand packet data, starting with service and subtype.
+    pub action: Vec<u8>,
+    pub enabled: bool,
+}
+
+impl EventActionDefinition {
+    /// Creates a new definition. Definitions are added disabled, as required by ECSS.
+    pub fn new(event_id: u16, action: Vec<u8>) -> Self {
+        EventActionDefinition {
+            event_id,
+            action,
+            enabled: false,
+        }
+    }
+}
+
+/// Callback invoked by the controller for every received definitions report.
+pub type DefinitionsReportHandler = Arc<dyn Fn(u32, &[EventActionDefinition]) + Send + Sync>;
+
+/// Encodes definitions as count followed by event ID, enabled flag, action length (u16) and action.
+///
+/// The enabled flag is ignored when adding definitions.
+fn encode_definitions(definitions: &[EventActionDefinition]) -> Vec<u8> {
+    let definitions = &definitions[..definitions.len().min(u8::MAX as usize)];
+    let mut data = vec![definitions.len() as u8];
+    for definition in definitions {
+        data.extend_from_slice(&definition.event_id.to_be_bytes());
+        data.push(definition.enabled as u8);
+        data.extend_from_slice(&(definition.action.len() as u16).to_be_bytes());
+        data.extend_from_slice(&definition.action);
+    }
+    data
+}
+
+/// Decodes definitions encoded by `encode_definitions`.
+fn decode_definitions(data: &[u8]) -> Option<Vec<EventActionDefinition>> {
+    let (&count, mut rest) = data.split_first()?;
+    let mut definitions = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        if rest.len() < 5 {
+            return None;
+        }
+        let event_id = u16::from_be_bytes([rest[0], rest[1]]);
+        let enabled = rest[2] != 0;
+        let length = u16::from_be_bytes([rest[3], rest[4]]) as usize;
+        rest = &rest[5..];
+        if rest.len() < length {
+            return None;
+        }
+        definitions.push(EventActionDefinition {
+            event_id,
+            action: rest[..length].to_vec(),
+            enabled,
+        });
+        rest = &rest[length..];
+    }
+    Some(definitions)
+}
+
+/// Controller for the Event-Action Service.
+pub struct EventActionServiceController {
+    parent: Arc<dyn Parent>,
+    definitions: Mutex<BTreeMap<u32, Vec<EventActionDefinition>>>,
+    report_handler: Option<DefinitionsReportHandler>,
+}
+
+impl EventActionServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        EventActionServiceController {
+            parent,
+            definitions: Mutex::new(BTreeMap::new()),
+            report_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every received definitions report.
+    pub fn set_report_handler(&mut self, handler: DefinitionsReportHandler) {
+        self.report_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+
+        if case == (19, 7)
+            && let Some(definitions) = decode_definitions(&data)
+        {
+            if let Some(handler) = &self.report_handler {
+                handler(node_id, &definitions);
+            }
+            self.definitions.lock().unwrap().insert(node_id, definitions);
+        }
+    }
+
+    /// Returns the definitions last reported by a node with (19,7).
+    pub fn definitions(&self, node_id: u32) -> Option<Vec<EventActionDefinition>> {
+        self.definitions.lock().unwrap().get(&node_id).cloned()
+    }
+
+    /// Adds event-action definitions to a node (19,1). They are added disabled.
+    pub fn send_add_definitions(&self, node_id: u32, definitions: &[EventActionDefinition]) {
+        let mut packet_data = vec![19, 1];
+        packet_data.extend(encode_definitions(definitions));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Deletes the definitions of the given events from a node (19,2).
+    pub fn send_delete_definitions(&self, node_id: u32, event_ids: &[u16]) {
+        let mut packet_data = vec![19, 2];
+        packet_data.extend(encode_id_list(event_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Deletes all definitions from a node except the enabled ones (19,3).
+    pub fn send_delete_all_definitions(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![19, 3]), node_id);
+    }
+
+    /// Enables the definitions of the given events on a node (19,4).
+    pub fn send_enable_definitions(&self, node_id: u32, event_ids: &[u16]) {
+        let mut packet_data = vec![19, 4];
+        packet_data.extend(encode_id_list(event_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Disables the definitions of the given events on a node (19,5).
+    pub fn send_disable_definitions(&self, node_id: u32, event_ids: &[u16]) {
+        let mut packet_data = vec![19, 5];
+        packet_data.extend(encode_id_list(event_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Requests the definitions of a node (19,6).
+    pub fn send_report_definitions(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![19, 6]), node_id);
+    }
+
+    /// Enables the event-action function of a node (19,8).
+    pub fn send_enable_event_action(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![19, 8]), node_id);
+    }
+
+    /// Disables the event-action function of a node (19,9).
+    pub fn send_disable_event_action(&self, node_id: u32) {
+        self.parent.send(Packet::new(vec![19, 9]), node_id);
+    }
+}
+
+/// Responder for the Event-Action Service.
+///
+/// Connect `received_event` to the event listener of the node's event
+/// reporting responder to execute the stored telecommands.
+pub struct EventActionServiceResponder {
+    parent: Arc<dyn Parent>,
+    definitions: Mutex<BTreeMap<u16, EventActionDefinition>>,
+    enabled: Mutex<bool>,
+}
+
+impl EventActionServiceResponder {
+    /// Creates a new responder with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        EventActionServiceResponder {
+            parent,
+            definitions: Mutex::new(BTreeMap::new()),
+            enabled: Mutex::new(true),
+        }
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+
+        match case {
+            (19, 1) => {
+                if let Some(definitions) = decode_definitions(&data) {
+                    for definition in definitions {
+                        self.add_definition(EventActionDefinition::new(definition.event_id, definition.action));
+                    }
+                }
+            }
+            (19, 2) => {
+                if let Some((event_ids, _)) = decode_id_list(&data) {
+                    for event_id in event_ids {
+                        self.delete_definition(event_id);
+                    }
+                }
+            }
+            (19, 3) => {
+                self.delete_all_definitions();
+            }
+            (19, 4) | (19, 5) => {
+                if let Some((event_ids, _)) = decode_id_list(&data) {
+                    for event_id in event_ids {
+                        self.set_definition_enabled(event_id, subtype == 4);
+                    }
+                }
+            }
+            (19, 6) => self.send_definitions_report(),
+            (19, 8) => self.set_enabled(true),
+            (19, 9) => self.set_enabled(false),
+            _ => {}
+        }
+    }
+
+    /// Adds a definition, unless one exists for the same event.
+    ///
+    /// Returns true if the definition was added.
+    pub fn add_definition(&self, definition: EventActionDefinition) -> bool {
+        let mut definitions = self.definitions.lock().unwrap();
+        if definitions.contains_key(&definition.event_id) {
+            return false;
+        }
+        definitions.insert(definition.event_id, definition);
+        true
+    }
+
+    /// Deletes the definition of an event. Enabled definitions cannot be deleted.
+    ///
+    /// Returns true if the definition was deleted.
+    pub fn delete_definition(&self, event_id: u16) -> bool {
+        let mut definitions = self.definitions.lock().unwrap();
+        if definitions.get(&event_id).is_none_or(|definition| definition.enabled) {
+            return false;
+        }
+        definitions.remove(&event_id);
+        true
+    }
+
+    /// Deletes the definitions of all events, except the enabled ones.
+    ///
+    /// Returns the number of deleted definitions.
+    pub fn delete_all_definitions(&self) -> usize {
+        let mut definitions = self.definitions.lock().unwrap();
+        let count = definitions.len();
+        definitions.retain(|_, definition| definition.enabled);
+        count - definitions.len()
+    }
+
+    /// Enables or disables the definition of an event.
+    pub fn set_definition_enabled(&self, event_id: u16, enabled: bool) {
+        if let Some(definition) = self.definitions.lock().unwrap().get_mut(&event_id) {
+            definition.enabled = enabled;
+        }
+    }
+
+    /// Returns the definition of an event.
+    pub fn get_definition(&self, event_id: u16) -> Option<EventActionDefinition> {
+        self.definitions.lock().unwrap().get(&event_id).cloned()
+    }
+
+    /// Enables or disables the event-action function as a whole.
+    pub fn set_enabled(&self, enabled: bool) {
+        *self.enabled.lock().unwrap() = enabled;
+    }
+
+    /// Returns true if the event-action function is enabled.
+    pub fn is_enabled(&self) -> bool {
+        *self.enabled.lock().unwrap()
+    }
+
+    /// Handler for a raised event. Dispatches the stored telecommand if its definition is enabled.
+    ///
+    /// Returns true if a telecommand was dispatched.
+    pub fn received_event(&self, event_id: u16) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+        // Dispatch outside the lock: the action may itself be an event-action telecommand.
+        let action = match self.definitions.lock().unwrap().get(&event_id) {
+            Some(definition) if definition.enabled => definition.action.clone(),
+            _ => return false,
+        };
+        self.parent.dispatch(action, CONTROLLER_NODE_ID);
+        true
+    }
+
+    /// Sends the event-action definitions report (19,7).
+    pub fn send_definitions_report(&self) {
+        let definitions: Vec<EventActionDefinition> = self.definitions.lock().unwrap().values().cloned().collect();
+        let mut packet_data = vec![19, 7];
+        packet_data.extend(encode_definitions(&definitions));
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
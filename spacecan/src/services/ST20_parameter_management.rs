@@ -12,41 +12,53 @@ use core::option::Option;
 use core::option::Option::{Some, None};
 use core::result::Result;
 use core::result::Result::{Ok, Err};
-use core::mem::size_of;
-use alloc::format;
+use std::sync::Mutex;
+
+use super::encoding::{self, decode_id_list, encode_id_list, EncodingError};
+use crate::storage::{ParameterStore, ParameterValues, PersistencePolicy, StoreError};
 
 #[cfg(feature = "std")]
-use std::io::{self, BufReader};
+use std::io::BufReader;
 #[cfg(feature = "std")]
 use std::fs::File;
 #[cfg(feature = "std")]
 use serde_json::Value;
 
+/// Node ID of the controller, the destination of all parameter reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+
+/// Represents a packet with data payload.
 #[derive(Debug)]
-struct Packet {
-    data: Vec<u8>,
+pub struct Packet {
+    pub data: Vec<u8>,
 }
 
 impl Packet {
-    fn new(data: Vec<u8>) -> Self {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
         Packet { data }
     }
 }
 
-trait Parent {
+/// Trait defining the parent interface for sending packets and verification reports.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
     fn send(&self, packet: Packet, node_id: u32);
+    /// Returns the request verification service (ST01) of this node.
     fn request_verification(&self) -> &dyn RequestVerification;
 }
 
-trait RequestVerification {
+/// Verification reports sent by the responder, see ST01.
+pub trait RequestVerification {
     fn send_success_acceptance_report(&self, args: &[u8]);
     fn send_success_completion_report(&self, args: &[u8]);
     fn send_fail_acceptance_report(&self, args: &[u8]);
     fn send_fail_completion_report(&self, args: &[u8]);
 }
 
+/// Parameter of the pool, identified by node ID and parameter ID.
 #[derive(Debug)]
-struct Parameter {
+pub struct Parameter {
     parameter_id: (u32, u32),
     parameter_name: String,
     encoding: String,
@@ -54,7 +66,8 @@ struct Parameter {
 }
 
 impl Parameter {
-    fn new(parameter_id: (u32, u32), parameter_name: String, encoding: String, value: f64) -> Self {
+    /// Creates a parameter whose value is encoded with the given format string, e.g. `"!f"`.
+    pub fn new(parameter_id: (u32, u32), parameter_name: String, encoding: String, value: f64) -> Self {
         Parameter {
             parameter_id,
             parameter_name,
@@ -63,45 +76,49 @@ impl Parameter {
         }
     }
 
-    fn encode(&self) -> Vec<u8> {
-        let _encoding = if self.encoding.starts_with("!") {
-            &self.encoding
-        } else {
-            &format!("!{}", self.encoding)
-        };
-        let size = self.get_encoded_size();
-        let buffer = vec![0; size];
-        buffer
+    pub fn parameter_id(&self) -> (u32, u32) {
+        self.parameter_id
     }
 
-    fn decode(&self, _data: &[u8]) -> f64 {
-        let _encoding = if self.encoding.starts_with("!") {
-            &self.encoding
-        } else {
-            &format!("!{}", self.encoding)
-        };
-        0.0
+    pub fn name(&self) -> &str {
+        &self.parameter_name
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, EncodingError> {
+        encoding::encode(&self.encoding, &[self.value])
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<f64, EncodingError> {
+        Ok(encoding::decode(&self.encoding, data)?[0])
     }
 
-    fn get_encoded_size(&self) -> usize {
-        size_of::<f64>()
+    fn get_encoded_size(&self) -> Result<usize, EncodingError> {
+        encoding::encoded_size(&self.encoding)
     }
 }
 
 struct ParameterManagementService {
-    parent: Box<dyn Parent>,
     parameter_pool: BTreeMap<(u32, u32), Parameter>,
+    store: Option<Box<dyn ParameterStore>>,
+    policy: PersistencePolicy,
+    restored_values: ParameterValues,
 }
 
 impl ParameterManagementService {
-    fn new(parent: Box<dyn Parent>) -> Self {
+    fn new() -> Self {
         ParameterManagementService {
-            parent,
             parameter_pool: BTreeMap::new(),
+            store: None,
+            policy: PersistencePolicy::Manual,
+            restored_values: ParameterValues::new(),
         }
     }
 
-    fn add_parameter(&mut self, parameter: Parameter) {
+    fn add_parameter(&mut self, mut parameter: Parameter) {
+        // Parameters defined after a load pick up their persisted value.
+        if let Some(value) = self.restored_values.get(&parameter.parameter_id) {
+            parameter.value = *value;
+        }
         self.parameter_pool.insert(parameter.parameter_id, parameter);
     }
 
@@ -109,34 +126,65 @@ impl ParameterManagementService {
         self.parameter_pool.get(&parameter_id)
     }
 
-    fn set_parameter_value(&mut self, parameter_id: (u32, u32), value: f64) {
-        if let Some(param) = self.parameter_pool.get_mut(&parameter_id) {
-            param.value = value;
+    fn set_parameter_value(&mut self, parameter_id: (u32, u32), value: f64) -> Result<(), StoreError> {
+        self.set_parameter_values(&[(parameter_id, value)])
+    }
+
+    /// Sets several values, saving them once if the policy saves on change.
+    fn set_parameter_values(&mut self, values: &[((u32, u32), f64)]) -> Result<(), StoreError> {
+        let mut changed = false;
+        for (parameter_id, value) in values {
+            if let Some(param) = self.parameter_pool.get_mut(parameter_id) {
+                param.value = *value;
+                changed = true;
+            }
         }
+        if changed && self.policy.saves_on_change() {
+            return self.save_parameters();
+        }
+        Ok(())
     }
 
     fn get_parameter_value(&self, parameter_id: (u32, u32)) -> Option<f64> {
         self.parameter_pool.get(&parameter_id).map(|p| p.value)
     }
 
-    fn get_parameter_encoding(&self, parameter_id: (u32, u32)) -> Option<&String> {
-        self.parameter_pool.get(&parameter_id).map(|p| &p.encoding)
+    fn attach_store(&mut self, store: Box<dyn ParameterStore>, policy: PersistencePolicy) -> Result<(), StoreError> {
+        self.store = Some(store);
+        self.policy = policy;
+        if policy.loads_on_start() {
+            self.load_parameters()?;
+        }
+        Ok(())
     }
-}
 
-pub struct ParameterManagementServiceController {
-    service: ParameterManagementService,
-}
-
-impl ParameterManagementServiceController {
-    pub fn new(parent: Box<dyn Parent>) -> Self {
-        ParameterManagementServiceController {
-            service: ParameterManagementService::new(parent),
+    fn load_parameters(&mut self) -> Result<(), StoreError> {
+        let Some(store) = self.store.as_mut() else {
+            return Ok(());
+        };
+        self.restored_values = store.load()?;
+        for (parameter_id, value) in &self.restored_values {
+            if let Some(param) = self.parameter_pool.get_mut(parameter_id) {
+                param.value = *value;
+            }
         }
+        Ok(())
+    }
+
+    fn save_parameters(&mut self) -> Result<(), StoreError> {
+        let Some(store) = self.store.as_mut() else {
+            return Ok(());
+        };
+        let values: ParameterValues = self
+            .parameter_pool
+            .iter()
+            .map(|(parameter_id, param)| (*parameter_id, param.value))
+            .collect();
+        store.save(&values)
     }
 
     #[cfg(feature = "std")]
-    pub fn add_parameters_from_file(&mut self, filepath: &str, node_id: u32) -> Result<(), std::io::Error> {
+    fn add_parameters_from_file(&mut self, filepath: &str, node_id: u32) -> Result<(), std::io::Error> {
         let file = File::open(filepath)?;
         let reader = BufReader::new(file);
         let json: Value = serde_json::from_reader(reader)?;
@@ -150,9 +198,219 @@ impl ParameterManagementServiceController {
                 let value = 0.0;
 
                 let parameter = Parameter::new(parameter_id, parameter_name, encoding, value);
-                self.service.add_parameter(parameter);
+                self.add_parameter(parameter);
             }
         }
         Ok(())
     }
+
+    /// Decodes parameter IDs and encoded values of one node, as carried by (20,2) and (20,3).
+    fn decode_values(&self, node_id: u32, data: &[u8]) -> Option<Vec<((u32, u32), f64)>> {
+        let (&count, mut data) = data.split_first()?;
+        let mut values = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let id = data.get(..4)?;
+            let parameter_id = (node_id, u32::from_be_bytes([id[0], id[1], id[2], id[3]]));
+            let parameter = self.get_parameter(parameter_id)?;
+            let size = parameter.get_encoded_size().ok()?;
+            let encoded = data.get(4..4 + size)?;
+            values.push((parameter_id, parameter.decode(encoded).ok()?));
+            data = &data[4 + size..];
+        }
+        Some(values)
+    }
+}
+
+/// Controller for the Parameter Management Service.
+///
+/// Holds the parameter definitions of all nodes; values reported by the
+/// nodes with (20,2) update the pool.
+pub struct ParameterManagementServiceController {
+    parent: Box<dyn Parent>,
+    service: ParameterManagementService,
+}
+
+impl ParameterManagementServiceController {
+    pub fn new(parent: Box<dyn Parent>) -> Self {
+        ParameterManagementServiceController {
+            parent,
+            service: ParameterManagementService::new(),
+        }
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    ///
+    /// Fails if reported values could not be saved to the attached store.
+    pub fn process(&mut self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) -> Result<(), StoreError> {
+        if (service, subtype) == (20, 2)
+            && let Some(values) = self.service.decode_values(node_id, &data)
+        {
+            return self.service.set_parameter_values(&values);
+        }
+        Ok(())
+    }
+
+    /// Adds a parameter definition to the pool.
+    pub fn add_parameter(&mut self, parameter: Parameter) {
+        self.service.add_parameter(parameter);
+    }
+
+    /// Attaches a persistent store and applies the given load/save policy.
+    pub fn attach_parameter_store(&mut self, store: Box<dyn ParameterStore>, policy: PersistencePolicy) -> Result<(), StoreError> {
+        self.service.attach_store(store, policy)
+    }
+
+    /// Restores parameter values from the attached store.
+    pub fn load_parameters(&mut self) -> Result<(), StoreError> {
+        self.service.load_parameters()
+    }
+
+    /// Writes the current parameter values to the attached store.
+    pub fn save_parameters(&mut self) -> Result<(), StoreError> {
+        self.service.save_parameters()
+    }
+
+    /// Sets a parameter value, saving it if the policy saves on change.
+    pub fn set_parameter_value(&mut self, parameter_id: (u32, u32), value: f64) -> Result<(), StoreError> {
+        self.service.set_parameter_value(parameter_id, value)
+    }
+
+    /// Returns the current value of a parameter.
+    pub fn get_parameter_value(&self, parameter_id: (u32, u32)) -> Option<f64> {
+        self.service.get_parameter_value(parameter_id)
+    }
+
+    /// Requests the values of parameters of a node (20,1).
+    pub fn send_report_request(&self, node_id: u32, parameter_ids: &[u32]) {
+        let mut packet_data = vec![20, 1];
+        packet_data.extend(encode_id_list(parameter_ids));
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Sets parameter values of a node (20,3), encoded as defined in the pool.
+    ///
+    /// Returns false without sending anything if a parameter is not in the
+    /// pool or its value cannot be encoded.
+    pub fn send_set_values(&self, node_id: u32, values: &[(u32, f64)]) -> bool {
+        let values = &values[..values.len().min(u8::MAX as usize)];
+        let mut packet_data = vec![20, 3, values.len() as u8];
+        for (parameter_id, value) in values {
+            let Some(parameter) = self.service.get_parameter((node_id, *parameter_id)) else {
+                return false;
+            };
+            let Ok(encoded) = encoding::encode(&parameter.encoding, &[*value]) else {
+                return false;
+            };
+            packet_data.extend_from_slice(&parameter_id.to_be_bytes());
+            packet_data.extend(encoded);
+        }
+        self.parent.send(Packet::new(packet_data), node_id);
+        true
+    }
+
+    #[cfg(feature = "std")]
+    pub fn add_parameters_from_file(&mut self, filepath: &str, node_id: u32) -> Result<(), std::io::Error> {
+        self.service.add_parameters_from_file(filepath, node_id)
+    }
+}
+
+/// Responder for the Parameter Management Service.
+///
+/// Owns the parameters of its node. Values set with (20,3) are saved to the
+/// attached store if its policy saves on change, and a store attached with
+/// a loading policy restores them on start.
+pub struct ParameterManagementServiceResponder {
+    parent: Box<dyn Parent>,
+    node_id: u32,
+    service: Mutex<ParameterManagementService>,
+}
+
+impl ParameterManagementServiceResponder {
+    /// Creates a responder for the parameters of the given node.
+    pub fn new(parent: Box<dyn Parent>, node_id: u32) -> Self {
+        ParameterManagementServiceResponder {
+            parent,
+            node_id,
+            service: Mutex::new(ParameterManagementService::new()),
+        }
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        let case = (service, subtype);
+        let verification = self.parent.request_verification();
+
+        match case {
+            (20, 1) => {
+                let Some(report) = decode_id_list(&data).and_then(|(ids, _)| self.encode_report(&ids)) else {
+                    verification.send_fail_acceptance_report(&[service, subtype]);
+                    return;
+                };
+                verification.send_success_acceptance_report(&[service, subtype]);
+                self.parent.send(Packet::new(report), CONTROLLER_NODE_ID);
+                verification.send_success_completion_report(&[service, subtype]);
+            }
+            (20, 3) => {
+                let mut pool = self.service.lock().unwrap();
+                let Some(values) = pool.decode_values(self.node_id, &data) else {
+                    verification.send_fail_acceptance_report(&[service, subtype]);
+                    return;
+                };
+                verification.send_success_acceptance_report(&[service, subtype]);
+                match pool.set_parameter_values(&values) {
+                    Ok(()) => verification.send_success_completion_report(&[service, subtype]),
+                    Err(_) => verification.send_fail_completion_report(&[service, subtype]),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Encodes a parameter value report (20,2), or None if a parameter is unknown.
+    fn encode_report(&self, parameter_ids: &[u32]) -> Option<Vec<u8>> {
+        let pool = self.service.lock().unwrap();
+        let mut report = vec![20, 2, parameter_ids.len() as u8];
+        for parameter_id in parameter_ids {
+            let parameter = pool.get_parameter((self.node_id, *parameter_id))?;
+            report.extend_from_slice(&parameter_id.to_be_bytes());
+            report.extend(parameter.encode().ok()?);
+        }
+        Some(report)
+    }
+
+    /// Adds a parameter of this node; a value restored from the store takes precedence.
+    pub fn add_parameter(&self, parameter_id: u32, parameter_name: String, encoding: String, value: f64) {
+        let parameter = Parameter::new((self.node_id, parameter_id), parameter_name, encoding, value);
+        self.service.lock().unwrap().add_parameter(parameter);
+    }
+
+    /// Attaches a persistent store and applies the given load/save policy.
+    pub fn attach_parameter_store(&self, store: Box<dyn ParameterStore>, policy: PersistencePolicy) -> Result<(), StoreError> {
+        self.service.lock().unwrap().attach_store(store, policy)
+    }
+
+    /// Restores parameter values from the attached store.
+    pub fn load_parameters(&self) -> Result<(), StoreError> {
+        self.service.lock().unwrap().load_parameters()
+    }
+
+    /// Writes the current parameter values to the attached store.
+    pub fn save_parameters(&self) -> Result<(), StoreError> {
+        self.service.lock().unwrap().save_parameters()
+    }
+
+    /// Sets a parameter value of this node, saving it if the policy saves on change.
+    pub fn set_parameter_value(&self, parameter_id: u32, value: f64) -> Result<(), StoreError> {
+        self.service.lock().unwrap().set_parameter_value((self.node_id, parameter_id), value)
+    }
+
+    /// Returns the current value of a parameter of this node.
+    pub fn get_parameter_value(&self, parameter_id: u32) -> Option<f64> {
+        self.service.lock().unwrap().get_parameter_value((self.node_id, parameter_id))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn add_parameters_from_file(&self, filepath: &str) -> Result<(), std::io::Error> {
+        self.service.lock().unwrap().add_parameters_from_file(filepath, self.node_id)
+    }
 }
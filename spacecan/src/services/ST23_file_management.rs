@@ -0,0 +1,787 @@
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::primitives::packet::MAX_PACKET_LENGTH;
+
+/// Node ID of the controller, the destination of all file reports.
+const CONTROLLER_NODE_ID: u32 = 0;
+/// Encoded size of file attributes: directory flag and size.
+const ATTRIBUTES_SIZE: usize = 9;
+
+/// Represents a packet with data payload.
+#[derive(Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+}
+
+impl Packet {
+    /// Creates a new packet with the given data.
+    pub fn new(data: Vec<u8>) -> Self {
+        Packet { data }
+    }
+}
+
+/// Trait defining the parent interface for sending packets.
+pub trait Parent: Send + Sync {
+    /// Sends a packet to the given node.
+    fn send(&self, packet: Packet, node_id: u32);
+    /// Sends packet data of any length to the given node by large packet transfer (ST13).
+    fn send_large(&self, data: Vec<u8>, node_id: u32);
+}
+
+/// Errors raised by file operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileError {
+    /// The file or directory does not exist.
+    NotFound,
+    /// A file or directory already exists at the path.
+    AlreadyExists,
+    /// A directory was expected.
+    NotADirectory,
+    /// A file was expected.
+    IsADirectory,
+    /// The directory still has entries.
+    DirectoryNotEmpty,
+    /// The path is malformed or leaves the file system.
+    InvalidPath,
+    /// The storage failed.
+    Io,
+}
+
+impl FileError {
+    fn code(&self) -> u8 {
+        match self {
+            FileError::NotFound => 1,
+            FileError::AlreadyExists => 2,
+            FileError::NotADirectory => 3,
+            FileError::IsADirectory => 4,
+            FileError::DirectoryNotEmpty => 5,
+            FileError::InvalidPath => 6,
+            FileError::Io => 7,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(FileError::NotFound),
+            2 => Some(FileError::AlreadyExists),
+            3 => Some(FileError::NotADirectory),
+            4 => Some(FileError::IsADirectory),
+            5 => Some(FileError::DirectoryNotEmpty),
+            6 => Some(FileError::InvalidPath),
+            7 => Some(FileError::Io),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::NotFound => write!(f, "file not found"),
+            FileError::AlreadyExists => write!(f, "file already exists"),
+            FileError::NotADirectory => write!(f, "not a directory"),
+            FileError::IsADirectory => write!(f, "is a directory"),
+            FileError::DirectoryNotEmpty => write!(f, "directory not empty"),
+            FileError::InvalidPath => write!(f, "invalid path"),
+            FileError::Io => write!(f, "file I/O error"),
+        }
+    }
+}
+
+/// Attributes of a file or directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileAttributes {
+    pub is_directory: bool,
+    /// Size in bytes; zero for directories.
+    pub size: u64,
+}
+
+impl FileAttributes {
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.push(self.is_directory as u8);
+        data.extend_from_slice(&self.size.to_be_bytes());
+    }
+
+    fn decode(data: &[u8]) -> Self {
+        let mut size = [0; 8];
+        size.copy_from_slice(&data[1..9]);
+        FileAttributes {
+            is_directory: data[0] != 0,
+            size: u64::from_be_bytes(size),
+        }
+    }
+}
+
+/// Entry of a directory listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    pub name: String,
+    pub attributes: FileAttributes,
+}
+
+/// File system the application exposes to the file management service.
+///
+/// Paths are relative to the root of the file system, with `/` as the
+/// separator; an empty path is the root directory.
+pub trait FileSystem: Send {
+    /// Creates an empty file.
+    fn create_file(&mut self, path: &str) -> Result<(), FileError>;
+    /// Deletes a file.
+    fn delete_file(&mut self, path: &str) -> Result<(), FileError>;
+    /// Creates a directory.
+    fn create_directory(&mut self, path: &str) -> Result<(), FileError>;
+    /// Deletes an empty directory.
+    fn delete_directory(&mut self, path: &str) -> Result<(), FileError>;
+    /// Returns the attributes of a file or directory.
+    fn attributes(&self, path: &str) -> Result<FileAttributes, FileError>;
+    /// Returns the entries of a directory, sorted by name.
+    fn list_directory(&self, path: &str) -> Result<Vec<DirectoryEntry>, FileError>;
+    /// Returns the contents of a file.
+    fn read_file(&self, path: &str) -> Result<Vec<u8>, FileError>;
+    /// Writes a file, creating or replacing it.
+    fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), FileError>;
+    /// Moves a file or directory to a path that does not exist yet.
+    fn rename(&mut self, from: &str, to: &str) -> Result<(), FileError>;
+    /// Copies a file to a path that does not exist yet.
+    fn copy_file(&mut self, from: &str, to: &str) -> Result<(), FileError> {
+        let data = self.read_file(from)?;
+        match self.attributes(to) {
+            Ok(_) => return Err(FileError::AlreadyExists),
+            Err(FileError::NotFound) => {}
+            Err(e) => return Err(e),
+        }
+        self.write_file(to, &data)
+    }
+}
+
+/// Splits a path into its components, rejecting `.` and `..`.
+fn path_components(path: &str) -> Result<Vec<&str>, FileError> {
+    let components: Vec<&str> = path.split('/').filter(|component| !component.is_empty()).collect();
+    if components.iter().any(|component| *component == "." || *component == "..") {
+        return Err(FileError::InvalidPath);
+    }
+    Ok(components)
+}
+
+/// File system in a directory of the host, using `std::fs`.
+#[derive(Debug, Clone)]
+pub struct StdFileSystem {
+    root: PathBuf,
+}
+
+impl StdFileSystem {
+    /// Creates a file system rooted at the given directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        StdFileSystem { root: root.into() }
+    }
+
+    fn resolve(&self, path: &str) -> Result<PathBuf, FileError> {
+        let mut resolved = self.root.clone();
+        for component in path_components(path)? {
+            // Reject components such as drive prefixes that would leave the root.
+            if !matches!(Path::new(component).components().next(), Some(Component::Normal(_))) {
+                return Err(FileError::InvalidPath);
+            }
+            resolved.push(component);
+        }
+        Ok(resolved)
+    }
+
+    fn is_directory(path: &Path) -> bool {
+        fs::metadata(path).is_ok_and(|metadata| metadata.is_dir())
+    }
+}
+
+/// Maps an I/O error to a file error.
+fn io_error(error: std::io::Error) -> FileError {
+    match error.kind() {
+        ErrorKind::NotFound => FileError::NotFound,
+        ErrorKind::AlreadyExists => FileError::AlreadyExists,
+        ErrorKind::NotADirectory => FileError::NotADirectory,
+        ErrorKind::IsADirectory => FileError::IsADirectory,
+        ErrorKind::DirectoryNotEmpty => FileError::DirectoryNotEmpty,
+        _ => FileError::Io,
+    }
+}
+
+impl FileSystem for StdFileSystem {
+    fn create_file(&mut self, path: &str) -> Result<(), FileError> {
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(self.resolve(path)?)
+            .map(|_| ())
+            .map_err(io_error)
+    }
+
+    fn delete_file(&mut self, path: &str) -> Result<(), FileError> {
+        let path = self.resolve(path)?;
+        if Self::is_directory(&path) {
+            return Err(FileError::IsADirectory);
+        }
+        fs::remove_file(path).map_err(io_error)
+    }
+
+    fn create_directory(&mut self, path: &str) -> Result<(), FileError> {
+        fs::create_dir(self.resolve(path)?).map_err(io_error)
+    }
+
+    fn delete_directory(&mut self, path: &str) -> Result<(), FileError> {
+        if path_components(path)?.is_empty() {
+            return Err(FileError::InvalidPath);
+        }
+        let path = self.resolve(path)?;
+        if !path.exists() {
+            return Err(FileError::NotFound);
+        }
+        if !Self::is_directory(&path) {
+            return Err(FileError::NotADirectory);
+        }
+        fs::remove_dir(path).map_err(io_error)
+    }
+
+    fn attributes(&self, path: &str) -> Result<FileAttributes, FileError> {
+        let metadata = fs::metadata(self.resolve(path)?).map_err(io_error)?;
+        Ok(FileAttributes {
+            is_directory: metadata.is_dir(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+        })
+    }
+
+    fn list_directory(&self, path: &str) -> Result<Vec<DirectoryEntry>, FileError> {
+        let path = self.resolve(path)?;
+        if path.exists() && !Self::is_directory(&path) {
+            return Err(FileError::NotADirectory);
+        }
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(path).map_err(io_error)? {
+            let entry = entry.map_err(io_error)?;
+            let metadata = entry.metadata().map_err(io_error)?;
+            entries.push(DirectoryEntry {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                attributes: FileAttributes {
+                    is_directory: metadata.is_dir(),
+                    size: if metadata.is_dir() { 0 } else { metadata.len() },
+                },
+            });
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    fn read_file(&self, path: &str) -> Result<Vec<u8>, FileError> {
+        let path = self.resolve(path)?;
+        if Self::is_directory(&path) {
+            return Err(FileError::IsADirectory);
+        }
+        fs::read(path).map_err(io_error)
+    }
+
+    fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), FileError> {
+        let path = self.resolve(path)?;
+        if Self::is_directory(&path) {
+            return Err(FileError::IsADirectory);
+        }
+        fs::write(path, data).map_err(io_error)
+    }
+
+    fn rename(&mut self, from: &str, to: &str) -> Result<(), FileError> {
+        let from = self.resolve(from)?;
+        let to = self.resolve(to)?;
+        if !from.exists() {
+            return Err(FileError::NotFound);
+        }
+        if to.exists() {
+            return Err(FileError::AlreadyExists);
+        }
+        fs::rename(from, to).map_err(io_error)
+    }
+}
+
+/// Node of the in-memory file system.
+#[derive(Debug, Clone)]
+enum MemoryNode {
+    File(Vec<u8>),
+    Directory,
+}
+
+/// File system held in RAM, for host testing.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFileSystem {
+    /// Files and directories by normalized path; the root is implicit.
+    nodes: BTreeMap<String, MemoryNode>,
+}
+
+impl MemoryFileSystem {
+    /// Creates an empty file system.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn normalize(path: &str) -> Result<String, FileError> {
+        Ok(path_components(path)?.join("/"))
+    }
+
+    fn parent(path: &str) -> &str {
+        path.rsplit_once('/').map_or("", |(parent, _)| parent)
+    }
+
+    fn is_directory(&self, path: &str) -> bool {
+        path.is_empty() || matches!(self.nodes.get(path), Some(MemoryNode::Directory))
+    }
+
+    /// Checks that a new node can be created at a normalized path.
+    fn check_new(&self, path: &str) -> Result<(), FileError> {
+        if path.is_empty() || self.nodes.contains_key(path) {
+            return Err(FileError::AlreadyExists);
+        }
+        let parent = Self::parent(path);
+        if self.is_directory(parent) {
+            Ok(())
+        } else if self.nodes.contains_key(parent) {
+            Err(FileError::NotADirectory)
+        } else {
+            Err(FileError::NotFound)
+        }
+    }
+
+    fn attributes_of(node: Option<&MemoryNode>) -> FileAttributes {
+        match node {
+            Some(MemoryNode::File(data)) => FileAttributes {
+                is_directory: false,
+                size: data.len() as u64,
+            },
+            _ => FileAttributes {
+                is_directory: true,
+                size: 0,
+            },
+        }
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn create_file(&mut self, path: &str) -> Result<(), FileError> {
+        let path = Self::normalize(path)?;
+        self.check_new(&path)?;
+        self.nodes.insert(path, MemoryNode::File(Vec::new()));
+        Ok(())
+    }
+
+    fn delete_file(&mut self, path: &str) -> Result<(), FileError> {
+        let path = Self::normalize(path)?;
+        match self.nodes.get(&path) {
+            Some(MemoryNode::File(_)) => {
+                self.nodes.remove(&path);
+                Ok(())
+            }
+            Some(MemoryNode::Directory) => Err(FileError::IsADirectory),
+            None if path.is_empty() => Err(FileError::IsADirectory),
+            None => Err(FileError::NotFound),
+        }
+    }
+
+    fn create_directory(&mut self, path: &str) -> Result<(), FileError> {
+        let path = Self::normalize(path)?;
+        self.check_new(&path)?;
+        self.nodes.insert(path, MemoryNode::Directory);
+        Ok(())
+    }
+
+    fn delete_directory(&mut self, path: &str) -> Result<(), FileError> {
+        let path = Self::normalize(path)?;
+        match self.nodes.get(&path) {
+            Some(MemoryNode::Directory) => {}
+            Some(MemoryNode::File(_)) => return Err(FileError::NotADirectory),
+            None if path.is_empty() => return Err(FileError::InvalidPath),
+            None => return Err(FileError::NotFound),
+        }
+        if self.nodes.keys().any(|key| Self::parent(key) == path) {
+            return Err(FileError::DirectoryNotEmpty);
+        }
+        self.nodes.remove(&path);
+        Ok(())
+    }
+
+    fn attributes(&self, path: &str) -> Result<FileAttributes, FileError> {
+        let path = Self::normalize(path)?;
+        if !self.is_directory(&path) && !self.nodes.contains_key(&path) {
+            return Err(FileError::NotFound);
+        }
+        Ok(Self::attributes_of(self.nodes.get(&path)))
+    }
+
+    fn list_directory(&self, path: &str) -> Result<Vec<DirectoryEntry>, FileError> {
+        let path = Self::normalize(path)?;
+        if !self.is_directory(&path) {
+            return Err(match self.nodes.contains_key(&path) {
+                true => FileError::NotADirectory,
+                false => FileError::NotFound,
+            });
+        }
+        Ok(self
+            .nodes
+            .iter()
+            .filter(|(key, _)| Self::parent(key) == path)
+            .map(|(key, node)| DirectoryEntry {
+                name: key.rsplit('/').next().unwrap_or(key).into(),
+                attributes: Self::attributes_of(Some(node)),
+            })
+            .collect())
+    }
+
+    fn read_file(&self, path: &str) -> Result<Vec<u8>, FileError> {
+        let path = Self::normalize(path)?;
+        match self.nodes.get(&path) {
+            Some(MemoryNode::File(data)) => Ok(data.clone()),
+            _ if self.is_directory(&path) => Err(FileError::IsADirectory),
+            _ => Err(FileError::NotFound),
+        }
+    }
+
+    fn write_file(&mut self, path: &str, data: &[u8]) -> Result<(), FileError> {
+        let path = Self::normalize(path)?;
+        match self.nodes.get_mut(&path) {
+            Some(MemoryNode::File(contents)) => {
+                *contents = data.to_vec();
+                Ok(())
+            }
+            Some(MemoryNode::Directory) => Err(FileError::IsADirectory),
+            None => {
+                self.check_new(&path)?;
+                self.nodes.insert(path, MemoryNode::File(data.to_vec()));
+                Ok(())
+            }
+        }
+    }
+
+    fn rename(&mut self, from: &str, to: &str) -> Result<(), FileError> {
+        let from = Self::normalize(from)?;
+        let to = Self::normalize(to)?;
+        if from.is_empty() || !self.nodes.contains_key(&from) {
+            return Err(FileError::NotFound);
+        }
+        let prefix = from.clone() + "/";
+        if to.starts_with(&prefix) {
+            return Err(FileError::InvalidPath);
+        }
+        self.check_new(&to)?;
+        let moved: Vec<String> = self
+            .nodes
+            .keys()
+            .filter(|key| **key == from || key.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for key in moved {
+            let node = self.nodes.remove(&key).unwrap();
+            self.nodes.insert(to.clone() + &key[from.len()..], node);
+        }
+        Ok(())
+    }
+}
+
+/// Failed file operation, reported with (23,131).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFailure {
+    /// Subtype of the failed request.
+    pub subtype: u8,
+    pub path: String,
+    pub error: FileError,
+}
+
+/// Callback invoked by the controller for every downloaded file.
+pub type DownloadHandler = Arc<dyn Fn(u32, &str, &[u8]) + Send + Sync>;
+
+/// Appends a path as length followed by its UTF-8 bytes.
+///
+/// Paths are limited to 255 bytes; longer paths are truncated.
+fn encode_path(data: &mut Vec<u8>, path: &str) {
+    let bytes = &path.as_bytes()[..path.len().min(u8::MAX as usize)];
+    data.push(bytes.len() as u8);
+    data.extend_from_slice(bytes);
+}
+
+/// Decodes a path written by `encode_path`, returning it and the remaining data.
+fn decode_path(data: &[u8]) -> Option<(String, &[u8])> {
+    let (&length, data) = data.split_first()?;
+    let bytes = data.get(..length as usize)?;
+    let path = core::str::from_utf8(bytes).ok()?;
+    Some((path.into(), &data[length as usize..]))
+}
+
+/// Controller for the File Management Service.
+///
+/// Files are uploaded with the mission-specific subtype (23,128) and
+/// downloaded with (23,129), answered by (23,130). Both are carried by
+/// large packet transfer, so the application routes reassembled ST13
+/// messages back into `process`.
+pub struct FileManagementServiceController {
+    parent: Arc<dyn Parent>,
+    attributes: Mutex<BTreeMap<(u32, String), FileAttributes>>,
+    listings: Mutex<BTreeMap<(u32, String), Vec<DirectoryEntry>>>,
+    downloads: Mutex<BTreeMap<(u32, String), Vec<u8>>>,
+    failures: Mutex<Vec<(u32, FileFailure)>>,
+    download_handler: Option<DownloadHandler>,
+}
+
+impl FileManagementServiceController {
+    /// Creates a new controller with the given parent.
+    pub fn new(parent: Arc<dyn Parent>) -> Self {
+        FileManagementServiceController {
+            parent,
+            attributes: Mutex::new(BTreeMap::new()),
+            listings: Mutex::new(BTreeMap::new()),
+            downloads: Mutex::new(BTreeMap::new()),
+            failures: Mutex::new(Vec::new()),
+            download_handler: None,
+        }
+    }
+
+    /// Sets the callback invoked for every downloaded file.
+    pub fn set_download_handler(&mut self, handler: DownloadHandler) {
+        self.download_handler = Some(handler);
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, node_id: u32) {
+        let case = (service, subtype);
+        let Some((path, data)) = decode_path(&data) else {
+            return;
+        };
+
+        match case {
+            (23, 4) if data.len() >= ATTRIBUTES_SIZE => {
+                let attributes = FileAttributes::decode(data);
+                self.attributes.lock().unwrap().insert((node_id, path), attributes);
+            }
+            (23, 13) => {
+                let mut entries = Vec::new();
+                let Some((&count, mut data)) = data.split_first() else {
+                    return;
+                };
+                for _ in 0..count {
+                    let Some((name, rest)) = decode_path(data) else {
+                        return;
+                    };
+                    if rest.len() < ATTRIBUTES_SIZE {
+                        return;
+                    }
+                    entries.push(DirectoryEntry {
+                        name,
+                        attributes: FileAttributes::decode(rest),
+                    });
+                    data = &rest[ATTRIBUTES_SIZE..];
+                }
+                self.listings.lock().unwrap().insert((node_id, path), entries);
+            }
+            (23, 130) => {
+                if let Some(handler) = &self.download_handler {
+                    handler(node_id, &path, data);
+                }
+                self.downloads.lock().unwrap().insert((node_id, path), data.to_vec());
+            }
+            (23, 131) if data.len() >= 2 => {
+                if let Some(error) = FileError::from_code(data[1]) {
+                    let failure = FileFailure {
+                        subtype: data[0],
+                        path,
+                        error,
+                    };
+                    self.failures.lock().unwrap().push((node_id, failure));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the attributes last reported for a path of a node.
+    pub fn attributes(&self, node_id: u32, path: &str) -> Option<FileAttributes> {
+        self.attributes.lock().unwrap().get(&(node_id, path.into())).copied()
+    }
+
+    /// Returns the listing last reported for a directory of a node.
+    pub fn listing(&self, node_id: u32, path: &str) -> Option<Vec<DirectoryEntry>> {
+        self.listings.lock().unwrap().get(&(node_id, path.into())).cloned()
+    }
+
+    /// Removes and returns a file downloaded from a node.
+    pub fn take_download(&self, node_id: u32, path: &str) -> Option<Vec<u8>> {
+        self.downloads.lock().unwrap().remove(&(node_id, path.into()))
+    }
+
+    /// Removes and returns the failures reported by all nodes.
+    pub fn take_failures(&self) -> Vec<(u32, FileFailure)> {
+        core::mem::take(&mut *self.failures.lock().unwrap())
+    }
+
+    fn send_path_request(&self, node_id: u32, subtype: u8, path: &str) {
+        let mut packet_data = vec![23, subtype];
+        encode_path(&mut packet_data, path);
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    fn send_two_path_request(&self, node_id: u32, subtype: u8, from: &str, to: &str) {
+        let mut packet_data = vec![23, subtype];
+        encode_path(&mut packet_data, from);
+        encode_path(&mut packet_data, to);
+        self.parent.send(Packet::new(packet_data), node_id);
+    }
+
+    /// Creates an empty file (23,1).
+    pub fn send_create_file(&self, node_id: u32, path: &str) {
+        self.send_path_request(node_id, 1, path);
+    }
+
+    /// Deletes a file (23,2).
+    pub fn send_delete_file(&self, node_id: u32, path: &str) {
+        self.send_path_request(node_id, 2, path);
+    }
+
+    /// Requests the attributes of a file or directory (23,3).
+    pub fn send_attributes_request(&self, node_id: u32, path: &str) {
+        self.send_path_request(node_id, 3, path);
+    }
+
+    /// Creates a directory (23,9).
+    pub fn send_create_directory(&self, node_id: u32, path: &str) {
+        self.send_path_request(node_id, 9, path);
+    }
+
+    /// Deletes an empty directory (23,10).
+    pub fn send_delete_directory(&self, node_id: u32, path: &str) {
+        self.send_path_request(node_id, 10, path);
+    }
+
+    /// Renames a file or directory (23,11).
+    pub fn send_rename(&self, node_id: u32, from: &str, to: &str) {
+        self.send_two_path_request(node_id, 11, from, to);
+    }
+
+    /// Requests the listing of a directory (23,12).
+    pub fn send_directory_listing_request(&self, node_id: u32, path: &str) {
+        self.send_path_request(node_id, 12, path);
+    }
+
+    /// Copies a file (23,14).
+    pub fn send_copy_file(&self, node_id: u32, from: &str, to: &str) {
+        self.send_two_path_request(node_id, 14, from, to);
+    }
+
+    /// Uploads a file, creating or replacing it (23,128).
+    pub fn upload(&self, node_id: u32, path: &str, contents: &[u8]) {
+        let mut data = vec![23, 128];
+        encode_path(&mut data, path);
+        data.extend_from_slice(contents);
+        self.parent.send_large(data, node_id);
+    }
+
+    /// Requests the download of a file (23,129).
+    ///
+    /// The contents arrive with (23,130) and are available from `take_download`.
+    pub fn request_download(&self, node_id: u32, path: &str) {
+        self.send_path_request(node_id, 129, path);
+    }
+}
+
+/// Responder for the File Management Service.
+///
+/// Failed requests are answered with the mission-specific failure report
+/// (23,131) carrying the path, the request subtype and the error.
+pub struct FileManagementServiceResponder {
+    parent: Arc<dyn Parent>,
+    file_system: Mutex<Box<dyn FileSystem>>,
+}
+
+impl FileManagementServiceResponder {
+    /// Creates a new responder serving the given file system.
+    pub fn new(parent: Arc<dyn Parent>, file_system: Box<dyn FileSystem>) -> Self {
+        FileManagementServiceResponder {
+            parent,
+            file_system: Mutex::new(file_system),
+        }
+    }
+
+    /// Processes incoming packets based on service and subtype.
+    pub fn process(&self, service: u8, subtype: u8, data: Vec<u8>, _node_id: u32) {
+        if service != 23 {
+            return;
+        }
+        let Some((path, data)) = decode_path(&data) else {
+            return;
+        };
+        let second_path = || decode_path(data).map(|(path, _)| path).ok_or(FileError::InvalidPath);
+        let mut file_system = self.file_system.lock().unwrap();
+
+        let result = match subtype {
+            1 => file_system.create_file(&path),
+            2 => file_system.delete_file(&path),
+            3 => file_system.attributes(&path).map(|attributes| {
+                let mut packet_data = vec![23, 4];
+                encode_path(&mut packet_data, &path);
+                attributes.encode(&mut packet_data);
+                self.send_report(packet_data);
+            }),
+            9 => file_system.create_directory(&path),
+            10 => file_system.delete_directory(&path),
+            11 => second_path().and_then(|to| file_system.rename(&path, &to)),
+            12 => file_system.list_directory(&path).map(|entries| {
+                let entries = &entries[..entries.len().min(u8::MAX as usize)];
+                let mut packet_data = vec![23, 13];
+                encode_path(&mut packet_data, &path);
+                packet_data.push(entries.len() as u8);
+                for entry in entries {
+                    encode_path(&mut packet_data, &entry.name);
+                    entry.attributes.encode(&mut packet_data);
+                }
+                self.send_report(packet_data);
+            }),
+            14 => second_path().and_then(|to| file_system.copy_file(&path, &to)),
+            128 => file_system.write_file(&path, data),
+            129 => file_system.read_file(&path).map(|contents| {
+                let mut packet_data = vec![23, 130];
+                encode_path(&mut packet_data, &path);
+                packet_data.extend_from_slice(&contents);
+                self.send_report(packet_data);
+            }),
+            _ => Ok(()),
+        };
+        drop(file_system);
+        if let Err(error) = result {
+            self.send_failure_report(subtype, &path, error);
+        }
+    }
+
+    /// Runs a closure with exclusive access to the file system.
+    pub fn with_file_system<R>(&self, f: impl FnOnce(&mut dyn FileSystem) -> R) -> R {
+        f(self.file_system.lock().unwrap().as_mut())
+    }
+
+    /// Sends a report as a single packet if it fits, by large packet transfer otherwise.
+    fn send_report(&self, packet_data: Vec<u8>) {
+        if packet_data.len() <= MAX_PACKET_LENGTH {
+            self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+        } else {
+            self.parent.send_large(packet_data, CONTROLLER_NODE_ID);
+        }
+    }
+
+    /// Sends the failure report (23,131).
+    pub fn send_failure_report(&self, subtype: u8, path: &str, error: FileError) {
+        let mut packet_data = vec![23, 131];
+        encode_path(&mut packet_data, path);
+        packet_data.push(subtype);
+        packet_data.push(error.code());
+        self.parent.send(Packet::new(packet_data), CONTROLLER_NODE_ID);
+    }
+}
@@ -0,0 +1,170 @@
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Errors raised while encoding or decoding values with a format string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingError {
+    /// The format string contains an unsupported type code.
+    UnknownFormat(char),
+    /// The number of values or bytes does not match the format string.
+    LengthMismatch,
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodingError::UnknownFormat(code) => write!(f, "Unknown format code: {}", code),
+            EncodingError::LengthMismatch => write!(f, "Data does not match format"),
+        }
+    }
+}
+
+/// Returns the size in bytes of a single type code.
+fn code_size(code: char) -> Result<usize, EncodingError> {
+    match code {
+        '?' | 'b' | 'B' => Ok(1),
+        'h' | 'H' => Ok(2),
+        'i' | 'I' | 'l' | 'L' | 'f' => Ok(4),
+        'q' | 'Q' | 'd' => Ok(8),
+        _ => Err(EncodingError::UnknownFormat(code)),
+    }
+}
+
+/// Iterates over the type codes of a format string, skipping byte order markers.
+///
+/// Formats follow the struct module notation used in the JSON configuration
+/// files, e.g. `"!Bhf"`. All values are encoded big-endian (network order).
+fn codes(encoding: &str) -> impl Iterator<Item = char> + '_ {
+    encoding
+        .chars()
+        .filter(|c| !matches!(c, '!' | '>' | '<' | '=' | '@' | ' '))
+}
+
+/// Returns the encoded size of a format string in bytes.
+pub fn encoded_size(encoding: &str) -> Result<usize, EncodingError> {
+    codes(encoding).map(code_size).sum()
+}
+
+/// Returns the number of values described by a format string.
+pub fn value_count(encoding: &str) -> usize {
+    codes(encoding).count()
+}
+
+/// Encodes values according to a format string.
+pub fn encode(encoding: &str, values: &[f64]) -> Result<Vec<u8>, EncodingError> {
+    if value_count(encoding) != values.len() {
+        return Err(EncodingError::LengthMismatch);
+    }
+    let mut data = Vec::with_capacity(encoded_size(encoding)?);
+    for (code, value) in codes(encoding).zip(values) {
+        let value = *value;
+        match code {
+            '?' => data.push((value != 0.0) as u8),
+            'b' => data.extend_from_slice(&(value as i8).to_be_bytes()),
+            'B' => data.push(value as u8),
+            'h' => data.extend_from_slice(&(value as i16).to_be_bytes()),
+            'H' => data.extend_from_slice(&(value as u16).to_be_bytes()),
+            'i' | 'l' => data.extend_from_slice(&(value as i32).to_be_bytes()),
+            'I' | 'L' => data.extend_from_slice(&(value as u32).to_be_bytes()),
+            'q' => data.extend_from_slice(&(value as i64).to_be_bytes()),
+            'Q' => data.extend_from_slice(&(value as u64).to_be_bytes()),
+            'f' => data.extend_from_slice(&(value as f32).to_be_bytes()),
+            'd' => data.extend_from_slice(&value.to_be_bytes()),
+            _ => return Err(EncodingError::UnknownFormat(code)),
+        }
+    }
+    Ok(data)
+}
+
+/// Decodes bytes according to a format string.
+pub fn decode(encoding: &str, data: &[u8]) -> Result<Vec<f64>, EncodingError> {
+    if encoded_size(encoding)? != data.len() {
+        return Err(EncodingError::LengthMismatch);
+    }
+    let mut values = Vec::with_capacity(value_count(encoding));
+    let mut offset = 0;
+    for code in codes(encoding) {
+        let size = code_size(code)?;
+        let bytes = &data[offset..offset + size];
+        offset += size;
+        let value = match code {
+            '?' => (bytes[0] != 0) as u8 as f64,
+            'b' => bytes[0] as i8 as f64,
+            'B' => bytes[0] as f64,
+            'h' => i16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+            'H' => u16::from_be_bytes([bytes[0], bytes[1]]) as f64,
+            'i' | 'l' => i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            'I' | 'L' => u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            'f' => f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            _ => {
+                let mut word = [0u8; 8];
+                word.copy_from_slice(bytes);
+                match code {
+                    'q' => i64::from_be_bytes(word) as f64,
+                    'Q' => u64::from_be_bytes(word) as f64,
+                    _ => f64::from_be_bytes(word),
+                }
+            }
+        };
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Identifier type that can be carried in a counted ID list.
+pub trait ListId: Copy {
+    /// Encoded size of one ID in bytes.
+    const SIZE: usize;
+
+    /// Appends the ID, most significant byte first.
+    fn write(self, data: &mut Vec<u8>);
+
+    /// Reads an ID from the first `SIZE` bytes.
+    fn read(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_list_id {
+    ($($id:ty),*) => {$(
+        impl ListId for $id {
+            const SIZE: usize = core::mem::size_of::<$id>();
+
+            fn write(self, data: &mut Vec<u8>) {
+                data.extend_from_slice(&self.to_be_bytes());
+            }
+
+            fn read(bytes: &[u8]) -> Self {
+                let mut word = [0u8; core::mem::size_of::<$id>()];
+                word.copy_from_slice(&bytes[..Self::SIZE]);
+                <$id>::from_be_bytes(word)
+            }
+        }
+    )*};
+}
+
+impl_list_id!(u8, u16, u32);
+
+/// Encodes a list of IDs as a one byte count followed by big-endian IDs.
+///
+/// At most 255 IDs fit into one list; further IDs are dropped.
+pub fn encode_id_list<T: ListId>(ids: &[T]) -> Vec<u8> {
+    let ids = &ids[..ids.len().min(u8::MAX as usize)];
+    let mut data = vec![ids.len() as u8];
+    for id in ids {
+        id.write(&mut data);
+    }
+    data
+}
+
+/// Decodes a list encoded by `encode_id_list`, returning it and the remaining data.
+pub fn decode_id_list<T: ListId>(data: &[u8]) -> Option<(Vec<T>, &[u8])> {
+    let (&count, data) = data.split_first()?;
+    let length = count as usize * T::SIZE;
+    if data.len() < length {
+        return None;
+    }
+    let ids = data[..length].chunks_exact(T::SIZE).map(T::read).collect();
+    Some((ids, &data[length..]))
+}
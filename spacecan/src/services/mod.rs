@@ -1,12 +1,40 @@
 #[cfg(feature = "std")]
 pub mod core;
 #[cfg(feature = "std")]
+pub mod encoding;
+#[cfg(feature = "std")]
 pub mod ST01_request_verification;
 #[cfg(feature = "std")]
+pub mod ST02_device_access;
+#[cfg(feature = "std")]
 pub mod ST03_housekeeping;
 #[cfg(feature = "std")]
+pub mod ST04_parameter_statistics;
+#[cfg(feature = "std")]
+pub mod ST05_event_reporting;
+#[cfg(feature = "std")]
+pub mod ST06_memory_management;
+#[cfg(feature = "std")]
 pub mod ST08_function_management;
 #[cfg(feature = "std")]
+pub mod ST09_time_management;
+#[cfg(feature = "std")]
+pub mod ST11_time_based_scheduling;
+#[cfg(feature = "std")]
+pub mod ST12_onboard_monitoring;
+#[cfg(feature = "std")]
+pub mod ST13_large_packet_transfer;
+#[cfg(feature = "std")]
+pub mod ST14_forwarding_control;
+#[cfg(feature = "std")]
+pub mod ST15_onboard_storage;
+#[cfg(feature = "std")]
 pub mod ST17_test;
 #[cfg(feature = "std")]
+pub mod ST18_onboard_operations;
+#[cfg(feature = "std")]
+pub mod ST19_event_action;
+#[cfg(feature = "std")]
 pub mod ST20_parameter_management;
+#[cfg(feature = "std")]
+pub mod ST23_file_management;
@@ -0,0 +1,52 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use super::{decode_record, encode_record, ParameterStore, ParameterValues, StoreError};
+
+/// Parameter store that keeps a single record in a file.
+///
+/// Saves go to a temporary file which is then renamed over the previous
+/// record, so an interrupted write never leaves a half-written record behind.
+pub struct FileParameterStore {
+    path: PathBuf,
+    sequence: u32,
+}
+
+impl FileParameterStore {
+    /// Creates a store backed by the file at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileParameterStore {
+            path: path.into(),
+            sequence: 0,
+        }
+    }
+
+    /// Returns the sequence number of the last record loaded or saved.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+}
+
+impl ParameterStore for FileParameterStore {
+    fn load(&mut self) -> Result<ParameterValues, StoreError> {
+        let record = match fs::read(&self.path) {
+            Ok(record) => record,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(ParameterValues::new()),
+            Err(_) => return Err(StoreError::Io),
+        };
+        let (sequence, values) = decode_record(&record)?;
+        self.sequence = sequence;
+        Ok(values)
+    }
+
+    fn save(&mut self, values: &ParameterValues) -> Result<(), StoreError> {
+        let sequence = self.sequence.wrapping_add(1);
+        let record = encode_record(sequence, values)?;
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, &record).map_err(|_| StoreError::Io)?;
+        fs::rename(&temp_path, &self.path).map_err(|_| StoreError::Io)?;
+        self.sequence = sequence;
+        Ok(())
+    }
+}
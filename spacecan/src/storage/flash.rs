@@ -0,0 +1,260 @@
+extern crate alloc;
+
+use alloc::vec;
+
+use super::{
+    decode_record, decode_record_header, encode_record, ParameterStore, ParameterValues, StoreError,
+    RECORD_HEADER_LENGTH,
+};
+
+/// Records are padded to this alignment so every record starts on a word boundary.
+const RECORD_ALIGNMENT: usize = 4;
+
+/// A set of equally sized, individually erasable flash sectors.
+///
+/// Offsets are relative to the start of the given sector. Erased bytes read as 0xFF.
+pub trait FlashSectors: Send {
+    /// Number of sectors reserved for the store.
+    fn sector_count(&self) -> usize;
+    /// Size of each sector in bytes.
+    fn sector_size(&self) -> usize;
+    /// Reads bytes from a sector into the buffer.
+    fn read(&self, sector: usize, offset: usize, buf: &mut [u8]);
+    /// Erases a whole sector.
+    fn erase(&mut self, sector: usize) -> Result<(), StoreError>;
+    /// Programs bytes into an erased region of a sector.
+    fn program(&mut self, sector: usize, offset: usize, data: &[u8]) -> Result<(), StoreError>;
+}
+
+/// Wear-levelled parameter store on raw flash sectors.
+///
+/// Every save appends a new record after the previous one. When a sector is
+/// full, the next sector is erased and writing continues there, so erase
+/// cycles are spread evenly across all sectors. On load the valid record with
+/// the highest sequence number wins; records with a bad CRC are skipped.
+pub struct FlashParameterStore<F: FlashSectors> {
+    flash: F,
+    scanned: bool,
+    sequence: u32,
+    sector: usize,
+    // Offset of the next free slot in the current sector, or None if the
+    // sector holds a damaged record and must not be appended to.
+    offset: Option<usize>,
+}
+
+impl<F: FlashSectors> FlashParameterStore<F> {
+    /// Creates a store on the given flash sectors.
+    ///
+    /// Fails with `StoreError::TooFewSectors` unless there are at least two
+    /// sectors: a save erases the next sector before programming it, and the
+    /// previous record must survive a power loss in between.
+    pub fn new(flash: F) -> Result<Self, StoreError> {
+        if flash.sector_count() < 2 {
+            return Err(StoreError::TooFewSectors);
+        }
+        Ok(FlashParameterStore {
+            flash,
+            scanned: false,
+            sequence: 0,
+            sector: 0,
+            offset: None,
+        })
+    }
+
+    /// Returns the sequence number of the last record loaded or saved.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Returns the sector holding the current record.
+    pub fn current_sector(&self) -> usize {
+        self.sector
+    }
+
+    /// Releases the underlying flash sectors.
+    pub fn free(self) -> F {
+        self.flash
+    }
+
+    /// Scans all sectors for the newest valid record and the next free slot.
+    fn scan(&mut self) -> ParameterValues {
+        let sector_size = self.flash.sector_size();
+        let mut newest: Option<(u32, ParameterValues)> = None;
+        self.sector = 0;
+        self.offset = None;
+
+        for sector in 0..self.flash.sector_count() {
+            let mut offset = 0;
+            let mut free_offset = None;
+            let mut holds_newest = false;
+            while offset + RECORD_HEADER_LENGTH <= sector_size {
+                let mut header = [0u8; RECORD_HEADER_LENGTH];
+                self.flash.read(sector, offset, &mut header);
+                let length = match decode_record_header(&header) {
+                    Ok(Some((_, length))) if offset + length <= sector_size => length,
+                    Ok(None) => {
+                        free_offset = Some(offset);
+                        break;
+                    }
+                    _ => break,
+                };
+                let mut record = vec![0u8; length];
+                self.flash.read(sector, offset, &mut record);
+                if let Ok((sequence, values)) = decode_record(&record) {
+                    let is_newer = match &newest {
+                        Some((newest_sequence, _)) => sequence_is_newer(sequence, *newest_sequence),
+                        None => true,
+                    };
+                    if is_newer {
+                        newest = Some((sequence, values));
+                        holds_newest = true;
+                    }
+                }
+                offset = align(offset + length);
+            }
+            if offset + RECORD_HEADER_LENGTH > sector_size {
+                free_offset = Some(sector_size);
+            }
+            if holds_newest {
+                self.sector = sector;
+                self.offset = free_offset;
+            }
+        }
+
+        self.scanned = true;
+        match newest {
+            Some((sequence, values)) => {
+                self.sequence = sequence;
+                values
+            }
+            None => {
+                self.sequence = 0;
+                // Nothing stored yet: force the first save to erase sector 0.
+                self.sector = self.flash.sector_count().saturating_sub(1);
+                ParameterValues::new()
+            }
+        }
+    }
+}
+
+impl<F: FlashSectors> ParameterStore for FlashParameterStore<F> {
+    fn load(&mut self) -> Result<ParameterValues, StoreError> {
+        Ok(self.scan())
+    }
+
+    fn save(&mut self, values: &ParameterValues) -> Result<(), StoreError> {
+        if !self.scanned {
+            self.scan();
+        }
+        let sequence = self.sequence.wrapping_add(1);
+        let record = encode_record(sequence, values)?;
+        let sector_size = self.flash.sector_size();
+        if record.len() > sector_size {
+            return Err(StoreError::TooLarge);
+        }
+
+        let offset = match self.offset {
+            Some(offset) if offset + record.len() <= sector_size => offset,
+            _ => {
+                self.sector = (self.sector + 1) % self.flash.sector_count();
+                self.flash.erase(self.sector)?;
+                0
+            }
+        };
+        // Leave the write position damaged until the program succeeds, so a
+        // failed write forces the next save onto a freshly erased sector.
+        self.offset = None;
+        self.flash.program(self.sector, offset, &record)?;
+        self.offset = Some(align(offset + record.len()));
+        self.sequence = sequence;
+        Ok(())
+    }
+}
+
+/// Rounds an offset up to the record alignment.
+fn align(offset: usize) -> usize {
+    offset.div_ceil(RECORD_ALIGNMENT) * RECORD_ALIGNMENT
+}
+
+/// Compares sequence numbers, allowing for wrap-around.
+fn sequence_is_newer(sequence: u32, other: u32) -> bool {
+    (sequence.wrapping_sub(other) as i32) > 0
+}
+
+/// Flash sectors of the STM32F7 embedded flash.
+///
+/// The sectors must be contiguous and of equal size, for example sectors
+/// 1 to 3 (32 KiB each) of an STM32F767 in single-bank mode.
+pub struct Stm32FlashSectors {
+    flash: stm32f7xx_hal::flash::Flash,
+    first_sector: u8,
+    sector_count: usize,
+    start_offset: usize,
+    sector_size: usize,
+}
+
+/// Base address of the embedded flash on the AXIM interface.
+const STM32_FLASH_BASE: usize = 0x0800_0000;
+
+impl Stm32FlashSectors {
+    /// Creates the sector set.
+    ///
+    /// `start_offset` is the offset of `first_sector` from the start of flash.
+    pub fn new(
+        flash: stm32f7xx_hal::flash::Flash,
+        first_sector: u8,
+        sector_count: usize,
+        start_offset: usize,
+        sector_size: usize,
+    ) -> Self {
+        Stm32FlashSectors {
+            flash,
+            first_sector,
+            sector_count,
+            start_offset,
+            sector_size,
+        }
+    }
+
+    /// Releases the flash peripheral.
+    pub fn free(self) -> stm32f7xx_hal::flash::Flash {
+        self.flash
+    }
+
+    fn offset_of(&self, sector: usize, offset: usize) -> usize {
+        self.start_offset + sector * self.sector_size + offset
+    }
+}
+
+impl FlashSectors for Stm32FlashSectors {
+    fn sector_count(&self) -> usize {
+        self.sector_count
+    }
+
+    fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    fn read(&self, sector: usize, offset: usize, buf: &mut [u8]) {
+        let address = STM32_FLASH_BASE + self.offset_of(sector, offset);
+        for (i, byte) in buf.iter_mut().enumerate() {
+            // Flash is memory mapped and readable at any time.
+            *byte = unsafe { core::ptr::read_volatile((address + i) as *const u8) };
+        }
+    }
+
+    fn erase(&mut self, sector: usize) -> Result<(), StoreError> {
+        self.flash.unlock();
+        let result = self.flash.blocking_erase_sector(self.first_sector + sector as u8);
+        self.flash.lock();
+        result.map_err(|_| StoreError::Flash)
+    }
+
+    fn program(&mut self, sector: usize, offset: usize, data: &[u8]) -> Result<(), StoreError> {
+        let start = self.offset_of(sector, offset);
+        self.flash.unlock();
+        let result = self.flash.blocking_program(start, data);
+        self.flash.lock();
+        result.map_err(|_| StoreError::Flash)
+    }
+}
@@ -0,0 +1,162 @@
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::primitives::crc::crc16;
+
+#[cfg(feature = "std")]
+pub mod file;
+pub mod flash;
+#[cfg(feature = "std")]
+pub mod packet_file;
+pub mod packets;
+
+/// Marker at the start of every persisted parameter record.
+const RECORD_MAGIC: u16 = 0x5350;
+/// Current layout version of persisted parameter records.
+pub const LAYOUT_VERSION: u8 = 1;
+/// Size of the record header: magic, version, reserved, sequence and entry count.
+pub const RECORD_HEADER_LENGTH: usize = 10;
+/// Size of a single entry: node ID, parameter ID and value.
+const RECORD_ENTRY_LENGTH: usize = 16;
+/// Size of the trailing CRC.
+const RECORD_CRC_LENGTH: usize = 2;
+
+/// Parameter values keyed by (node ID, parameter ID), as held by the ST20 parameter pool.
+pub type ParameterValues = BTreeMap<(u32, u32), f64>;
+
+/// Errors reported by parameter and packet stores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreError {
+    /// The backing medium could not be read or written.
+    Io,
+    /// A record failed its CRC check or is truncated.
+    Corrupted,
+    /// A record was written with a different layout version.
+    LayoutMismatch(u8),
+    /// The record does not fit into the backing medium.
+    TooLarge,
+    /// The flash controller reported an erase or programming error.
+    Flash,
+    /// A packet store holds its maximum number of packets.
+    Full,
+    /// A flash store needs at least two sectors to keep a valid record while erasing.
+    TooFewSectors,
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io => write!(f, "Store I/O error"),
+            StoreError::Corrupted => write!(f, "Parameter record is corrupted"),
+            StoreError::LayoutMismatch(version) => write!(f, "Unsupported parameter record layout: {}", version),
+            StoreError::TooLarge => write!(f, "Parameter record exceeds storage capacity"),
+            StoreError::Flash => write!(f, "Flash erase or program failed"),
+            StoreError::Full => write!(f, "Packet store is full"),
+            StoreError::TooFewSectors => write!(f, "Flash store needs at least two sectors"),
+        }
+    }
+}
+
+/// When parameter values are read from and written to the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistencePolicy {
+    /// Values are only loaded and saved on explicit request.
+    Manual,
+    /// Values are restored when the store is attached.
+    LoadOnStart,
+    /// Values are saved whenever a parameter changes.
+    SaveOnChange,
+    /// Values are restored on attach and saved on every change.
+    LoadAndSave,
+}
+
+impl PersistencePolicy {
+    /// Returns true if values should be restored when the store is attached.
+    pub fn loads_on_start(&self) -> bool {
+        matches!(self, PersistencePolicy::LoadOnStart | PersistencePolicy::LoadAndSave)
+    }
+
+    /// Returns true if values should be saved after every change.
+    pub fn saves_on_change(&self) -> bool {
+        matches!(self, PersistencePolicy::SaveOnChange | PersistencePolicy::LoadAndSave)
+    }
+}
+
+/// Backend that persists parameter values across reboots.
+pub trait ParameterStore: Send {
+    /// Loads the most recently saved values. Returns an empty set if nothing was saved yet.
+    fn load(&mut self) -> Result<ParameterValues, StoreError>;
+    /// Saves the given values, replacing any previously saved set.
+    fn save(&mut self, values: &ParameterValues) -> Result<(), StoreError>;
+}
+
+/// Returns the encoded length of a record holding the given number of entries.
+pub fn record_length(count: usize) -> usize {
+    RECORD_HEADER_LENGTH + count * RECORD_ENTRY_LENGTH + RECORD_CRC_LENGTH
+}
+
+/// Encodes parameter values into a CRC-protected record.
+pub fn encode_record(sequence: u32, values: &ParameterValues) -> Result<Vec<u8>, StoreError> {
+    if values.len() > u16::MAX as usize {
+        return Err(StoreError::TooLarge);
+    }
+    let mut record = Vec::with_capacity(record_length(values.len()));
+    record.extend_from_slice(&RECORD_MAGIC.to_be_bytes());
+    record.push(LAYOUT_VERSION);
+    record.push(0);
+    record.extend_from_slice(&sequence.to_be_bytes());
+    record.extend_from_slice(&(values.len() as u16).to_be_bytes());
+    for ((node_id, parameter_id), value) in values {
+        record.extend_from_slice(&node_id.to_be_bytes());
+        record.extend_from_slice(&parameter_id.to_be_bytes());
+        record.extend_from_slice(&value.to_be_bytes());
+    }
+    let crc = crc16(&record);
+    record.extend_from_slice(&crc.to_be_bytes());
+    Ok(record)
+}
+
+/// Parses a record header and returns the sequence number and total record length.
+///
+/// Returns `Ok(None)` if the header is erased flash (all 0xFF).
+pub fn decode_record_header(header: &[u8]) -> Result<Option<(u32, usize)>, StoreError> {
+    if header.len() < RECORD_HEADER_LENGTH {
+        return Err(StoreError::Corrupted);
+    }
+    if header[..RECORD_HEADER_LENGTH].iter().all(|byte| *byte == 0xFF) {
+        return Ok(None);
+    }
+    if u16::from_be_bytes([header[0], header[1]]) != RECORD_MAGIC {
+        return Err(StoreError::Corrupted);
+    }
+    if header[2] != LAYOUT_VERSION {
+        return Err(StoreError::LayoutMismatch(header[2]));
+    }
+    let sequence = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    let count = u16::from_be_bytes([header[8], header[9]]) as usize;
+    Ok(Some((sequence, record_length(count))))
+}
+
+/// Decodes a record and returns its sequence number and parameter values.
+pub fn decode_record(record: &[u8]) -> Result<(u32, ParameterValues), StoreError> {
+    let (sequence, length) = decode_record_header(record)?.ok_or(StoreError::Corrupted)?;
+    if record.len() < length {
+        return Err(StoreError::Corrupted);
+    }
+    let (body, crc) = record[..length].split_at(length - RECORD_CRC_LENGTH);
+    if crc16(body) != u16::from_be_bytes([crc[0], crc[1]]) {
+        return Err(StoreError::Corrupted);
+    }
+    let mut values = ParameterValues::new();
+    for entry in body[RECORD_HEADER_LENGTH..].chunks_exact(RECORD_ENTRY_LENGTH) {
+        let node_id = u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]);
+        let parameter_id = u32::from_be_bytes([entry[4], entry[5], entry[6], entry[7]]);
+        let mut value = [0u8; 8];
+        value.copy_from_slice(&entry[8..16]);
+        values.insert((node_id, parameter_id), f64::from_be_bytes(value));
+    }
+    Ok((sequence, values))
+}
@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::vec;
+use std::vec::Vec;
+
+use super::packets::{decode_packet_record, encode_packet_record, PacketStore, StoredPacket};
+use super::StoreError;
+
+/// File entry holding a packet record.
+const ENTRY_PACKET: u8 = 0;
+/// File entry marking the oldest packet as removed.
+const ENTRY_DROP: u8 = 1;
+
+/// Packet store that appends packet records to a file.
+///
+/// The packets are mirrored in memory. Removing the oldest packet appends a
+/// one-byte drop marker; the file is compacted once as many packets were
+/// dropped as the store can hold. A torn record at the end of the file,
+/// left by an interrupted write, and packets beyond the capacity the store
+/// is opened with are discarded when the store is opened.
+pub struct FilePacketStore {
+    path: PathBuf,
+    capacity: usize,
+    packets: VecDeque<StoredPacket>,
+    dropped: usize,
+}
+
+impl FilePacketStore {
+    /// Opens the store backed by the file at the given path, loading the packets it holds.
+    pub fn open(path: impl Into<PathBuf>, capacity: usize) -> Result<Self, StoreError> {
+        let path = path.into();
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(_) => return Err(StoreError::Io),
+        };
+        let mut packets = VecDeque::new();
+        let mut dropped = 0;
+        let mut offset = 0;
+        while offset < contents.len() {
+            if contents[offset] == ENTRY_DROP {
+                packets.pop_front();
+                dropped += 1;
+                offset += 1;
+                continue;
+            }
+            let Ok((packet, length)) = decode_packet_record(&contents[offset + 1..]) else {
+                break;
+            };
+            packets.push_back(packet);
+            offset += 1 + length;
+        }
+        let trimmed = packets.len() > capacity;
+        while packets.len() > capacity {
+            packets.pop_front();
+        }
+
+        let mut store = FilePacketStore {
+            path,
+            capacity,
+            packets,
+            dropped,
+        };
+        if offset != contents.len() || dropped >= capacity || trimmed {
+            store.compact()?;
+        }
+        Ok(store)
+    }
+
+    /// Rewrites the file with the packets currently held.
+    fn compact(&mut self) -> Result<(), StoreError> {
+        let mut contents = Vec::new();
+        for packet in &self.packets {
+            contents.push(ENTRY_PACKET);
+            contents.extend(encode_packet_record(packet)?);
+        }
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, &contents).map_err(|_| StoreError::Io)?;
+        fs::rename(&temp_path, &self.path).map_err(|_| StoreError::Io)?;
+        self.dropped = 0;
+        Ok(())
+    }
+
+    fn append(&self, entry: &[u8]) -> Result<(), StoreError> {
+        let mut file: File = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|_| StoreError::Io)?;
+        file.write_all(entry).map_err(|_| StoreError::Io)
+    }
+}
+
+impl PacketStore for FilePacketStore {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    fn push(&mut self, packet: StoredPacket) -> Result<(), StoreError> {
+        if self.packets.len() >= self.capacity {
+            return Err(StoreError::Full);
+        }
+        let mut entry = vec![ENTRY_PACKET];
+        entry.extend(encode_packet_record(&packet)?);
+        self.append(&entry)?;
+        self.packets.push_back(packet);
+        Ok(())
+    }
+
+    fn pop_oldest(&mut self) -> Result<Option<StoredPacket>, StoreError> {
+        let Some(packet) = self.packets.pop_front() else {
+            return Ok(None);
+        };
+        self.dropped += 1;
+        if self.dropped >= self.capacity {
+            self.compact()?;
+        } else {
+            self.append(&[ENTRY_DROP])?;
+        }
+        Ok(Some(packet))
+    }
+
+    fn packets(&self) -> Result<Vec<StoredPacket>, StoreError> {
+        Ok(self.packets.iter().cloned().collect())
+    }
+
+    fn clear(&mut self) -> Result<(), StoreError> {
+        self.packets.clear();
+        self.compact()
+    }
+}
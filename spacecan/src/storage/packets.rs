@@ -0,0 +1,140 @@
+extern crate alloc;
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use super::StoreError;
+use crate::primitives::crc::crc16;
+use crate::primitives::time::CucTime;
+
+/// Size of a packet record header: sequence, CUC time and packet length.
+const PACKET_HEADER_LENGTH: usize = 13;
+/// Size of the trailing CRC of a packet record.
+const PACKET_CRC_LENGTH: usize = 2;
+
+/// Telemetry packet kept in a packet store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredPacket {
+    /// Sequence number assigned by the store's owner, increasing per store.
+    pub sequence: u32,
+    /// Onboard time at which the packet was stored.
+    pub time: CucTime,
+    /// Packet data, starting with service and subtype.
+    pub data: Vec<u8>,
+}
+
+impl StoredPacket {
+    /// Creates a new stored packet.
+    pub fn new(sequence: u32, time: CucTime, data: Vec<u8>) -> Self {
+        StoredPacket { sequence, time, data }
+    }
+}
+
+/// Backend holding the packets of one packet store, oldest first.
+///
+/// Stores are bounded; what happens when a full store receives another
+/// packet is decided by the owner, who either drops the oldest packet or
+/// rejects the new one.
+pub trait PacketStore: Send {
+    /// Returns the maximum number of packets.
+    fn capacity(&self) -> usize;
+    /// Returns the number of stored packets.
+    fn len(&self) -> usize;
+    /// Returns true if no packet is stored.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Appends a packet. Fails with `StoreError::Full` if the store is full.
+    fn push(&mut self, packet: StoredPacket) -> Result<(), StoreError>;
+    /// Removes the oldest packet.
+    fn pop_oldest(&mut self) -> Result<Option<StoredPacket>, StoreError>;
+    /// Returns all stored packets, oldest first.
+    fn packets(&self) -> Result<Vec<StoredPacket>, StoreError>;
+    /// Removes all packets.
+    fn clear(&mut self) -> Result<(), StoreError>;
+}
+
+/// Packet store held in RAM.
+#[derive(Debug, Clone)]
+pub struct MemoryPacketStore {
+    packets: VecDeque<StoredPacket>,
+    capacity: usize,
+}
+
+impl MemoryPacketStore {
+    /// Creates an empty store holding at most `capacity` packets.
+    pub fn new(capacity: usize) -> Self {
+        MemoryPacketStore {
+            packets: VecDeque::new(),
+            capacity,
+        }
+    }
+}
+
+impl PacketStore for MemoryPacketStore {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    fn push(&mut self, packet: StoredPacket) -> Result<(), StoreError> {
+        if self.packets.len() >= self.capacity {
+            return Err(StoreError::Full);
+        }
+        self.packets.push_back(packet);
+        Ok(())
+    }
+
+    fn pop_oldest(&mut self) -> Result<Option<StoredPacket>, StoreError> {
+        Ok(self.packets.pop_front())
+    }
+
+    fn packets(&self) -> Result<Vec<StoredPacket>, StoreError> {
+        Ok(self.packets.iter().cloned().collect())
+    }
+
+    fn clear(&mut self) -> Result<(), StoreError> {
+        self.packets.clear();
+        Ok(())
+    }
+}
+
+/// Encodes a packet into a CRC-protected record.
+pub fn encode_packet_record(packet: &StoredPacket) -> Result<Vec<u8>, StoreError> {
+    if packet.data.len() > u16::MAX as usize {
+        return Err(StoreError::TooLarge);
+    }
+    let mut record = Vec::with_capacity(PACKET_HEADER_LENGTH + packet.data.len() + PACKET_CRC_LENGTH);
+    record.extend_from_slice(&packet.sequence.to_be_bytes());
+    record.extend_from_slice(&packet.time.to_bytes());
+    record.extend_from_slice(&(packet.data.len() as u16).to_be_bytes());
+    record.extend_from_slice(&packet.data);
+    let crc = crc16(&record);
+    record.extend_from_slice(&crc.to_be_bytes());
+    Ok(record)
+}
+
+/// Decodes the packet record at the start of `data`, returning it and its encoded length.
+pub fn decode_packet_record(data: &[u8]) -> Result<(StoredPacket, usize), StoreError> {
+    if data.len() < PACKET_HEADER_LENGTH {
+        return Err(StoreError::Corrupted);
+    }
+    let length = u16::from_be_bytes([data[11], data[12]]) as usize;
+    let record_length = PACKET_HEADER_LENGTH + length + PACKET_CRC_LENGTH;
+    if data.len() < record_length {
+        return Err(StoreError::Corrupted);
+    }
+    let (body, crc) = data[..record_length].split_at(record_length - PACKET_CRC_LENGTH);
+    if crc16(body) != u16::from_be_bytes([crc[0], crc[1]]) {
+        return Err(StoreError::Corrupted);
+    }
+    let packet = StoredPacket {
+        sequence: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+        time: CucTime::from_bytes(&data[4..11]).ok_or(StoreError::Corrupted)?,
+        data: body[PACKET_HEADER_LENGTH..].to_vec(),
+    };
+    Ok((packet, record_length))
+}
@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod tests {
+    use crate::asynchronous::bus::{AsyncBus, VirtualBus};
+    use crate::asynchronous::network::{AsyncNetwork, BusSelection};
+    use crate::asynchronous::node::{AsyncController, AsyncResponder, ResponderEvent};
+    use crate::primitives::can_frame::CanFrame;
+    use crate::primitives::roster::{NodeRoster, SwitchoverStatus};
+    use crate::primitives::scheduler::{Duration as Ticks, Instant, SimulatedClock};
+    use crate::primitives::sync::{SyncConsumer, SyncScheduler};
+    use crate::primitives::time::{CdsTime, CucTime, TimeFrame};
+    use std::boxed::Box;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+    use std::vec;
+    use std::vec::Vec;
+    use tokio_stream::StreamExt;
+
+    fn nodes() -> (AsyncController<VirtualBus>, AsyncResponder<VirtualBus>) {
+        let (bus_a, bus_b) = (VirtualBus::new(), VirtualBus::new());
+        let controller = AsyncController::new(AsyncNetwork::new(bus_a.clone(), bus_b.clone()));
+        let responder = AsyncResponder::new(AsyncNetwork::new(bus_a, bus_b), 5);
+        (controller, responder)
+    }
+
+    #[tokio::test]
+    async fn test_packets_and_events() {
+        let (controller, responder) = nodes();
+        let mut events = responder.events();
+        let mut packets = controller.packets();
+
+        let telecommand: Vec<u8> = (0..20).collect();
+        controller.send_packet(5, telecommand.clone()).await.unwrap();
+        controller.send_packet(6, vec![1, 2]).await.unwrap();
+        controller.send_sync().await.unwrap();
+        assert_eq!(events.next().await, Some(ResponderEvent::Telecommand(telecommand)));
+        assert_eq!(events.next().await, Some(ResponderEvent::Sync(None)));
+
+        let scet = CucTime::new(0x0102_0304, 0x0A0B0C);
+        let utc = CdsTime::new(0x1234, 3_600_000, 250);
+        controller.send_scet(&scet).await.unwrap();
+        controller.send_utc(&utc).await.unwrap();
+        assert_eq!(events.next().await, Some(ResponderEvent::Time(TimeFrame::Scet(scet))));
+        assert_eq!(events.next().await, Some(ResponderEvent::Time(TimeFrame::Utc(utc))));
+
+        responder.send_packet(vec![3, 1, 7]).await.unwrap();
+        assert_eq!(packets.next().await, Some((5, vec![3, 1, 7])));
+
+        let reports = Arc::new(AtomicU32::new(0));
+        {
+            let reports = reports.clone();
+            responder.every(Duration::from_millis(10), move || {
+                reports.fetch_add(1, Ordering::Relaxed);
+                async {}
+            });
+        }
+        controller.start_heartbeat(Duration::from_millis(10));
+        assert_eq!(events.next().await, Some(ResponderEvent::Heartbeat));
+
+        // Shutdown stops the periodic tasks and ends the streams.
+        controller.shutdown().await;
+        responder.shutdown().await;
+        let runs = reports.load(Ordering::Relaxed);
+        assert!(runs > 0);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(reports.load(Ordering::Relaxed), runs);
+        assert_eq!(packets.next().await, None);
+        while events.next().await.is_some() {}
+    }
+
+    #[tokio::test]
+    async fn test_sync_scheduler_runs_on_received_syncs() {
+        let (controller, responder) = nodes();
+        let clock = Arc::new(SimulatedClock::new());
+        let mut sync_scheduler = SyncScheduler::with_clock(Duration::from_millis(100), clock.clone());
+        let (sender, mut counts) = tokio::sync::mpsc::unbounded_channel();
+        sync_scheduler.add_action(1, Box::new(move |count| sender.send(count).unwrap()));
+        let sync_scheduler = responder.start_sync_scheduler(sync_scheduler);
+
+        for count in 0..3 {
+            controller.send_sync().await.unwrap();
+            assert_eq!(counts.recv().await, Some(count));
+            clock.advance(Ticks::millis(100));
+        }
+        // The sync after this one is lost.
+        clock.advance(Ticks::millis(100));
+        controller.send_sync().await.unwrap();
+        assert_eq!(counts.recv().await, Some(3));
+
+        let sync_scheduler = sync_scheduler.lock().unwrap();
+        assert_eq!(sync_scheduler.count(), 4);
+        assert_eq!(sync_scheduler.missed(), 1);
+        assert_eq!(sync_scheduler.max_jitter(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_sync_counter_reaches_consumer() {
+        let (mut controller, responder) = nodes();
+        controller.set_sync_counter(Some(3));
+        let mut sync_consumer = SyncConsumer::with_clock(Duration::from_millis(250), Arc::new(SimulatedClock::new()));
+        sync_consumer.set_counter_overflow(Some(3));
+        let sync_consumer = responder.start_sync_consumer(sync_consumer);
+        let mut events = responder.events();
+
+        for counter in [1, 2, 3, 1] {
+            controller.send_sync().await.unwrap();
+            assert_eq!(controller.sync_counter(), Some(counter));
+            assert_eq!(events.next().await, Some(ResponderEvent::Sync(Some(counter))));
+            wait_for(|| sync_consumer.counter() == Some(counter)).await;
+        }
+        assert_eq!(sync_consumer.missed(), 0);
+
+        // A sync with counter 2 was lost.
+        controller.network().send(CanFrame::new(0x080, Some(vec![3])).unwrap()).await.unwrap();
+        assert_eq!(events.next().await, Some(ResponderEvent::Sync(Some(3))));
+        wait_for(|| sync_consumer.counter() == Some(3)).await;
+        assert_eq!(sync_consumer.missed(), 1);
+
+        // Periodic syncs carry the counter too.
+        controller.set_sync_counter(Some(8));
+        controller.start_sync(Duration::from_millis(10));
+        assert_eq!(events.next().await, Some(ResponderEvent::Sync(Some(1))));
+        controller.shutdown().await;
+        responder.shutdown().await;
+    }
+
+    /// Waits until the tasks of the nodes met a condition.
+    async fn wait_for(condition: impl Fn() -> bool) {
+        let waiting = async {
+            while !condition() {
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_controller_uses_injected_clock() {
+        let (bus_a, bus_b) = (VirtualBus::new(), VirtualBus::new());
+        let clock = Arc::new(SimulatedClock::new());
+        clock.advance(Ticks::secs(5));
+        let controller = AsyncController::with_clock(AsyncNetwork::new(bus_a.clone(), bus_b.clone()), clock.clone());
+        controller.start_roster();
+        let responder = AsyncResponder::new(AsyncNetwork::new(bus_a, bus_b), 5);
+
+        responder.send_packet(vec![3, 25]).await.unwrap();
+        wait_for(|| controller.nodes().iter().any(|node| node.last_telemetry.is_some())).await;
+        assert_eq!(controller.nodes()[0].last_telemetry, Some(Instant::from_ticks(5_000_000)));
+        assert_eq!(controller.roster().entry(5).unwrap().last_seen, Instant::from_ticks(5_000_000));
+        controller.shutdown().await;
+        responder.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_responder_switches_bus_without_heartbeat() {
+        let (bus_a, bus_b) = (VirtualBus::new(), VirtualBus::new());
+        let mut responder = AsyncResponder::new(AsyncNetwork::new(bus_a.clone(), bus_b.clone()), 5);
+        responder.set_heartbeat_timeout(Some(Duration::from_millis(50)));
+        let mut events = responder.events();
+
+        // The controller only sends on bus B, so the responder ignores it until switching.
+        let heartbeat = CanFrame::new(0x700, None).unwrap();
+        bus_b.send(heartbeat.clone()).await.unwrap();
+        assert_eq!(events.next().await, Some(ResponderEvent::BusSwitched(BusSelection::B)));
+        assert_eq!(responder.network().selected_bus(), BusSelection::B);
+        bus_a.send(heartbeat.clone()).await.unwrap();
+        bus_b.send(heartbeat).await.unwrap();
+        assert_eq!(events.next().await, Some(ResponderEvent::Heartbeat));
+        responder.shutdown().await;
+        assert_eq!(events.next().await, None);
+    }
+
+    #[test]
+    fn test_roster_switchover_deadline() {
+        let mut roster = NodeRoster::new();
+        roster.set_expected_nodes(vec![2]);
+        roster.record(1, BusSelection::A, Instant::from_ticks(0));
+        roster.start_switchover(BusSelection::B, Instant::from_ticks(10), Ticks::millis(100));
+        assert_eq!(roster.switchover().unwrap().pending(), [1, 2]);
+
+        roster.record(1, BusSelection::B, Instant::from_ticks(20));
+        assert_eq!(roster.nodes_on(BusSelection::B), [1]);
+        assert_eq!(roster.switchover_status(Instant::from_ticks(100_010)), Some(SwitchoverStatus::InProgress));
+        assert_eq!(roster.switchover_status(Instant::from_ticks(100_011)), Some(SwitchoverStatus::Failed));
+        roster.record(2, BusSelection::B, Instant::from_ticks(100_020));
+        assert_eq!(roster.switchover_status(Instant::from_ticks(100_020)), Some(SwitchoverStatus::Completed));
+    }
+
+    #[tokio::test]
+    async fn test_commanded_switchover() {
+        let (bus_a, bus_b) = (VirtualBus::new(), VirtualBus::new());
+        let controller = AsyncController::new(AsyncNetwork::new(bus_a.clone(), bus_b.clone()));
+        controller.start_roster();
+        controller.start_heartbeat(Duration::from_millis(10));
+
+        // Nodes 1 and 2 follow the heartbeats; node 3 stays on its bus.
+        let mut responders = Vec::new();
+        let mut events = Vec::new();
+        for node_id in 1..=3 {
+            let mut responder = AsyncResponder::new(AsyncNetwork::new(bus_a.clone(), bus_b.clone()), node_id);
+            if node_id != 3 {
+                responder.set_heartbeat_timeout(Some(Duration::from_millis(40)));
+            }
+            events.push(responder.events());
+            let responder = Arc::new(responder);
+            let sender = responder.clone();
+            responder.every(Duration::from_millis(10), move || {
+                let sender = sender.clone();
+                async move {
+                    let _ = sender.send_packet(vec![3, 25]).await;
+                }
+            });
+            responders.push(responder);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(controller.roster().nodes_on(BusSelection::A), [1, 2, 3]);
+
+        assert_eq!(controller.switch_bus(Duration::from_millis(300)), BusSelection::B);
+        assert_eq!(controller.verify_switchover().await, Err(vec![3]));
+        let roster = controller.roster();
+        assert_eq!(roster.nodes_on(BusSelection::B), [1, 2]);
+        assert_eq!(roster.entry(3).unwrap().bus, BusSelection::A);
+
+        controller.shutdown().await;
+        for responder in responders {
+            responder.shutdown().await;
+        }
+    }
+}
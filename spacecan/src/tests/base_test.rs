@@ -1,8 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::can_frame::CanFrame;
-    use crate::base::{Bus, BusImpl};
+    use crate::primitives::can_frame::CanFrame;
+    use crate::transport::base::{Bus, BusImpl};
+    use std::vec;
     
     #[test]
     fn test_bus_send_and_receive() {
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::primitives::can_frame::CanFrame;
+    use std::vec;
     
     #[test]
     fn test_can_frame_creation_valid() {
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::ST02_device_access::{
+        DeviceAccessServiceController, DeviceAccessServiceResponder, DeviceError, MockDevice, Packet, Parent,
+        RegisterValue,
+    };
+    use std::boxed::Box;
+    use std::sync::{Arc, Mutex};
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, _node_id: u32) {
+            self.sent.lock().unwrap().push(packet.data);
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn take_sent(parent: &RecordingParent) -> Vec<Vec<u8>> {
+        parent.sent.lock().unwrap().drain(..).collect()
+    }
+
+    #[test]
+    fn test_device_errors() {
+        let responder = DeviceAccessServiceResponder::new(parent());
+        let device = MockDevice::new().with_register(0x10, 1).with_read_only_register(0x20, 0xAB);
+        responder.register_device(1, Box::new(device.clone()));
+
+        assert_eq!(responder.load_register(1, 0x10, 5), Err(DeviceError::Off));
+        responder.set_power(1, true).unwrap();
+        assert_eq!(responder.load_register(1, 0x20, 5), Err(DeviceError::ReadOnly(0x20)));
+        assert_eq!(responder.load_register(1, 0x30, 5), Err(DeviceError::UnknownRegister(0x30)));
+        assert_eq!(responder.dump_register(2, 0x10), Err(DeviceError::UnknownDevice(2)));
+        device.set_failing(true);
+        assert_eq!(responder.dump_register(1, 0x10), Err(DeviceError::Bus));
+        device.set_failing(false);
+        assert_eq!(responder.dump_register(1, 0x20), Ok(0xAB));
+    }
+
+    #[test]
+    fn test_commanding_over_the_bus() {
+        let parent = parent();
+        let controller = DeviceAccessServiceController::new(parent.clone());
+        let responder = DeviceAccessServiceResponder::new(parent.clone());
+        let sensor = MockDevice::new().with_register(0x01, 0).with_register(0x02, 7);
+        let heater = MockDevice::new().with_register(0x01, 0);
+        responder.register_device(1, Box::new(sensor.clone()));
+        responder.register_device(2, Box::new(heater.clone()));
+
+        controller.send_on_off(4, &[(1, true), (2, true)]);
+        controller.send_register_load(4, &[RegisterValue::new(1, 0x01, 0x1234), RegisterValue::new(2, 0x01, 3)]);
+        controller.send_on_off(4, &[(2, false)]);
+        controller.send_register_dump_request(4, &[(1, 0x01), (1, 0x02), (2, 0x01)]);
+        for request in take_sent(&parent) {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        assert!(sensor.is_on());
+        assert!(!heater.is_on());
+        assert_eq!(heater.register(0x01), Some(3));
+
+        let report = take_sent(&parent).remove(0);
+        controller.process(report[0], report[1], report[2..].to_vec(), 4);
+        assert_eq!(controller.register(4, 1, 0x01), Some(0x1234));
+        assert_eq!(controller.register(4, 1, 0x02), Some(7));
+        // The heater is off, so its register is left out of the report.
+        assert_eq!(controller.register(4, 2, 0x01), None);
+    }
+}
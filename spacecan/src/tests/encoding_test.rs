@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::encoding::{decode_id_list, encode_id_list};
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_id_lists() {
+        let data = encode_id_list(&[0x0102u16, 0x0304]);
+        assert_eq!(data, [2, 1, 2, 3, 4]);
+        assert_eq!(decode_id_list::<u16>(&data), Some((vec![0x0102, 0x0304], &[][..])));
+
+        let mut data = encode_id_list(&[7u8]);
+        data.extend(encode_id_list(&[0x0A0B_0C0Du32]));
+        let (store_ids, rest) = decode_id_list::<u8>(&data).unwrap();
+        assert_eq!(store_ids, [7]);
+        assert_eq!(decode_id_list::<u32>(rest), Some((vec![0x0A0B_0C0D], &[][..])));
+
+        // Too few bytes for the count, and at most 255 IDs.
+        assert_eq!(decode_id_list::<u16>(&[2, 0, 1, 0]), None);
+        assert_eq!(decode_id_list::<u16>(&[]), None);
+        let ids: Vec<u16> = (0..300).collect();
+        assert_eq!(encode_id_list(&ids)[0], 255);
+    }
+}
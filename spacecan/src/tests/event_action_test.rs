@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::ST05_event_reporting::{self, EventDefinition, EventReportingServiceResponder, Severity};
+    use crate::services::ST19_event_action::{
+        EventActionDefinition, EventActionServiceController, EventActionServiceResponder, Packet, Parent,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<(Vec<u8>, u32)>>,
+        dispatched: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+
+        fn dispatch(&self, data: Vec<u8>, _node_id: u32) {
+            self.dispatched.lock().unwrap().push(data);
+        }
+    }
+
+    impl ST05_event_reporting::Parent for RecordingParent {
+        fn send(&self, packet: ST05_event_reporting::Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+            dispatched: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn forward(parent: &RecordingParent, responder: &EventActionServiceResponder) {
+        let requests: Vec<Vec<u8>> = parent.sent.lock().unwrap().drain(..).map(|(data, _)| data).collect();
+        for request in requests {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+    }
+
+    #[test]
+    fn test_event_triggers_stored_telecommand() {
+        let parent = parent();
+        let responder = Arc::new(EventActionServiceResponder::new(parent.clone()));
+        let mut event_reporting = EventReportingServiceResponder::new(parent.clone());
+        event_reporting.add_event_definition(EventDefinition::new(
+            0x0102,
+            "over_temperature".into(),
+            Severity::High,
+            "".into(),
+        ));
+        let event_action = responder.clone();
+        event_reporting.set_event_listener(Arc::new(move |event_id, _| {
+            event_action.received_event(event_id);
+        }));
+
+        let controller = EventActionServiceController::new(parent.clone());
+        controller.send_add_definitions(4, &[EventActionDefinition::new(0x0102, vec![8, 1, 3])]);
+        forward(&parent, &responder);
+
+        // Definitions are added disabled.
+        event_reporting.raise_event_raw(0x0102, &[]);
+        assert!(parent.dispatched.lock().unwrap().is_empty());
+
+        controller.send_enable_definitions(4, &[0x0102]);
+        forward(&parent, &responder);
+        parent.sent.lock().unwrap().clear();
+        event_reporting.raise_event_raw(0x0102, &[]);
+        assert_eq!(parent.dispatched.lock().unwrap()[..], [vec![8, 1, 3]]);
+
+        responder.process(19, 9, Vec::new(), 0);
+        assert!(!responder.received_event(0x0102));
+    }
+
+    #[test]
+    fn test_delete_and_report_definitions() {
+        let parent = parent();
+        let responder = EventActionServiceResponder::new(parent.clone());
+        let controller = EventActionServiceController::new(parent.clone());
+        responder.add_definition(EventActionDefinition::new(1, vec![17, 1]));
+        responder.add_definition(EventActionDefinition::new(2, vec![8, 1, 0]));
+        responder.set_definition_enabled(2, true);
+
+        // Enabled definitions are kept.
+        controller.send_delete_definitions(4, &[1, 2]);
+        controller.send_report_definitions(4);
+        forward(&parent, &responder);
+
+        let report = parent.sent.lock().unwrap().remove(0);
+        assert_eq!(report.1, 0);
+        controller.process(report.0[0], report.0[1], report.0[2..].to_vec(), 4);
+        let definitions = controller.definitions(4).unwrap();
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].event_id, 2);
+        assert!(definitions[0].enabled);
+        assert_eq!(definitions[0].action, [8, 1, 0]);
+    }
+
+    #[test]
+    fn test_delete_all_keeps_enabled_definitions() {
+        let parent = parent();
+        let responder = EventActionServiceResponder::new(parent.clone());
+        let controller = EventActionServiceController::new(parent.clone());
+        for event_id in 1..=3 {
+            responder.add_definition(EventActionDefinition::new(event_id, vec![17, 1]));
+        }
+        responder.set_definition_enabled(3, true);
+
+        controller.send_delete_all_definitions(4);
+        forward(&parent, &responder);
+        assert!(responder.get_definition(1).is_none());
+        assert!(responder.get_definition(2).is_none());
+        assert!(responder.get_definition(3).unwrap().enabled);
+    }
+}
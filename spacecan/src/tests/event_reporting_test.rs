@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::ST05_event_reporting::{
+        EventDefinition, EventReport, EventReportingServiceController, EventReportingServiceResponder, Packet, Parent,
+        Severity,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<(Vec<u8>, u32)>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent { sent: Mutex::new(Vec::new()) })
+    }
+
+    fn over_temperature() -> EventDefinition {
+        EventDefinition::new(0x0102, "over_temperature".into(), Severity::High, "!Bf".into())
+    }
+
+    #[test]
+    fn test_responder_raises_event_with_severity_subtype() {
+        let parent = parent();
+        let mut responder = EventReportingServiceResponder::new(parent.clone());
+        responder.add_event_definition(over_temperature());
+
+        assert!(responder.raise_event(0x0102, &[3.0, 85.5]));
+        let sent = parent.sent.lock().unwrap();
+        assert_eq!(sent[0].0[..5], [5, 4, 0x01, 0x02, 3]);
+        assert_eq!(sent[0].0.len(), 9);
+    }
+
+    #[test]
+    fn test_disabled_event_is_not_reported() {
+        let parent = parent();
+        let mut responder = EventReportingServiceResponder::new(parent.clone());
+        responder.add_event_definition(over_temperature());
+
+        responder.process(5, 6, vec![1, 0x01, 0x02], 1);
+        assert!(!responder.raise_event(0x0102, &[3.0, 85.5]));
+
+        responder.process(5, 7, vec![], 1);
+        assert_eq!(parent.sent.lock().unwrap()[0].0, vec![5, 8, 1, 0x01, 0x02]);
+
+        responder.process(5, 5, vec![1, 0x01, 0x02], 1);
+        assert!(responder.raise_event(0x0102, &[3.0, 85.5]));
+    }
+
+    #[test]
+    fn test_controller_decodes_typed_report() {
+        let responder_parent = parent();
+        let mut responder = EventReportingServiceResponder::new(responder_parent.clone());
+        responder.add_event_definition(over_temperature());
+        responder.raise_event(0x0102, &[3.0, 85.5]);
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        let mut controller = EventReportingServiceController::new(parent());
+        controller.add_event_definition(7, over_temperature());
+        controller.set_event_handler(Arc::new(move |report: &EventReport| {
+            received_clone.lock().unwrap().push(report.clone());
+        }));
+
+        let data = responder_parent.sent.lock().unwrap()[0].0.clone();
+        controller.process(data[0], data[1], data[2..].to_vec(), 7);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0].severity, Severity::High);
+        assert_eq!(received[0].event_name.as_deref(), Some("over_temperature"));
+        assert_eq!(received[0].auxiliary_data, vec![3.0, 85.5]);
+    }
+
+    #[test]
+    fn test_controller_sends_enable_disable_requests() {
+        let parent = parent();
+        let controller = EventReportingServiceController::new(parent.clone());
+        controller.send_disable_event_generation(3, &[1, 2]);
+        controller.send_enable_event_generation(3, &[2]);
+
+        let sent = parent.sent.lock().unwrap();
+        assert_eq!(sent[0], (vec![5, 6, 2, 0, 1, 0, 2], 3));
+        assert_eq!(sent[1], (vec![5, 5, 1, 0, 2], 3));
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::ST13_large_packet_transfer::{
+        self as st13, LargePacketTransferServiceController, LargePacketTransferServiceResponder,
+    };
+    use crate::services::ST23_file_management::{
+        self as st23, FileError, FileManagementServiceController, FileManagementServiceResponder, FileSystem,
+        MemoryFileSystem, StdFileSystem,
+    };
+    use core::time::Duration;
+    use std::boxed::Box;
+    use std::sync::{Arc, Mutex, OnceLock};
+    use std::vec::Vec;
+
+    /// Queue of packets on one side of the bus, shared by ST13 and ST23.
+    #[derive(Default)]
+    struct Side {
+        sent: Mutex<Vec<Vec<u8>>>,
+        dispatched: Mutex<Vec<Vec<u8>>>,
+        large: OnceLock<Arc<dyn Fn(Vec<u8>) + Send + Sync>>,
+    }
+
+    impl st13::Parent for Side {
+        fn send(&self, packet: st13::Packet, _node_id: u32) {
+            self.sent.lock().unwrap().push(packet.data);
+        }
+
+        fn dispatch(&self, data: Vec<u8>, _node_id: u32) {
+            self.dispatched.lock().unwrap().push(data);
+        }
+    }
+
+    impl st23::Parent for Side {
+        fn send(&self, packet: st23::Packet, _node_id: u32) {
+            self.sent.lock().unwrap().push(packet.data);
+        }
+
+        fn send_large(&self, data: Vec<u8>, _node_id: u32) {
+            (self.large.get().unwrap())(data);
+        }
+    }
+
+    fn take(queue: &Mutex<Vec<Vec<u8>>>) -> Vec<Vec<u8>> {
+        queue.lock().unwrap().drain(..).collect()
+    }
+
+    #[test]
+    fn test_memory_file_system() {
+        let mut file_system = MemoryFileSystem::new();
+        file_system.create_directory("logs").unwrap();
+        file_system.write_file("logs/a.txt", b"hello").unwrap();
+        file_system.create_file("/logs//b.txt").unwrap();
+        assert_eq!(file_system.create_file("logs/b.txt"), Err(FileError::AlreadyExists));
+        assert_eq!(file_system.create_file("missing/c.txt"), Err(FileError::NotFound));
+        assert_eq!(file_system.create_file("logs/a.txt/c"), Err(FileError::NotADirectory));
+        assert_eq!(file_system.read_file("../etc/passwd"), Err(FileError::InvalidPath));
+        assert_eq!(file_system.delete_directory("logs"), Err(FileError::DirectoryNotEmpty));
+
+        file_system.copy_file("logs/a.txt", "a.txt").unwrap();
+        assert_eq!(file_system.copy_file("logs/a.txt", "a.txt"), Err(FileError::AlreadyExists));
+        file_system.rename("logs", "archive").unwrap();
+        let names: Vec<_> = file_system
+            .list_directory("")
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.name, entry.attributes.size))
+            .collect();
+        assert_eq!(names, [("a.txt".into(), 5), ("archive".into(), 0)]);
+        assert_eq!(file_system.read_file("archive/a.txt").unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_std_file_system() {
+        let root = std::env::temp_dir().join(std::format!("spacecan_files_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        let mut file_system = StdFileSystem::new(&root);
+        file_system.create_directory("data").unwrap();
+        file_system.write_file("data/x.bin", &[1, 2, 3]).unwrap();
+        file_system.copy_file("data/x.bin", "data/y.bin").unwrap();
+        assert_eq!(file_system.attributes("data/y.bin").unwrap().size, 3);
+        assert_eq!(file_system.delete_file("data"), Err(FileError::IsADirectory));
+        assert_eq!(file_system.delete_directory("data"), Err(FileError::DirectoryNotEmpty));
+        assert_eq!(file_system.read_file("data/../../x"), Err(FileError::InvalidPath));
+        let names: Vec<_> = file_system
+            .list_directory("data")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, ["x.bin", "y.bin"]);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_upload_and_download_by_large_packet_transfer() {
+        let ground = Arc::new(Side::default());
+        let node = Arc::new(Side::default());
+        let transfer_controller = Arc::new(LargePacketTransferServiceController::new(ground.clone()));
+        let transfer_responder = Arc::new(LargePacketTransferServiceResponder::new(node.clone()));
+        let _ = ground.large.set({
+            let transfer_controller = transfer_controller.clone();
+            Arc::new(move |data| {
+                transfer_controller.send_large_message(3, &data);
+            })
+        });
+        let _ = node.large.set({
+            let transfer_responder = transfer_responder.clone();
+            Arc::new(move |data| {
+                transfer_responder.send_large_message(&data);
+            })
+        });
+        let controller = FileManagementServiceController::new(ground.clone());
+        let responder = FileManagementServiceResponder::new(node.clone(), Box::new(MemoryFileSystem::new()));
+
+        // Up-link: ST13 parts are reassembled and dispatched to ST23.
+        let contents: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+        controller.send_create_directory(3, "images");
+        controller.upload(3, "images/frame.raw", &contents);
+        for packet in take(&ground.sent) {
+            if packet[0] == 13 {
+                transfer_responder.process(packet[0], packet[1], packet[2..].to_vec(), 0, Duration::ZERO);
+            } else {
+                responder.process(packet[0], packet[1], packet[2..].to_vec(), 0);
+            }
+        }
+        for message in take(&node.dispatched) {
+            responder.process(message[0], message[1], message[2..].to_vec(), 0);
+        }
+        let stored = responder.with_file_system(|file_system| file_system.read_file("images/frame.raw"));
+        assert_eq!(stored.unwrap(), contents);
+
+        // Down-link: the file is too large for one packet and comes back by ST13.
+        controller.request_download(3, "images/frame.raw");
+        controller.request_download(3, "images/missing.raw");
+        for packet in take(&ground.sent) {
+            responder.process(packet[0], packet[1], packet[2..].to_vec(), 0);
+        }
+        for packet in take(&node.sent) {
+            if packet[0] == 13 {
+                transfer_controller.process(packet[0], packet[1], packet[2..].to_vec(), 3, Duration::ZERO);
+            } else {
+                controller.process(packet[0], packet[1], packet[2..].to_vec(), 3);
+            }
+        }
+        for message in transfer_controller.take_messages(3) {
+            controller.process(message.data[0], message.data[1], message.data[2..].to_vec(), 3);
+        }
+        assert_eq!(controller.take_download(3, "images/frame.raw").unwrap(), contents);
+        let failures = controller.take_failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].1.subtype, 129);
+        assert_eq!(failures[0].1.path, "images/missing.raw");
+        assert_eq!(failures[0].1.error, FileError::NotFound);
+
+        controller.send_directory_listing_request(3, "images");
+        for packet in take(&ground.sent) {
+            responder.process(packet[0], packet[1], packet[2..].to_vec(), 0);
+        }
+        for packet in take(&node.sent) {
+            controller.process(packet[0], packet[1], packet[2..].to_vec(), 3);
+        }
+        let listing = controller.listing(3, "images").unwrap();
+        assert_eq!(listing[0].name, "frame.raw");
+        assert_eq!(listing[0].attributes.size, 5000);
+    }
+}
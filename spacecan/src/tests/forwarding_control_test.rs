@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::ST14_forwarding_control::{
+        ForwardControl, ForwardingControlServiceController, ForwardingControlServiceResponder, Packet, Parent,
+        ALL_SUBTYPES,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, _node_id: u32) {
+            self.sent.lock().unwrap().push(packet.data);
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn deliver(parent: &RecordingParent, responder: &ForwardingControlServiceResponder) {
+        let requests: Vec<Vec<u8>> = parent.sent.lock().unwrap().drain(..).collect();
+        for request in requests {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+    }
+
+    #[test]
+    fn test_filtering() {
+        let mut configuration = ForwardControl::new();
+        assert!(configuration.is_forwarded(&[3, 25, 1]));
+
+        configuration.disabled_report_types.insert((17, ALL_SUBTYPES));
+        configuration.disabled_housekeeping_reports.insert(1);
+        configuration.disabled_events.insert(0x0102);
+        assert!(!configuration.is_forwarded(&[17, 2]));
+        assert!(!configuration.is_forwarded(&[3, 25, 1, 0xAA]));
+        assert!(configuration.is_forwarded(&[3, 25, 2, 0xAA]));
+        assert!(!configuration.is_forwarded(&[5, 4, 1, 2]));
+        assert!(configuration.is_forwarded(&[5, 1, 1, 3]));
+        assert!(configuration.is_forwarded(&[1, 1]));
+    }
+
+    #[test]
+    fn test_configuration_over_the_bus() {
+        let parent = parent();
+        let controller = ForwardingControlServiceController::new(parent.clone());
+        let responder = ForwardingControlServiceResponder::new(parent.clone());
+
+        controller.send_disable_report_types(3, &[(17, 2), (20, 2)]);
+        controller.send_disable_housekeeping_reports(3, &[4]);
+        controller.send_disable_events(3, &[7, 8]);
+        controller.send_enable_events(3, &[8]);
+        controller.send_enable_report_types(3, &[(20, 2)]);
+        deliver(&parent, &responder);
+
+        assert!(!responder.forward(Packet::new(vec![17, 2])));
+        assert!(!responder.forward(Packet::new(vec![3, 25, 4])));
+        assert!(responder.forward(Packet::new(vec![20, 2])));
+        assert!(responder.forward(Packet::new(vec![5, 2, 0, 8])));
+        assert_eq!(parent.sent.lock().unwrap().len(), 2);
+        parent.sent.lock().unwrap().clear();
+
+        controller.send_configuration_request(3);
+        deliver(&parent, &responder);
+        let report = parent.sent.lock().unwrap().remove(0);
+        controller.process(report[0], report[1], report[2..].to_vec(), 3);
+        assert_eq!(controller.configuration(3), Some(responder.configuration()));
+        let configuration = controller.configuration(3).unwrap();
+        assert_eq!(configuration.disabled_report_types.iter().collect::<Vec<_>>(), [&(17, 2)]);
+        assert_eq!(configuration.disabled_events.iter().collect::<Vec<_>>(), [&7]);
+    }
+}
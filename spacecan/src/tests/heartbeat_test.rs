@@ -1,30 +1,58 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::heartbeat::{HeartbeatProducer, HeartbeatConsumer};
-    use std::sync::Arc;
+    use crate::primitives::can_frame::{CanFrame, CanFrameError};
+    use crate::primitives::heartbeat::{HeartbeatConsumer, HeartbeatProducer};
+    use crate::primitives::network::Parent;
+    use crate::primitives::scheduler::{Duration as Ticks, SimulatedClock};
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
-    use std::thread;
-    
+    use std::vec::Vec;
+
+    struct MockNetwork {
+        sent: Mutex<Vec<CanFrame>>,
+    }
+
+    impl MockNetwork {
+        fn new() -> Self {
+            MockNetwork { sent: Mutex::new(Vec::new()) }
+        }
+    }
+
+    impl Parent for MockNetwork {
+        fn received_frame(&self, _can_frame: CanFrame) {}
+
+        fn send(&self, can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            self.sent.lock().unwrap().push(can_frame.clone());
+            Ok(())
+        }
+    }
+
     #[test]
     fn test_heartbeat_producer_send() {
-        let producer = HeartbeatProducer::new(Arc::new(MockNetwork::new())).unwrap();
+        let network = Arc::new(MockNetwork::new());
+        let producer = HeartbeatProducer::new(network.clone()).unwrap();
         assert!(producer.send().is_ok());
+        assert_eq!(network.sent.lock().unwrap()[0].can_id(), 0x700);
     }
-    
+
     #[test]
     fn test_heartbeat_consumer_receive() {
-        let consumer = HeartbeatConsumer::new(Duration::from_secs(2));
+        let clock = Arc::new(SimulatedClock::new());
+        let consumer = HeartbeatConsumer::with_clock(Duration::from_secs(2), clock.clone());
         consumer.receive_heartbeat();
-        
+
         assert!(!consumer.check_timeout()); // Should not timeout immediately
+        clock.advance(Ticks::secs(2));
+        assert!(!consumer.check_timeout());
     }
-    
+
     #[test]
     fn test_heartbeat_consumer_timeout() {
-        let consumer = HeartbeatConsumer::new(Duration::from_millis(100));
-        thread::sleep(Duration::from_millis(200)); // Wait longer than timeout
-        
+        let clock = Arc::new(SimulatedClock::new());
+        let consumer = HeartbeatConsumer::with_clock(Duration::from_millis(100), clock.clone());
+        consumer.receive_heartbeat();
+        clock.advance(Ticks::millis(200)); // Wait longer than timeout
+
         assert!(consumer.check_timeout()); // Should detect timeout
     }
 }
@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::packet::MAX_PACKET_LENGTH;
+    use crate::services::ST13_large_packet_transfer::{
+        Abort, AbortReason, LargeMessageReceiver, LargePacketTransferServiceController,
+        LargePacketTransferServiceResponder, Packet, Parent, PartKind,
+    };
+    use core::time::Duration;
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<(Vec<u8>, u32)>>,
+        dispatched: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+
+        fn dispatch(&self, data: Vec<u8>, _node_id: u32) {
+            self.dispatched.lock().unwrap().push(data);
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+            dispatched: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn take_sent(parent: &RecordingParent) -> Vec<Vec<u8>> {
+        parent.sent.lock().unwrap().drain(..).map(|(data, _)| data).collect()
+    }
+
+    #[test]
+    fn test_uplink_message_is_dispatched() {
+        let parent = parent();
+        let controller = LargePacketTransferServiceController::new(parent.clone());
+        let responder = LargePacketTransferServiceResponder::new(parent.clone());
+
+        let message: Vec<u8> = (0..2500).map(|i| i as u8).collect();
+        controller.send_large_message(5, &message);
+        let parts = take_sent(&parent);
+        let subtypes: Vec<u8> = parts.iter().map(|part| part[1]).collect();
+        assert_eq!(subtypes, [9, 10, 11]);
+        assert!(parts.iter().all(|part| part.len() <= MAX_PACKET_LENGTH));
+
+        for part in parts {
+            responder.process(part[0], part[1], part[2..].to_vec(), 0, Duration::ZERO);
+        }
+        assert_eq!(parent.dispatched.lock().unwrap()[..], [message]);
+    }
+
+    #[test]
+    fn test_downlink_message_is_reassembled() {
+        let parent = parent();
+        let controller = LargePacketTransferServiceController::new(parent.clone());
+        let responder = LargePacketTransferServiceResponder::new(parent.clone());
+
+        let transaction_id = responder.send_large_message(&[1, 2, 3]);
+        let parts = take_sent(&parent);
+        assert_eq!(parts.len(), 2);
+        for part in parts {
+            controller.process(part[0], part[1], part[2..].to_vec(), 5, Duration::ZERO);
+        }
+        let messages = controller.take_messages(5);
+        assert_eq!(messages[0].transaction_id, transaction_id);
+        assert_eq!(messages[0].data, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_sequence_error_is_reported() {
+        let parent = parent();
+        let controller = LargePacketTransferServiceController::new(parent.clone());
+        let responder = LargePacketTransferServiceResponder::new(parent.clone());
+
+        controller.send_large_message(5, &vec![0; 3000]);
+        let parts = take_sent(&parent);
+        responder.process(parts[0][0], parts[0][1], parts[0][2..].to_vec(), 0, Duration::ZERO);
+        responder.process(parts[2][0], parts[2][1], parts[2][2..].to_vec(), 0, Duration::ZERO);
+
+        let report = take_sent(&parent).remove(0);
+        assert_eq!(report[..2], [13, 16]);
+        controller.process(report[0], report[1], report[2..].to_vec(), 5, Duration::ZERO);
+        assert_eq!(
+            controller.take_aborts(),
+            [Abort {
+                node_id: 5,
+                transaction_id: 0,
+                reason: AbortReason::SequenceError
+            }]
+        );
+        assert!(parent.dispatched.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reception_timeout() {
+        let mut receiver = LargeMessageReceiver::new(Duration::from_secs(2), 100);
+        assert_eq!(receiver.receive_part(3, PartKind::First, &[0, 7, 0, 0, 1], Duration::ZERO), Ok(None));
+        assert!(receiver.check_timeouts(Duration::from_secs(2)).is_empty());
+        let aborts = receiver.check_timeouts(Duration::from_secs(3));
+        assert_eq!(aborts[0].reason, AbortReason::Timeout);
+        assert_eq!(receiver.pending(), 0);
+        assert_eq!(
+            receiver.receive_part(3, PartKind::Last, &[0, 7, 0, 1], Duration::from_secs(3)).unwrap_err().reason,
+            AbortReason::UnknownTransaction
+        );
+    }
+}
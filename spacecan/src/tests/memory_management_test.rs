@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::crc::crc16;
+    use crate::services::ST06_memory_management::{
+        Access, MemoryError, MemoryManagementServiceController, MemoryManagementServiceResponder, Packet, Parent,
+        RamRegion, RequestVerification, MAX_TRANSFER_LENGTH,
+    };
+    use std::boxed::Box;
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<(Vec<u8>, u32)>>,
+        verification: RecordingVerification,
+    }
+
+    #[derive(Default)]
+    struct RecordingVerification {
+        reports: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl RecordingVerification {
+        fn record(&self, subtype: u8, args: &[u8]) {
+            let mut report = vec![1, subtype];
+            report.extend_from_slice(args);
+            self.reports.lock().unwrap().push(report);
+        }
+    }
+
+    impl RequestVerification for RecordingVerification {
+        fn send_success_acceptance_report(&self, args: &[u8]) {
+            self.record(1, args);
+        }
+        fn send_success_completion_report(&self, args: &[u8]) {
+            self.record(7, args);
+        }
+        fn send_fail_acceptance_report(&self, args: &[u8]) {
+            self.record(2, args);
+        }
+        fn send_fail_completion_report(&self, args: &[u8]) {
+            self.record(8, args);
+        }
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+        fn request_verification(&self) -> &dyn RequestVerification {
+            &self.verification
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+            verification: RecordingVerification::default(),
+        })
+    }
+
+    fn take_sent(parent: &RecordingParent) -> Vec<Vec<u8>> {
+        parent.sent.lock().unwrap().drain(..).map(|(data, _)| data).collect()
+    }
+
+    fn take_reports(parent: &RecordingParent) -> Vec<Vec<u8>> {
+        core::mem::take(&mut *parent.verification.reports.lock().unwrap())
+    }
+
+    fn responder(parent: Arc<RecordingParent>) -> MemoryManagementServiceResponder {
+        let responder = MemoryManagementServiceResponder::new(parent);
+        let contents: Vec<u8> = (0..3000).map(|i| i as u8).collect();
+        responder.register_region(1, 0x2000_0000, Access::ReadWrite, Box::new(RamRegion::new(contents)));
+        responder.register_region(2, 0x0800_0000, Access::ReadOnly, Box::new(RamRegion::new(vec![0xAA; 16])));
+        responder
+    }
+
+    #[test]
+    fn test_access_permissions_and_ranges() {
+        let responder = responder(parent());
+        assert_eq!(responder.load(2, 0x0800_0000, &[1]), Err(MemoryError::AccessDenied));
+        assert_eq!(responder.dump(3, 0, 1), Err(MemoryError::UnknownMemoryId(3)));
+        assert_eq!(responder.dump(2, 0x0800_0010, 1), Err(MemoryError::OutOfRange));
+        assert_eq!(responder.dump(2, 0x07FF_FFFF, 1), Err(MemoryError::OutOfRange));
+        assert_eq!(responder.dump(2, 0x0800_000E, 2), Ok(vec![0xAA, 0xAA]));
+    }
+
+    #[test]
+    fn test_split_dump_is_reassembled() {
+        let parent = parent();
+        let responder = responder(parent.clone());
+        let controller = MemoryManagementServiceController::new(parent.clone());
+
+        controller.send_dump_request(7, 1, 0x2000_0010, 2500).unwrap();
+        let requests = take_sent(&parent);
+        assert_eq!(requests.len(), 3);
+        for request in requests {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        let reports = take_sent(&parent);
+        assert!(reports.iter().all(|report| report.len() <= MAX_TRANSFER_LENGTH + 11));
+        for report in reports.into_iter().rev() {
+            controller.process(report[0], report[1], report[2..].to_vec(), 7);
+        }
+
+        assert!(!controller.is_dump_pending(7, 1));
+        let dump = controller.take_dump(7, 1).unwrap();
+        assert_eq!(dump.start_address, 0x2000_0010);
+        assert_eq!(dump.data.len(), 2500);
+        assert!(dump.data.iter().enumerate().all(|(i, byte)| *byte == (i + 0x10) as u8));
+    }
+
+    #[test]
+    fn test_load_and_check() {
+        let parent = parent();
+        let responder = responder(parent.clone());
+        let controller = MemoryManagementServiceController::new(parent.clone());
+
+        let patch = vec![0x55; 1500];
+        controller.send_load(7, 1, 0x2000_0100, &patch).unwrap();
+        controller.send_check_request(7, 1, 0x2000_0100, 1500);
+        let mut requests = take_sent(&parent);
+        assert_eq!(requests.len(), 3);
+        // A corrupted load is rejected.
+        let mut corrupted = requests[0].clone();
+        corrupted[10] ^= 0xFF;
+        responder.process(corrupted[0], corrupted[1], corrupted[2..].to_vec(), 0);
+        assert_eq!(responder.dump(1, 0x2000_0100, 1), Ok(vec![0]));
+        assert!(take_sent(&parent).is_empty());
+        assert_eq!(take_reports(&parent), [[1, 2, 6, 2]]);
+
+        for request in requests.drain(..) {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        assert_eq!(responder.dump(1, 0x2000_0100, 1500), Ok(patch.clone()));
+        assert_eq!(
+            take_reports(&parent),
+            [[1, 1, 6, 2], [1, 7, 6, 2], [1, 1, 6, 2], [1, 7, 6, 2], [1, 1, 6, 9], [1, 7, 6, 9]]
+        );
+
+        let report = take_sent(&parent).remove(0);
+        controller.process(report[0], report[1], report[2..].to_vec(), 7);
+        let checksum = controller.last_checksum(7).unwrap();
+        assert_eq!(checksum.length, 1500);
+        assert_eq!(checksum.checksum, crc16(&patch));
+    }
+
+    #[test]
+    fn test_failures_are_reported() {
+        let parent = parent();
+        let responder = responder(parent.clone());
+        let controller = MemoryManagementServiceController::new(parent.clone());
+
+        controller.send_load(7, 2, 0x0800_0000, &[1]).unwrap();
+        controller.send_dump_request(7, 3, 0, 4).unwrap();
+        controller.send_check_request(7, 1, 0x2000_0000, 4000);
+        for request in take_sent(&parent) {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        assert!(take_sent(&parent).is_empty());
+        assert_eq!(take_reports(&parent), [[1, 2, 6, 2], [1, 2, 6, 5], [1, 2, 6, 9]]);
+        assert_eq!(responder.dump(2, 0x0800_0000, 1), Ok(vec![0xAA]));
+
+        // The failed dump is abandoned by the controller.
+        assert!(controller.cancel_dump(7, 3));
+        assert!(!controller.is_dump_pending(7, 3));
+    }
+
+    #[test]
+    fn test_transfer_length_is_limited() {
+        let parent = parent();
+        let responder = responder(parent.clone());
+        let length = MAX_TRANSFER_LENGTH as u16 + 1;
+
+        assert_eq!(responder.send_dump_report(1, 0x2000_0000, length), Err(MemoryError::OutOfRange));
+        let data = vec![0x55; length as usize];
+        let mut rest = data.clone();
+        rest.extend_from_slice(&crc16(&data).to_be_bytes());
+        assert_eq!(responder.received_load(1, 0x2000_0000, length, &rest), Err(MemoryError::OutOfRange));
+        assert!(take_sent(&parent).is_empty());
+
+        let mut header = vec![1, 0x20, 0, 0, 0];
+        header.extend_from_slice(&length.to_be_bytes());
+        responder.process(6, 5, header.clone(), 0);
+        let mut load = header;
+        load.extend_from_slice(&rest);
+        responder.process(6, 2, load, 0);
+        assert!(take_sent(&parent).is_empty());
+        assert_eq!(take_reports(&parent), [[1, 2, 6, 5], [1, 2, 6, 2]]);
+        assert_eq!(responder.dump(1, 0x2000_0000, 2), Ok(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_dump_requests_are_checked() {
+        let parent = parent();
+        let responder = responder(parent.clone());
+        let controller = MemoryManagementServiceController::new(parent.clone());
+
+        // Areas past the end of the address space are refused before sending.
+        assert_eq!(controller.send_dump_request(7, 1, u32::MAX - 9, 11), Err(MemoryError::OutOfRange));
+        assert_eq!(controller.send_load(7, 1, u32::MAX, &[1, 2]), Err(MemoryError::OutOfRange));
+        assert!(take_sent(&parent).is_empty());
+        assert!(!controller.is_dump_pending(7, 1));
+        controller.send_dump_request(7, 1, u32::MAX - 9, 10).unwrap();
+        controller.cancel_dump(7, 1);
+        take_sent(&parent);
+
+        // An empty dump completes without a request.
+        controller.send_dump_request(7, 1, 0x2000_0000, 0).unwrap();
+        assert!(take_sent(&parent).is_empty());
+        assert!(!controller.is_dump_pending(7, 1));
+        assert!(controller.take_dump(7, 1).unwrap().data.is_empty());
+
+        // A report shorter than the requested chunk leaves the dump pending.
+        controller.send_dump_request(7, 1, 0x2000_0000, 8).unwrap();
+        responder.send_dump_report(1, 0x2000_0000, 4).unwrap();
+        let report = take_sent(&parent).pop().unwrap();
+        controller.process(report[0], report[1], report[2..].to_vec(), 7);
+        assert!(controller.is_dump_pending(7, 1));
+        responder.send_dump_report(1, 0x2000_0000, 8).unwrap();
+        let report = take_sent(&parent).pop().unwrap();
+        controller.process(report[0], report[1], report[2..].to_vec(), 7);
+        assert_eq!(controller.take_dump(7, 1).unwrap().data, [0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+}
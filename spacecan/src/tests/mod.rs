@@ -0,0 +1,29 @@
+mod async_node_test;
+mod base_test;
+mod can_frames_test;
+mod device_access_test;
+mod encoding_test;
+mod event_action_test;
+mod event_reporting_test;
+mod file_management_test;
+mod forwarding_control_test;
+mod heartbeat_test;
+mod large_packet_transfer_test;
+mod memory_management_test;
+mod monitoring_test;
+mod node_registry_test;
+mod onboard_operations_test;
+mod onboard_storage_test;
+mod parameter_management_test;
+mod parameter_statistics_test;
+mod redundancy_test;
+mod scheduler_test;
+mod scheduling_test;
+mod simulated_clock_test;
+mod statistics_test;
+mod storage_test;
+mod sync_scheduler_test;
+mod sync_test;
+mod test_service_test;
+mod time_test;
+mod transmit_queue_test;
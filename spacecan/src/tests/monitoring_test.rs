@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::ST05_event_reporting::{self, EventDefinition, EventReportingServiceResponder, Severity};
+    use crate::services::ST12_onboard_monitoring::{
+        Check, CheckStatus, MonitoringDefinition, OnboardMonitoringServiceController, OnboardMonitoringServiceResponder,
+        Packet, Parent,
+    };
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<(Vec<u8>, u32)>>,
+        values: Mutex<BTreeMap<u32, f64>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+
+        fn get_parameter_value(&self, parameter_id: u32) -> Option<f64> {
+            self.values.lock().unwrap().get(&parameter_id).copied()
+        }
+    }
+
+    impl ST05_event_reporting::Parent for RecordingParent {
+        fn send(&self, packet: ST05_event_reporting::Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+            values: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    fn set_value(parent: &RecordingParent, parameter_id: u32, value: f64) {
+        parent.values.lock().unwrap().insert(parameter_id, value);
+    }
+
+    #[test]
+    fn test_limit_check_with_repetition() {
+        let parent = parent();
+        let responder = OnboardMonitoringServiceResponder::new(parent.clone());
+        responder.add_definition(MonitoringDefinition::new(1, 10, 2, Check::Limit { low: 0.0, high: 50.0 }));
+
+        set_value(&parent, 10, 20.0);
+        assert!(responder.poll().is_empty());
+        assert_eq!(responder.poll().len(), 1);
+        assert_eq!(responder.check_status(1), Some(CheckStatus::WithinLimits));
+
+        set_value(&parent, 10, 60.0);
+        assert!(responder.poll().is_empty());
+        set_value(&parent, 10, 20.0);
+        assert!(responder.poll().is_empty());
+        set_value(&parent, 10, 60.0);
+        assert!(responder.poll().is_empty());
+        let transitions = responder.poll();
+        assert_eq!(transitions[0].previous_status, CheckStatus::WithinLimits);
+        assert_eq!(transitions[0].current_status, CheckStatus::AboveHighLimit);
+
+        let sent = parent.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].0[..3], [12, 12, 1]);
+        assert_eq!(sent[1].1, 0);
+    }
+
+    #[test]
+    fn test_expected_value_and_delta_checks() {
+        let parent = parent();
+        let responder = OnboardMonitoringServiceResponder::new(parent.clone());
+        responder.add_definition(MonitoringDefinition::new(
+            1,
+            10,
+            1,
+            Check::ExpectedValue { mask: 0x0F, expected: 0x05 },
+        ));
+        responder.add_definition(MonitoringDefinition::new(2, 11, 1, Check::Delta { low: -1.0, high: 1.0 }));
+
+        set_value(&parent, 10, 0x35 as f64);
+        set_value(&parent, 11, 100.0);
+        responder.poll();
+        assert_eq!(responder.check_status(1), Some(CheckStatus::ExpectedValue));
+        assert_eq!(responder.check_status(2), Some(CheckStatus::Unchecked));
+
+        set_value(&parent, 10, 0x36 as f64);
+        set_value(&parent, 11, 97.0);
+        responder.poll();
+        assert_eq!(responder.check_status(1), Some(CheckStatus::UnexpectedValue));
+        assert_eq!(responder.check_status(2), Some(CheckStatus::BelowLowThreshold));
+
+        parent.values.lock().unwrap().clear();
+        responder.poll();
+        assert_eq!(responder.check_status(1), Some(CheckStatus::Invalid));
+    }
+
+    #[test]
+    fn test_runtime_configuration_and_events() {
+        let parent = parent();
+        let mut event_reporting = EventReportingServiceResponder::new(parent.clone());
+        event_reporting.add_event_definition(EventDefinition::new(7, "limit".into(), Severity::Medium, "!Hd".into()));
+        let mut responder = OnboardMonitoringServiceResponder::new(parent.clone());
+        responder.set_event_reporting(Arc::new(event_reporting));
+        let controller = OnboardMonitoringServiceController::new(parent.clone());
+
+        let definition = MonitoringDefinition::new(3, 10, 1, Check::Limit { low: 0.0, high: 1.0 }).with_event(7);
+        controller.send_add_definitions(5, &[definition]);
+        controller.send_disable_monitoring(5);
+        let requests: Vec<Vec<u8>> = parent.sent.lock().unwrap().drain(..).map(|(data, _)| data).collect();
+        for request in requests {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        assert_eq!(responder.get_definition(3), Some(definition));
+        set_value(&parent, 10, 2.0);
+        assert!(responder.poll().is_empty());
+
+        responder.process(12, 15, Vec::new(), 0);
+        responder.poll();
+        let sent: Vec<Vec<u8>> = parent.sent.lock().unwrap().drain(..).map(|(data, _)| data).collect();
+        assert_eq!(sent[1][..4], [5, 3, 0, 7]);
+
+        controller.process(sent[0][0], sent[0][1], sent[0][2..].to_vec(), 5);
+        assert_eq!(controller.check_status(5, 3), Some(CheckStatus::AboveHighLimit));
+    }
+}
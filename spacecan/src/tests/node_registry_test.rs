@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use crate::asynchronous::bus::VirtualBus;
+    use crate::asynchronous::network::{AsyncNetwork, BusSelection};
+    use crate::asynchronous::node::{AsyncController, AsyncResponder};
+    use crate::asynchronous::registry::{NodeEvent, NodeRegistry, NodeState};
+    use crate::primitives::scheduler::Instant;
+    use crate::services::ST17_test::Reachability;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use std::vec;
+    use std::vec::Vec;
+
+    const CONFIG: &str = r#"{"silence_timeout_ms": 100, "nodes": [
+        {"node_id": 1, "name": "eps"},
+        {"node_id": 2, "name": "aocs"}
+    ]}"#;
+
+    fn at(millis: u64) -> Instant {
+        Instant::from_ticks(millis * 1000)
+    }
+
+    #[test]
+    fn test_registry_tracks_liveness_and_commands() {
+        let mut registry = NodeRegistry::from_json(CONFIG).unwrap();
+        assert!(NodeRegistry::from_json(r#"{"nodes": [{"name": "eps"}]}"#).is_err());
+        assert_eq!(registry.node(2).unwrap().name.as_deref(), Some("aocs"));
+        assert_eq!(registry.node(1).unwrap().state, NodeState::Unknown);
+
+        assert_eq!(registry.record_telemetry(1, BusSelection::A, at(0)), Some(NodeEvent::Appeared));
+        assert_eq!(registry.record_telemetry(1, BusSelection::A, at(50)), None);
+        // An unconfigured node is added when heard.
+        assert_eq!(registry.record_telemetry(9, BusSelection::B, at(60)), Some(NodeEvent::Appeared));
+
+        registry.command_sent(1, (8, 1));
+        registry.command_sent(1, (8, 1));
+        registry.record_packet(1, &[1, 1, 8, 1]);
+        registry.record_packet(1, &[1, 7, 8, 1]);
+        // Reports for requests that are not pending settle nothing.
+        registry.record_packet(1, &[1, 7, 20, 3]);
+        registry.record_packet(1, &[1, 8]);
+        registry.record_packet(1, &[3]);
+        registry.set_reachability(1, Reachability::TimedOut);
+        let node = registry.node(1).unwrap();
+        assert_eq!((node.pending_commands, node.packet_errors), (1, 1));
+        assert_eq!(node.reachability, Some(Reachability::TimedOut));
+
+        assert_eq!(registry.check_liveness(at(150)), []);
+        assert_eq!(registry.check_liveness(at(151)), [(1, NodeEvent::Silent)]);
+        assert_eq!(registry.check_liveness(at(200)), [(9, NodeEvent::Silent)]);
+        assert_eq!(registry.record_telemetry(1, BusSelection::B, at(300)), Some(NodeEvent::Recovered));
+        let nodes: Vec<(u32, NodeState)> = registry.nodes().iter().map(|node| (node.node_id, node.state)).collect();
+        assert_eq!(nodes, [(1, NodeState::Alive), (2, NodeState::Unknown), (9, NodeState::Silent)]);
+    }
+
+    #[tokio::test]
+    async fn test_controller_reports_node_events() {
+        let (bus_a, bus_b) = (VirtualBus::new(), VirtualBus::new());
+        let controller = AsyncController::new(AsyncNetwork::new(bus_a.clone(), bus_b.clone()));
+        controller.set_registry(NodeRegistry::from_json(CONFIG).unwrap());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        {
+            let events = events.clone();
+            controller.add_node_handler(Arc::new(move |node_id, event| {
+                events.lock().unwrap().push((node_id, event));
+            }));
+        }
+        controller.start_roster();
+
+        let responder = AsyncResponder::new(AsyncNetwork::new(bus_a, bus_b), 1);
+        controller.send_packet(1, vec![8, 1, 4]).await.unwrap();
+        responder.send_packet(vec![1, 7, 8, 1]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(events.lock().unwrap()[..], [(1, NodeEvent::Appeared)]);
+        let node = controller.nodes().remove(0);
+        assert_eq!((node.state, node.pending_commands, node.bus), (NodeState::Alive, 0, Some(BusSelection::A)));
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        responder.send_packet(vec![3, 25, 1]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(
+            events.lock().unwrap()[..],
+            [(1, NodeEvent::Appeared), (1, NodeEvent::Silent), (1, NodeEvent::Recovered)]
+        );
+        controller.shutdown().await;
+    }
+}
@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::ST18_onboard_operations::{
+        Comparison, Instruction, ObcpError, OnboardOperationsServiceController, OnboardOperationsServiceResponder,
+        Packet, Parent, Procedure, ProcedureState, RequestVerification,
+    };
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<Vec<u8>>>,
+        dispatched: Mutex<Vec<(Vec<u8>, u32)>>,
+        parameters: Mutex<BTreeMap<u32, f64>>,
+        verification: RecordingVerification,
+    }
+
+    #[derive(Default)]
+    struct RecordingVerification {
+        reports: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl RecordingVerification {
+        fn record(&self, subtype: u8, args: &[u8]) {
+            let mut report = vec![1, subtype];
+            report.extend_from_slice(args);
+            self.reports.lock().unwrap().push(report);
+        }
+    }
+
+    impl RequestVerification for RecordingVerification {
+        fn send_success_acceptance_report(&self, args: &[u8]) {
+            self.record(1, args);
+        }
+        fn send_success_completion_report(&self, args: &[u8]) {
+            self.record(7, args);
+        }
+        fn send_fail_acceptance_report(&self, args: &[u8]) {
+            self.record(2, args);
+        }
+        fn send_fail_completion_report(&self, args: &[u8]) {
+            self.record(8, args);
+        }
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, _node_id: u32) {
+            self.sent.lock().unwrap().push(packet.data);
+        }
+
+        fn dispatch(&self, data: Vec<u8>, node_id: u32) {
+            self.dispatched.lock().unwrap().push((data, node_id));
+        }
+
+        fn get_parameter_value(&self, parameter_id: u32) -> Option<f64> {
+            self.parameters.lock().unwrap().get(&parameter_id).copied()
+        }
+
+        fn request_verification(&self) -> &dyn RequestVerification {
+            &self.verification
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+            dispatched: Mutex::new(Vec::new()),
+            parameters: Mutex::new(BTreeMap::new()),
+            verification: RecordingVerification::default(),
+        })
+    }
+
+    /// Sends (8,1) once parameter 1 exceeds 10, checking every other tick.
+    const HEATER_PROCEDURE: &str = r#"{"steps": [
+        {"check": {"parameter_id": 1, "comparison": ">", "value": 10.0}},
+        {"jump_if": 4},
+        {"wait": 2},
+        {"jump": 0},
+        {"send": [8, 1, 3]},
+        "end"
+    ]}"#;
+
+    #[test]
+    fn test_bytecode_roundtrip() {
+        let procedure = Procedure::from_json(HEATER_PROCEDURE).unwrap();
+        assert_eq!(
+            procedure.instructions[0],
+            Instruction::Check {
+                parameter_id: 1,
+                comparison: Comparison::Greater,
+                value: 10.0
+            }
+        );
+        assert_eq!(Procedure::decode(&procedure.encode()), Ok(procedure));
+
+        assert_eq!(
+            Procedure::new(vec![Instruction::Jump { target: 1 }]),
+            Err(ObcpError::InvalidProcedure)
+        );
+        assert_eq!(Procedure::decode(&[0x01, 0x00]), Err(ObcpError::InvalidProcedure));
+        assert_eq!(Procedure::from_json(r#"{"steps": [{"fly": 1}]}"#), Err(ObcpError::InvalidProcedure));
+    }
+
+    #[test]
+    fn test_procedure_runs_on_ticks() {
+        let parent = parent();
+        let controller = OnboardOperationsServiceController::new(parent.clone());
+        let responder = OnboardOperationsServiceResponder::new(parent.clone(), 6);
+        parent.parameters.lock().unwrap().insert(1, 0.0);
+
+        controller
+            .send_load(6, 1, &Procedure::from_json(HEATER_PROCEDURE).unwrap())
+            .unwrap();
+        controller.send_start(6, 1);
+        for request in parent.sent.lock().unwrap().drain(..) {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+
+        assert_eq!(responder.tick(), 0);
+        assert_eq!(responder.tick(), 0);
+        parent.parameters.lock().unwrap().insert(1, 12.0);
+        responder.suspend(1).unwrap();
+        assert_eq!(responder.tick(), 0);
+        assert_eq!(responder.status(1).unwrap().state, ProcedureState::Suspended);
+        responder.resume(1).unwrap();
+        // The wait ends on this tick, the check passes and the command is sent.
+        assert_eq!(responder.tick(), 1);
+        assert_eq!(parent.dispatched.lock().unwrap()[..], [(vec![8, 1, 3], 6)]);
+        assert_eq!(responder.status(1).unwrap().state, ProcedureState::Inactive);
+        assert_eq!(responder.unload(2), Err(ObcpError::UnknownProcedure(2)));
+
+        controller.send_status_request(6);
+        let request = parent.sent.lock().unwrap().remove(0);
+        responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        let report = parent.sent.lock().unwrap().remove(0);
+        controller.process(report[0], report[1], report[2..].to_vec(), 6);
+        assert_eq!(controller.statuses(6).unwrap(), responder.statuses());
+    }
+
+    #[test]
+    fn test_failures_and_loops() {
+        let parent = parent();
+        let responder = OnboardOperationsServiceResponder::new(parent.clone(), 6);
+        let endless = Procedure::new(vec![Instruction::Send { data: vec![17, 1] }, Instruction::Jump { target: 0 }]);
+        responder.load(1, endless.unwrap()).unwrap();
+        let unknown = Procedure::new(vec![Instruction::Check {
+            parameter_id: 99,
+            comparison: Comparison::Equal,
+            value: 0.0,
+        }]);
+        responder.load(2, unknown.unwrap()).unwrap();
+        responder.start(1).unwrap();
+        responder.start(2).unwrap();
+
+        // A loop without waits is cut off by the per-tick budget.
+        assert_eq!(responder.tick(), 16);
+        assert_eq!(responder.status(2).unwrap().state, ProcedureState::Failed);
+        assert_eq!(responder.unload(1), Err(ObcpError::InvalidState(1)));
+        responder.stop(1).unwrap();
+        assert_eq!(responder.tick(), 0);
+        responder.unload(1).unwrap();
+    }
+
+    #[test]
+    fn test_rejected_requests_are_reported() {
+        let parent = parent();
+        let responder = OnboardOperationsServiceResponder::new(parent.clone(), 6);
+        let controller = OnboardOperationsServiceController::new(parent.clone());
+
+        // Too many instructions for the u16 program counter.
+        let oversized = vec![Instruction::Wait { ticks: 1 }; u16::MAX as usize + 1];
+        assert_eq!(Procedure::new(oversized.clone()), Err(ObcpError::InvalidProcedure));
+        let procedure = Procedure { instructions: oversized };
+        assert_eq!(responder.load(1, procedure), Err(ObcpError::InvalidProcedure));
+
+        controller.send_start(6, 3);
+        controller.send_load(6, 4, &Procedure::new(vec![Instruction::End]).unwrap()).unwrap();
+        controller.send_load(6, 4, &Procedure::new(vec![Instruction::End]).unwrap()).unwrap();
+        controller.send_resume(6, 4);
+        let requests: Vec<Vec<u8>> = parent.sent.lock().unwrap().drain(..).collect();
+        for request in requests {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        // Malformed requests are not accepted.
+        responder.process(18, 1, vec![5, 0xFF], 0);
+        responder.process(18, 3, vec![], 0);
+        assert!(parent.sent.lock().unwrap().is_empty());
+        assert_eq!(responder.statuses().len(), 1);
+
+        assert_eq!(
+            *parent.verification.reports.lock().unwrap(),
+            [
+                [1, 1, 18, 3],
+                [1, 8, 18, 3],
+                [1, 1, 18, 1],
+                [1, 7, 18, 1],
+                [1, 1, 18, 1],
+                [1, 8, 18, 1],
+                [1, 1, 18, 6],
+                [1, 8, 18, 6],
+                [1, 2, 18, 1],
+                [1, 2, 18, 3],
+            ]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::time::CucTime;
+    use crate::services::ST15_onboard_storage::{
+        OnboardStorageServiceController, OnboardStorageServiceResponder, OverwritePolicy, Packet,
+        Parent,
+    };
+    use crate::storage::packet_file::FilePacketStore;
+    use crate::storage::packets::{MemoryPacketStore, PacketStore, StoredPacket};
+    use std::boxed::Box;
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, _node_id: u32) {
+            self.sent.lock().unwrap().push(packet.data);
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn forward(parent: &RecordingParent, controller: &OnboardStorageServiceController) {
+        for data in parent.sent.lock().unwrap().drain(..) {
+            controller.process(data[0], data[1], data[2..].to_vec(), 4);
+        }
+    }
+
+    fn sequences(responder: &OnboardStorageServiceResponder, store_id: u8) -> Vec<u32> {
+        responder
+            .packets(store_id)
+            .unwrap()
+            .iter()
+            .map(|packet| packet.sequence)
+            .collect()
+    }
+
+    #[test]
+    fn test_overwrite_policies() {
+        let responder = OnboardStorageServiceResponder::new(parent());
+        responder
+            .add_store(1, Box::new(MemoryPacketStore::new(2)), OverwritePolicy::Circular)
+            .unwrap();
+        responder
+            .add_store(2, Box::new(MemoryPacketStore::new(2)), OverwritePolicy::Bounded)
+            .unwrap();
+        responder.add_routing(1, (3, 25));
+        responder.add_routing(2, (3, 25));
+
+        assert_eq!(responder.store(&[5, 1], CucTime::new(1, 0)), 0);
+        for second in 0..3 {
+            responder.store(&[3, 25, second as u8], CucTime::new(second, 0));
+        }
+        assert_eq!(sequences(&responder, 1), [1, 2]);
+        assert_eq!(sequences(&responder, 2), [0, 1]);
+
+        responder.set_storage_enabled(1, false);
+        assert_eq!(responder.store(&[3, 25], CucTime::new(4, 0)), 0);
+    }
+
+    #[test]
+    fn test_retrieval_over_the_bus() {
+        let parent = parent();
+        let controller = OnboardStorageServiceController::new(parent.clone());
+        let responder = OnboardStorageServiceResponder::new(parent.clone());
+        responder
+            .add_store(1, Box::new(MemoryPacketStore::new(10)), OverwritePolicy::Circular)
+            .unwrap();
+
+        controller.send_add_routing(4, 1, &[(5, 1), (5, 2)]);
+        let request = parent.sent.lock().unwrap().remove(0);
+        responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        for second in 0..5 {
+            responder.store(&[5, 1 + second as u8 % 2], CucTime::new(second, 0));
+        }
+
+        controller.send_retrieve_by_time(4, 1, CucTime::new(1, 0), CucTime::new(2, 0));
+        controller.send_retrieve_by_sequence(4, 1, 4, 10);
+        let requests: Vec<Vec<u8>> = parent.sent.lock().unwrap().drain(..).collect();
+        for request in requests {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        forward(&parent, &controller);
+        let retrieved = controller.take_retrieved(4);
+        let sequences: Vec<u32> = retrieved.iter().map(|packet| packet.packet.sequence).collect();
+        assert_eq!(sequences, [1, 2, 4]);
+        assert_eq!(retrieved[0].packet.data, [5, 2]);
+        assert_eq!(retrieved[0].packet.time, CucTime::new(1, 0));
+
+        responder.delete_content(1, CucTime::new(2, 0)).unwrap();
+        responder.send_summary_report();
+        forward(&parent, &controller);
+        let summary = controller.summaries(4).unwrap()[0];
+        assert_eq!(summary.packet_count, 2);
+        assert_eq!(summary.capacity, 10);
+        assert_eq!(summary.first_sequence, 3);
+        assert!(summary.enabled && summary.circular);
+    }
+
+    #[test]
+    fn test_summary_with_large_sequence_numbers() {
+        let parent = parent();
+        let controller = OnboardStorageServiceController::new(parent.clone());
+        let responder = OnboardStorageServiceResponder::new(parent.clone());
+        let mut store = MemoryPacketStore::new(4);
+        store.push(StoredPacket::new(0x0001_2345, CucTime::new(1, 0), vec![3, 25])).unwrap();
+        responder.add_store(2, Box::new(store), OverwritePolicy::Bounded).unwrap();
+
+        responder.send_summary_report();
+        forward(&parent, &controller);
+        let summary = controller.summaries(4).unwrap()[0];
+        assert_eq!(summary.store_id, 2);
+        assert_eq!(summary.first_sequence, 0x0001_2345);
+        assert_eq!(summary.packet_count, 1);
+        assert!(!summary.circular);
+    }
+
+    #[test]
+    fn test_file_store_survives_reopen() {
+        let path = std::env::temp_dir().join(std::format!("spacecan_packets_{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut store = FilePacketStore::open(&path, 3).unwrap();
+        for sequence in 0..3 {
+            store
+                .push(StoredPacket::new(sequence, CucTime::new(sequence, 0), vec![3, 25]))
+                .unwrap();
+        }
+        store.pop_oldest().unwrap();
+        drop(store);
+
+        // A torn record at the end of the file is discarded.
+        let mut contents = std::fs::read(&path).unwrap();
+        contents.extend_from_slice(&[0, 0, 0, 0]);
+        std::fs::write(&path, contents).unwrap();
+
+        let store = FilePacketStore::open(&path, 3).unwrap();
+        let packets = store.packets().unwrap();
+        assert_eq!(packets.iter().map(|packet| packet.sequence).collect::<Vec<_>>(), [1, 2]);
+
+        let responder = OnboardStorageServiceResponder::new(parent());
+        responder
+            .add_store(1, Box::new(store), OverwritePolicy::Circular)
+            .unwrap();
+        responder.add_routing(1, (3, 25));
+        responder.store(&[3, 25], CucTime::new(9, 0));
+        assert_eq!(sequences(&responder, 1), [1, 2, 3]);
+        drop(responder);
+
+        // Packets beyond a smaller capacity are removed from the file as well.
+        let store = FilePacketStore::open(&path, 2).unwrap();
+        assert_eq!(store.packets().unwrap().iter().map(|packet| packet.sequence).collect::<Vec<_>>(), [2, 3]);
+        drop(store);
+        let store = FilePacketStore::open(&path, 3).unwrap();
+        assert_eq!(store.packets().unwrap().iter().map(|packet| packet.sequence).collect::<Vec<_>>(), [2, 3]);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::ST20_parameter_management::{
+        Packet, Parameter, ParameterManagementServiceController, ParameterManagementServiceResponder, Parent,
+        RequestVerification,
+    };
+    use crate::storage::{ParameterStore, ParameterValues, PersistencePolicy, StoreError};
+    use std::boxed::Box;
+    use std::string::ToString;
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    type Sent = Arc<Mutex<Vec<(Vec<u8>, u32)>>>;
+
+    struct MockParent {
+        sent: Sent,
+        verification: MockVerification,
+    }
+
+    struct MockVerification {
+        sent: Sent,
+    }
+
+    impl RequestVerification for MockVerification {
+        fn send_success_acceptance_report(&self, args: &[u8]) {
+            self.sent.lock().unwrap().push(([&[1, 1], args].concat(), 0));
+        }
+
+        fn send_success_completion_report(&self, args: &[u8]) {
+            self.sent.lock().unwrap().push(([&[1, 7], args].concat(), 0));
+        }
+
+        fn send_fail_acceptance_report(&self, args: &[u8]) {
+            self.sent.lock().unwrap().push(([&[1, 2], args].concat(), 0));
+        }
+
+        fn send_fail_completion_report(&self, args: &[u8]) {
+            self.sent.lock().unwrap().push(([&[1, 8], args].concat(), 0));
+        }
+    }
+
+    impl Parent for MockParent {
+        fn send(&self, packet: Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+
+        fn request_verification(&self) -> &dyn RequestVerification {
+            &self.verification
+        }
+    }
+
+    fn parent() -> (Box<MockParent>, Sent) {
+        let sent: Sent = Arc::new(Mutex::new(Vec::new()));
+        let verification = MockVerification { sent: sent.clone() };
+        (Box::new(MockParent { sent: sent.clone(), verification }), sent)
+    }
+
+    #[derive(Clone, Default)]
+    struct MemoryStore {
+        values: Arc<Mutex<ParameterValues>>,
+        saves: Arc<Mutex<u32>>,
+        failing: bool,
+    }
+
+    impl ParameterStore for MemoryStore {
+        fn load(&mut self) -> Result<ParameterValues, StoreError> {
+            Ok(self.values.lock().unwrap().clone())
+        }
+
+        fn save(&mut self, values: &ParameterValues) -> Result<(), StoreError> {
+            if self.failing {
+                return Err(StoreError::Io);
+            }
+            *self.values.lock().unwrap() = values.clone();
+            *self.saves.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_responder_restores_and_saves_values() {
+        let store = MemoryStore::default();
+        store.values.lock().unwrap().insert((4, 1), 2.5);
+
+        let (mock, sent) = parent();
+        let responder = ParameterManagementServiceResponder::new(mock, 4);
+        responder
+            .attach_parameter_store(Box::new(store.clone()), PersistencePolicy::LoadAndSave)
+            .unwrap();
+        responder.add_parameter(1, "gain".to_string(), "!f".to_string(), 0.0);
+        responder.add_parameter(2, "mode".to_string(), "!B".to_string(), 1.0);
+        assert_eq!(responder.get_parameter_value(1), Some(2.5));
+
+        // Set parameter 2 to 3 (20,3); the change is saved.
+        responder.process(20, 3, vec![1, 0, 0, 0, 2, 3], 0);
+        assert_eq!(responder.get_parameter_value(2), Some(3.0));
+        assert_eq!(*store.saves.lock().unwrap(), 1);
+        assert_eq!(store.values.lock().unwrap().get(&(4, 2)), Some(&3.0));
+        assert_eq!(sent.lock().unwrap().split_off(0), [(vec![1, 1, 20, 3], 0), (vec![1, 7, 20, 3], 0)]);
+
+        // Unknown parameters are refused without changing anything.
+        responder.process(20, 3, vec![1, 0, 0, 0, 9, 3], 0);
+        assert_eq!(sent.lock().unwrap().split_off(0), [(vec![1, 2, 20, 3], 0)]);
+        assert_eq!(*store.saves.lock().unwrap(), 1);
+
+        // Report both values (20,1).
+        responder.process(20, 1, vec![2, 0, 0, 0, 1, 0, 0, 0, 2], 0);
+        let mut report = vec![20, 2, 2, 0, 0, 0, 1];
+        report.extend_from_slice(&2.5f32.to_be_bytes());
+        report.extend_from_slice(&[0, 0, 0, 2, 3]);
+        assert_eq!(sent.lock().unwrap()[1], (report.clone(), 0));
+
+        // The controller decodes the report into its pool.
+        let (mock, sent) = parent();
+        let mut controller = ParameterManagementServiceController::new(mock);
+        controller.add_parameter(Parameter::new((4, 1), "gain".to_string(), "!f".to_string(), 0.0));
+        controller.add_parameter(Parameter::new((4, 2), "mode".to_string(), "!B".to_string(), 0.0));
+        controller.process(20, 2, report[2..].to_vec(), 4).unwrap();
+        assert_eq!(controller.get_parameter_value((4, 1)), Some(2.5));
+        assert_eq!(controller.get_parameter_value((4, 2)), Some(3.0));
+
+        // A failed save is reported to the caller.
+        let store = MemoryStore { failing: true, ..Default::default() };
+        controller.attach_parameter_store(Box::new(store), PersistencePolicy::SaveOnChange).unwrap();
+        assert_eq!(controller.process(20, 2, report[2..].to_vec(), 4), Err(StoreError::Io));
+
+        assert!(controller.send_set_values(4, &[(2, 5.0)]));
+        assert!(!controller.send_set_values(4, &[(9, 5.0)]));
+        assert_eq!(sent.lock().unwrap()[..], [(vec![20, 3, 1, 0, 0, 0, 2, 5], 4)]);
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::ST04_parameter_statistics::{
+        Packet, ParameterStatisticsServiceController, ParameterStatisticsServiceResponder, Parent,
+        StatisticsDefinition,
+    };
+    use core::time::Duration;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<Vec<u8>>>,
+        parameters: Mutex<BTreeMap<u32, f64>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, _node_id: u32) {
+            self.sent.lock().unwrap().push(packet.data);
+        }
+
+        fn get_parameter_value(&self, parameter_id: u32) -> Option<f64> {
+            self.parameters.lock().unwrap().get(&parameter_id).copied()
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+            parameters: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    fn take_sent(parent: &RecordingParent) -> Vec<Vec<u8>> {
+        parent.sent.lock().unwrap().drain(..).collect()
+    }
+
+    #[test]
+    fn test_statistics_accumulate_per_sampling_interval() {
+        let parent = parent();
+        let responder = ParameterStatisticsServiceResponder::new(parent.clone());
+        responder.add_definition(StatisticsDefinition::new(1, Duration::from_secs(1)));
+
+        for (second, value) in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0].into_iter().enumerate() {
+            parent.parameters.lock().unwrap().insert(1, value);
+            responder.poll(Duration::from_secs(second as u64));
+            // Not due yet, so this value is never sampled.
+            parent.parameters.lock().unwrap().insert(1, 100.0);
+            responder.poll(Duration::from_millis(second as u64 * 1000 + 500));
+        }
+        let statistics = responder.statistics()[0];
+        assert_eq!(statistics.samples, 8);
+        assert_eq!(statistics.min, 2.0);
+        assert_eq!(statistics.max, 9.0);
+        assert_eq!(statistics.mean, 5.0);
+        assert_eq!(statistics.standard_deviation, 2.0);
+
+        responder.reset(Duration::from_secs(9));
+        assert_eq!(responder.statistics()[0].samples, 0);
+        assert!(take_sent(&parent).is_empty());
+    }
+
+    #[test]
+    fn test_reports_over_the_bus() {
+        let parent = parent();
+        let controller = ParameterStatisticsServiceController::new(parent.clone());
+        let responder = ParameterStatisticsServiceResponder::new(parent.clone());
+        parent.parameters.lock().unwrap().insert(7, 1.5);
+
+        controller.send_add_definitions(2, &[StatisticsDefinition::new(7, Duration::from_millis(100))]);
+        controller.send_enable_periodic_reporting(2, Duration::from_secs(1));
+        for request in take_sent(&parent) {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        for tick in 0..=10 {
+            responder.poll(Duration::from_millis(tick * 100));
+        }
+
+        let reports = take_sent(&parent);
+        assert_eq!(reports.len(), 1);
+        controller.process(reports[0][0], reports[0][1], reports[0][2..].to_vec(), 2);
+        let report = controller.last_report(2).unwrap();
+        assert_eq!(report.start, Duration::ZERO);
+        assert_eq!(report.end, Duration::from_secs(1));
+        assert_eq!(report.statistics[0].parameter_id, 7);
+        assert_eq!(report.statistics[0].samples, 11);
+        assert_eq!(report.statistics[0].mean, 1.5);
+        assert_eq!(responder.statistics()[0].samples, 0);
+
+        controller.send_disable_periodic_reporting(2);
+        controller.send_definitions_request(2);
+        for request in take_sent(&parent) {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        assert_eq!(responder.periodic_reporting(), None);
+        let report = take_sent(&parent).remove(0);
+        controller.process(report[0], report[1], report[2..].to_vec(), 2);
+        assert_eq!(
+            controller.definitions(2).unwrap(),
+            [StatisticsDefinition::new(7, Duration::from_millis(100))]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::can_frame::{CanFrame, CanFrameError};
+    use crate::primitives::network::{Bus, BusSelection, Network, Parent};
+    use crate::primitives::redundancy::{RedundancyManager, SwitchPolicy, SwitchReason};
+    use crate::primitives::scheduler::{Duration, Instant, SimulatedClock};
+    use std::sync::{Arc, Mutex};
+    use std::vec::Vec;
+
+    struct NullParent;
+
+    impl Parent for NullParent {
+        fn received_frame(&self, _can_frame: CanFrame) {}
+
+        fn send(&self, _can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockBus {
+        receiving: Mutex<bool>,
+        failing: Mutex<bool>,
+        inbox: Mutex<Vec<CanFrame>>,
+    }
+
+    impl Bus for MockBus {
+        fn flush_frame_buffer(&self) {
+            self.inbox.lock().unwrap().clear();
+        }
+
+        fn start_receive(&self) {
+            *self.receiving.lock().unwrap() = true;
+        }
+
+        fn stop_receive(&self) {
+            *self.receiving.lock().unwrap() = false;
+        }
+
+        fn send(&self, _can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            if *self.failing.lock().unwrap() {
+                return Err(CanFrameError::SendFailed);
+            }
+            Ok(())
+        }
+
+        fn get_frame(&self) -> Option<CanFrame> {
+            self.inbox.lock().unwrap().pop()
+        }
+    }
+
+    fn at(millis: u64) -> Instant {
+        Instant::from_ticks(millis * 1000)
+    }
+
+    #[test]
+    fn test_policies() {
+        let mut manager = RedundancyManager::new(SwitchPolicy::HeartbeatMiss { max_missed: 2 });
+        manager.record_heartbeat(BusSelection::A, at(0));
+        assert_eq!(manager.record_heartbeat_missed(at(100)), None);
+        let switch = manager.record_heartbeat_missed(at(200)).unwrap();
+        assert_eq!((switch.from, switch.to, switch.reason), (BusSelection::A, BusSelection::B, SwitchReason::HeartbeatMissed));
+        assert!(!manager.is_nominal());
+        assert_eq!(manager.redundant(), BusSelection::B);
+        assert_eq!(manager.health(BusSelection::A).missed_heartbeats, 2);
+        assert_eq!(manager.health(BusSelection::A).last_heartbeat, Some(at(0)));
+
+        // Errors on the other bus do not count towards the selected one.
+        manager.set_policy(SwitchPolicy::ErrorThreshold { max_errors: 3 });
+        manager.set_max_switches(Some(2));
+        manager.record_error(BusSelection::A, at(300));
+        manager.record_error(BusSelection::B, at(300));
+        manager.record_tx_failure(BusSelection::B, at(310));
+        let switch = manager.record_tx_failure(BusSelection::B, at(320)).unwrap();
+        assert_eq!((switch.to, switch.reason), (BusSelection::A, SwitchReason::ErrorThreshold));
+        assert_eq!(manager.health(BusSelection::B).errors, 1);
+        assert_eq!(manager.health(BusSelection::B).tx_failures, 2);
+        // Bus A's earlier error was cleared when it was selected again.
+        assert_eq!(manager.health(BusSelection::A).recent_errors, 0);
+
+        // The automatic switch limit is reached; commanded switches still work.
+        for _ in 0..3 {
+            assert_eq!(manager.record_error(BusSelection::A, at(400)), None);
+        }
+        manager.set_policy(SwitchPolicy::ManualOnly);
+        assert_eq!(manager.switch(at(500)).reason, SwitchReason::Manual);
+        assert_eq!(manager.select(BusSelection::B, at(600)), None);
+        assert_eq!(manager.history().len(), 3);
+        assert_eq!(manager.history()[2].time, at(500));
+    }
+
+    #[test]
+    fn test_network_switches_back_and_forth() {
+        let clock = Arc::new(SimulatedClock::new());
+        let redundancy = RedundancyManager::new(SwitchPolicy::ErrorThreshold { max_errors: 2 });
+        let network = Network::with_redundancy(
+            Arc::new(NullParent),
+            3,
+            MockBus::default(),
+            MockBus::default(),
+            redundancy,
+            clock.clone(),
+        );
+        network.start();
+        assert!(*network.bus(BusSelection::A).receiving.lock().unwrap());
+
+        clock.advance(Duration::millis(10));
+        assert_eq!(network.switch_bus().to, BusSelection::B);
+        assert_eq!(network.switch_bus().to, BusSelection::A);
+        assert_eq!(network.switch_bus().to, BusSelection::B);
+        assert!(*network.bus(BusSelection::B).receiving.lock().unwrap());
+        assert!(!*network.bus(BusSelection::A).receiving.lock().unwrap());
+
+        // A stalled frame counts once; with a bus error on bus B the network moves back to the nominal bus.
+        *network.bus(BusSelection::B).failing.lock().unwrap() = true;
+        let frame = CanFrame::new(0x303, None).unwrap();
+        assert!(network.send(&frame).is_ok());
+        assert!(network.send(&frame).is_ok());
+        assert_eq!(network.selected_bus(), BusSelection::B);
+        assert_eq!(network.bus_health(BusSelection::B).tx_failures, 1);
+        assert!(network.bus_error(BusSelection::B).is_some());
+        assert_eq!(network.selected_bus(), network.nominal_bus());
+        assert_eq!(network.transmit(), Ok(()));
+        assert_eq!(network.queued_frames(), 0);
+
+        // Heartbeats received on the selected bus are recorded.
+        network.bus(BusSelection::A).inbox.lock().unwrap().push(CanFrame::new(0x700, None).unwrap());
+        network.process();
+        assert_eq!(network.bus_health(BusSelection::A).last_heartbeat, Some(at(10)));
+
+        let history = network.switch_history();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[3].reason, SwitchReason::ErrorThreshold);
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::can_frame::{CanFrame, CanFrameError};
+    use crate::primitives::heartbeat::HeartbeatProducer;
+    use crate::primitives::network::Parent;
+    use crate::primitives::scheduler::{Duration, Instant, Scheduler, SimulatedClock};
+    use crate::primitives::sync::SyncProducer;
+    use crate::services::ST03_housekeeping::{self as housekeeping, HousekeepingServiceResponder, Parameter};
+    use std::string::ToString;
+    use std::boxed::Box;
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    fn set(clock: &SimulatedClock, millis: u64) {
+        clock.set(Instant::from_ticks(millis * 1000));
+    }
+
+    #[derive(Default)]
+    struct MockNetwork {
+        sent: Mutex<Vec<u32>>,
+    }
+
+    impl housekeeping::Parent for MockNetwork {
+        fn send(&self, packet: housekeeping::Packet) {
+            // Record reports in the frame log by the telemetry CAN ID of node 1.
+            assert_eq!(packet.data, [3, 25, 1, 0, 0x2A]);
+            self.sent.lock().unwrap().push(0x301);
+        }
+
+        fn get_parameter(&self, parameter_id: (u32, u32)) -> Parameter {
+            Parameter::new("!H".to_string(), parameter_id.1 as f64)
+        }
+    }
+
+    impl Parent for MockNetwork {
+        fn received_frame(&self, _can_frame: CanFrame) {}
+
+        fn send(&self, can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            self.sent.lock().unwrap().push(can_frame.can_id());
+            Ok(())
+        }
+    }
+
+    fn scheduler() -> (Arc<SimulatedClock>, Scheduler<Arc<SimulatedClock>>) {
+        let clock = Arc::new(SimulatedClock::new());
+        (clock.clone(), Scheduler::new(clock))
+    }
+
+    #[test]
+    fn test_periodic_and_one_shot_tasks() {
+        let (clock, mut scheduler) = scheduler();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let periodic = {
+            let log = log.clone();
+            let mut runs = 0;
+            scheduler.add_periodic(
+                Duration::millis(100),
+                Box::new(move |now: Instant| {
+                    runs += 1;
+                    log.lock().unwrap().push(('p', runs, now.duration_since_epoch().to_millis()));
+                }),
+            )
+        };
+        let once = {
+            let log = log.clone();
+            scheduler.add_once(
+                Duration::millis(100),
+                Box::new(move |now: Instant| log.lock().unwrap().push(('o', 1, now.duration_since_epoch().to_millis()))),
+            )
+        };
+        assert_eq!(scheduler.next_due(), Some(Instant::from_ticks(100_000)));
+
+        set(&clock, 99);
+        assert_eq!(scheduler.poll(), 0);
+        set(&clock, 100);
+        // Tasks due at the same time run in registration order.
+        assert_eq!(scheduler.poll(), 2);
+        assert!(!scheduler.contains(once));
+        // A late poll runs the task once and skips the missed periods.
+        set(&clock, 450);
+        assert_eq!(scheduler.poll(), 1);
+        assert_eq!(scheduler.next_due(), Some(Instant::from_ticks(500_000)));
+        set(&clock, 500);
+        assert_eq!(scheduler.poll(), 1);
+        assert_eq!(
+            log.lock().unwrap()[..],
+            [('p', 1, 100), ('o', 1, 100), ('p', 2, 450), ('p', 3, 500)]
+        );
+
+        assert!(scheduler.remove(periodic));
+        assert!(scheduler.is_empty());
+        set(&clock, 1000);
+        assert_eq!(scheduler.poll(), 0);
+    }
+
+    #[test]
+    fn test_poll_drives_heartbeat_sync_and_housekeeping() {
+        let (clock, mut scheduler) = scheduler();
+        let network = Arc::new(MockNetwork::default());
+        let mut heartbeat = HeartbeatProducer::new(network.clone()).unwrap();
+        let mut sync = SyncProducer::new(network.clone()).unwrap();
+        let mut housekeeping = HousekeepingServiceResponder::new(network.clone());
+        housekeeping.define_housekeeping_report((1, 1), 0.4, true, vec![(1, 42)]);
+        housekeeping.define_housekeeping_report((1, 2), 0.1, false, vec![(1, 43)]);
+        heartbeat.start(&mut scheduler, Duration::millis(200));
+        sync.start(&mut scheduler, Duration::millis(100));
+        housekeeping.start(&mut scheduler);
+
+        for millis in (0..=400).step_by(50) {
+            set(&clock, millis);
+            scheduler.poll();
+        }
+        assert_eq!(network.sent.lock().unwrap()[..], [0x080, 0x700, 0x080, 0x080, 0x700, 0x080, 0x301]);
+
+        heartbeat.stop(&mut scheduler);
+        housekeeping.stop(&mut scheduler);
+        assert!(!heartbeat.is_running());
+        assert!(!housekeeping.is_running());
+        assert_eq!(scheduler.len(), 1);
+        // Restarting replaces the running task.
+        sync.start(&mut scheduler, Duration::millis(50));
+        assert_eq!(scheduler.len(), 1);
+    }
+}
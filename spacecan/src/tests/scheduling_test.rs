@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::time::CucTime;
+    use crate::services::ST11_time_based_scheduling::{
+        Packet, Parent, RequestVerification, Schedule, ScheduleError, ScheduleSummaryEntry, ScheduledActivity,
+        TimeBasedSchedulingServiceController, TimeBasedSchedulingServiceResponder,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<(Vec<u8>, u32)>>,
+        dispatched: Mutex<Vec<Vec<u8>>>,
+        verification: RecordingVerification,
+    }
+
+    #[derive(Default)]
+    struct RecordingVerification {
+        reports: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl RecordingVerification {
+        fn record(&self, subtype: u8, args: &[u8]) {
+            let mut report = vec![1, subtype];
+            report.extend_from_slice(args);
+            self.reports.lock().unwrap().push(report);
+        }
+    }
+
+    impl RequestVerification for RecordingVerification {
+        fn send_success_acceptance_report(&self, args: &[u8]) {
+            self.record(1, args);
+        }
+        fn send_success_completion_report(&self, args: &[u8]) {
+            self.record(7, args);
+        }
+        fn send_fail_acceptance_report(&self, args: &[u8]) {
+            self.record(2, args);
+        }
+        fn send_fail_completion_report(&self, args: &[u8]) {
+            self.record(8, args);
+        }
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+
+        fn dispatch(&self, data: Vec<u8>, _node_id: u32) {
+            self.dispatched.lock().unwrap().push(data);
+        }
+
+        fn request_verification(&self) -> &dyn RequestVerification {
+            &self.verification
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent {
+            sent: Mutex::new(Vec::new()),
+            dispatched: Mutex::new(Vec::new()),
+            verification: RecordingVerification::default(),
+        })
+    }
+
+    fn activity(request_id: u16, coarse: u32) -> ScheduledActivity {
+        ScheduledActivity::new(request_id, CucTime::new(coarse, 0), vec![17, 1, request_id as u8])
+    }
+
+    #[test]
+    fn test_schedule_orders_by_release_time() {
+        let mut schedule = Schedule::new(3);
+        schedule.insert(activity(1, 30)).unwrap();
+        schedule.insert(activity(2, 10)).unwrap();
+        schedule.insert(activity(3, 10)).unwrap();
+        assert_eq!(schedule.insert(activity(4, 5)), Err(ScheduleError::Full));
+        schedule.remove(1).unwrap();
+        assert_eq!(schedule.insert(activity(2, 5)), Err(ScheduleError::DuplicateRequestId(2)));
+
+        let ids: Vec<u16> = schedule.activities().iter().map(|a| a.request_id).collect();
+        assert_eq!(ids, [2, 3]);
+
+        schedule.time_shift(2, 15_000).unwrap();
+        assert_eq!(schedule.next_release_time(), Some(CucTime::new(10, 0)));
+        assert_eq!(schedule.get(2).unwrap().release_time, CucTime::new(25, 0));
+    }
+
+    #[test]
+    fn test_responder_releases_due_activities() {
+        let parent = parent();
+        let controller = TimeBasedSchedulingServiceController::new(parent.clone());
+        controller.send_insert_activities(3, &[activity(1, 100), activity(2, 200)]);
+        let insert = parent.sent.lock().unwrap().remove(0).0;
+
+        let responder = TimeBasedSchedulingServiceResponder::new(parent.clone());
+        responder.process(insert[0], insert[1], insert[2..].to_vec(), 0);
+        assert_eq!(responder.schedule().len(), 2);
+
+        assert_eq!(responder.poll(CucTime::new(99, 0)), 0);
+        assert_eq!(responder.poll(CucTime::new(100, 0)), 1);
+        assert_eq!(parent.dispatched.lock().unwrap()[0], [17, 1, 1]);
+
+        responder.process(11, 2, vec![], 0);
+        assert_eq!(responder.poll(CucTime::new(300, 0)), 0);
+        responder.process(11, 1, vec![], 0);
+        assert_eq!(responder.poll(CucTime::new(300, 0)), 1);
+        assert!(responder.schedule().is_empty());
+    }
+
+    #[test]
+    fn test_delete_time_shift_and_summary() {
+        let parent = parent();
+        let controller = TimeBasedSchedulingServiceController::new(parent.clone());
+        let responder = TimeBasedSchedulingServiceResponder::new(parent.clone());
+        for request_id in 1..=3 {
+            responder.insert(activity(request_id, 100 * request_id as u32)).unwrap();
+        }
+
+        controller.send_delete_activities(3, &[2]);
+        controller.send_time_shift_all(3, -50_000);
+        controller.send_time_shift_activities(3, 1_000, &[1]);
+        controller.send_summary_report_all(3);
+        let requests: Vec<Vec<u8>> = parent.sent.lock().unwrap().drain(..).map(|(data, _)| data).collect();
+        for request in requests {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+
+        let report = parent.sent.lock().unwrap().remove(0);
+        assert_eq!(report.0[..3], [11, 13, 2]);
+        assert_eq!(report.1, 0);
+        controller.process(report.0[0], report.0[1], report.0[2..].to_vec(), 3);
+        assert_eq!(
+            controller.schedule_summary(3).unwrap(),
+            [
+                ScheduleSummaryEntry {
+                    request_id: 1,
+                    release_time: CucTime::new(51, 0)
+                },
+                ScheduleSummaryEntry {
+                    request_id: 3,
+                    release_time: CucTime::new(250, 0)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_changes_are_reported() {
+        let parent = parent();
+        let controller = TimeBasedSchedulingServiceController::new(parent.clone());
+        let responder = TimeBasedSchedulingServiceResponder::with_capacity(parent.clone(), 1);
+        responder.insert(activity(1, 100)).unwrap();
+
+        controller.send_insert_activities(3, &[activity(1, 200), activity(2, 200)]);
+        controller.send_delete_activities(3, &[7]);
+        controller.send_time_shift_activities(3, 1_000, &[1, 8]);
+        controller.send_delete_activities(3, &[1]);
+        let requests: Vec<Vec<u8>> = parent.sent.lock().unwrap().drain(..).map(|(data, _)| data).collect();
+        for request in requests {
+            responder.process(request[0], request[1], request[2..].to_vec(), 0);
+        }
+        // Malformed requests are not accepted.
+        responder.process(11, 7, vec![0, 0], 0);
+        responder.process(11, 5, vec![2, 0, 1], 0);
+        assert!(responder.schedule().is_empty());
+        assert!(parent.sent.lock().unwrap().is_empty());
+
+        assert_eq!(
+            *parent.verification.reports.lock().unwrap(),
+            [
+                [1, 1, 11, 4],
+                [1, 8, 11, 4],
+                [1, 1, 11, 5],
+                [1, 8, 11, 5],
+                [1, 1, 11, 7],
+                [1, 8, 11, 7],
+                [1, 1, 11, 5],
+                [1, 7, 11, 5],
+                [1, 2, 11, 7],
+                [1, 2, 11, 5],
+            ]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::can_frame::{CanFrame, CanFrameError};
+    use crate::primitives::heartbeat::{HeartbeatConsumer, HeartbeatProducer};
+    use crate::primitives::network::{Bus, BusSelection, Network, Parent};
+    use crate::primitives::redundancy::{RedundancyManager, SwitchPolicy, SwitchReason};
+    use crate::primitives::scheduler::{Duration as Ticks, Instant, Scheduler, SimulatedClock};
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use std::boxed::Box;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use std::vec::Vec;
+
+    /// Delivers sent heartbeats straight to the responder's consumer.
+    struct Loopback {
+        consumer: Mutex<Option<Arc<HeartbeatConsumer>>>,
+        sent: AtomicU32,
+    }
+
+    impl Parent for Loopback {
+        fn received_frame(&self, _can_frame: CanFrame) {}
+
+        fn send(&self, _can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            self.sent.fetch_add(1, Ordering::Relaxed);
+            if let Some(consumer) = &*self.consumer.lock().unwrap() {
+                consumer.receive_heartbeat();
+            }
+            Ok(())
+        }
+    }
+
+    struct NullParent;
+
+    impl Parent for NullParent {
+        fn received_frame(&self, _can_frame: CanFrame) {}
+
+        fn send(&self, _can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            Ok(())
+        }
+    }
+
+    struct NullBus;
+
+    impl Bus for NullBus {
+        fn flush_frame_buffer(&self) {}
+
+        fn start_receive(&self) {}
+
+        fn stop_receive(&self) {}
+
+        fn send(&self, _can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            Ok(())
+        }
+
+        fn get_frame(&self) -> Option<CanFrame> {
+            None
+        }
+    }
+
+    /// Advances the clock in steps of 10 ms, polling the scheduler after each step.
+    fn run_for(clock: &SimulatedClock, scheduler: &mut Scheduler<Arc<SimulatedClock>>, millis: u64) {
+        for _ in 0..millis / 10 {
+            clock.advance(Ticks::millis(10));
+            scheduler.poll();
+        }
+    }
+
+    #[test]
+    fn test_heartbeats_and_bus_switch_in_simulated_time() {
+        let clock = Arc::new(SimulatedClock::new());
+        let mut scheduler = Scheduler::new(clock.clone());
+        let network = Arc::new(Loopback {
+            consumer: Mutex::new(None),
+            sent: AtomicU32::new(0),
+        });
+
+        // The responder's network switches buses on every missed heartbeat.
+        let responder = Arc::new(Network::with_redundancy(
+            Arc::new(NullParent),
+            5,
+            NullBus,
+            NullBus,
+            RedundancyManager::new(SwitchPolicy::HeartbeatMiss { max_missed: 1 }),
+            clock.clone(),
+        ));
+        let mut consumer = HeartbeatConsumer::with_clock(Duration::from_millis(2500), clock.clone());
+        {
+            let responder = responder.clone();
+            consumer.set_timeout_handler(Arc::new(move || {
+                responder.heartbeat_missed();
+            }));
+        }
+        let consumer = Arc::new(consumer);
+        *network.consumer.lock().unwrap() = Some(consumer.clone());
+        {
+            let consumer = consumer.clone();
+            scheduler.add_periodic(Ticks::millis(100), Box::new(move |_| {
+                consumer.poll();
+            }));
+        }
+
+        let mut producer = HeartbeatProducer::new(network.clone()).unwrap();
+        producer.start(&mut scheduler, Ticks::secs(1));
+        run_for(&clock, &mut scheduler, 10_000);
+        assert_eq!(network.sent.load(Ordering::Relaxed), 10);
+        assert!(!consumer.check_timeout());
+        assert!(responder.switch_history().is_empty());
+
+        // The controller stops after its heartbeat at 10 s: the responder
+        // switches bus once per timeout, polled every 100 ms.
+        producer.stop(&mut scheduler);
+        run_for(&clock, &mut scheduler, 3_000);
+        assert_eq!(responder.selected_bus(), BusSelection::B);
+        run_for(&clock, &mut scheduler, 3_000);
+        assert_eq!(network.sent.load(Ordering::Relaxed), 10);
+        assert_eq!(consumer.timeouts(), 2);
+        assert_eq!(responder.selected_bus(), BusSelection::A);
+        let switches: Vec<_> = responder
+            .switch_history()
+            .iter()
+            .map(|switch| (switch.time, switch.to, switch.reason))
+            .collect();
+        assert_eq!(
+            switches,
+            [
+                (Instant::from_ticks(12_600_000), BusSelection::B, SwitchReason::HeartbeatMissed),
+                (Instant::from_ticks(15_200_000), BusSelection::A, SwitchReason::HeartbeatMissed),
+            ]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::can_frame::{CanFrame, CanFrameError};
+    use crate::primitives::network::{Bus, BusSelection, Network, Parent};
+    use crate::primitives::redundancy::{RedundancyManager, SwitchPolicy};
+    use crate::primitives::scheduler::{Duration, SimulatedClock};
+    use crate::primitives::statistics::{frame_bits, function_code, ControllerErrorCounters};
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct NullParent;
+
+    impl Parent for NullParent {
+        fn received_frame(&self, _can_frame: CanFrame) {}
+
+        fn send(&self, _can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingBus {
+        failing: Mutex<bool>,
+        inbox: Mutex<Vec<CanFrame>>,
+        counters: Option<ControllerErrorCounters>,
+    }
+
+    impl Bus for CountingBus {
+        fn flush_frame_buffer(&self) {}
+
+        fn start_receive(&self) {}
+
+        fn stop_receive(&self) {}
+
+        fn send(&self, _can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            if *self.failing.lock().unwrap() {
+                return Err(CanFrameError::SendFailed);
+            }
+            Ok(())
+        }
+
+        fn get_frame(&self) -> Option<CanFrame> {
+            self.inbox.lock().unwrap().pop()
+        }
+
+        fn error_counters(&self) -> Option<ControllerErrorCounters> {
+            self.counters
+        }
+    }
+
+    #[test]
+    fn test_frame_bits() {
+        assert_eq!(function_code(0x285), 5);
+        assert_eq!(frame_bits(0), 55);
+        assert_eq!(frame_bits(8), 135);
+    }
+
+    #[test]
+    fn test_network_statistics() {
+        let clock = Arc::new(SimulatedClock::new());
+        let counters = ControllerErrorCounters {
+            transmit_errors: 8,
+            receive_errors: 1,
+            bus_off_events: 1,
+        };
+        let bus_b = CountingBus {
+            counters: Some(counters),
+            ..Default::default()
+        };
+        let network = Network::with_redundancy(
+            Arc::new(NullParent),
+            0,
+            CountingBus::default(),
+            bus_b,
+            RedundancyManager::new(SwitchPolicy::ManualOnly),
+            clock.clone(),
+        );
+        network.set_bitrate(125_000);
+
+        network.send(&CanFrame::new(0x285, Some(vec![0; 8])).unwrap()).unwrap();
+        network.send(&CanFrame::new(0x080, None).unwrap()).unwrap();
+        network.bus(BusSelection::A).inbox.lock().unwrap().push(CanFrame::new(0x305, Some(vec![0; 4])).unwrap());
+        network.process();
+        *network.bus(BusSelection::A).failing.lock().unwrap() = true;
+        network.send(&CanFrame::new(0x286, Some(vec![1])).unwrap()).unwrap();
+        // Retrying the stalled frame does not count another failure.
+        assert_eq!(network.transmit(), Err(CanFrameError::SendFailed));
+        network.reassembly_error(5);
+        clock.advance(Duration::millis(10));
+
+        let statistics = network.statistics();
+        let bus_a = statistics.bus(BusSelection::A);
+        assert_eq!((bus_a.by_function[5].frames_sent, bus_a.by_function[5].bytes_sent), (1, 8));
+        assert_eq!((bus_a.by_function[6].frames_received, bus_a.by_function[6].bytes_received), (1, 4));
+        assert_eq!(bus_a.total().frames_sent, 2);
+        assert_eq!(bus_a.send_failures, 1);
+        assert_eq!(bus_a.bits, (frame_bits(8) + frame_bits(0) + frame_bits(4)) as u64);
+        // 285 bits in 10 ms at 125 kbit/s.
+        assert!((bus_a.bus_load - 0.228).abs() < 1e-4);
+        assert_eq!(bus_a.controller, None);
+        assert_eq!(statistics.bus(BusSelection::B).controller, Some(counters));
+
+        let node = statistics.node(5);
+        assert_eq!((node.traffic.frames_sent, node.traffic.frames_received), (1, 1));
+        assert_eq!(node.reassembly_errors, 1);
+        assert_eq!(statistics.node(6).send_failures, 1);
+        assert_eq!(statistics.node(0).traffic.frames_sent, 1);
+
+        network.reset_statistics();
+        let statistics = network.statistics();
+        assert_eq!(statistics.bus(BusSelection::A).bits, 0);
+        assert!(statistics.nodes.is_empty());
+        assert_eq!(statistics.bitrate, 125_000);
+    }
+
+    #[test]
+    fn test_packet_frames_out_of_sequence_are_reassembly_errors() {
+        let network = Network::with_redundancy(
+            Arc::new(NullParent),
+            0,
+            CountingBus::default(),
+            CountingBus::default(),
+            RedundancyManager::new(SwitchPolicy::ManualOnly),
+            Arc::new(SimulatedClock::new()),
+        );
+        let frames = [
+            (0x305, vec![2, 0, 1]),
+            (0x306, vec![0, 0, 1]),
+            (0x305, vec![2, 1, 1]),
+            (0x305, vec![2, 2, 1]),
+            // Frame 1 of the second packet is lost.
+            (0x305, vec![2, 0, 1]),
+            (0x305, vec![2, 2, 1]),
+            // The third packet breaks off when the fourth starts.
+            (0x285, vec![1, 0, 1]),
+            (0x285, vec![1, 0, 1]),
+            (0x285, vec![1, 1, 1]),
+            (0x306, vec![1]),
+            (0x080, vec![1]),
+        ];
+        for (can_id, data) in frames.into_iter().rev() {
+            network.bus(BusSelection::A).inbox.lock().unwrap().push(CanFrame::new(can_id, Some(data)).unwrap());
+        }
+        while !network.bus(BusSelection::A).inbox.lock().unwrap().is_empty() {
+            network.process();
+        }
+
+        let statistics = network.statistics();
+        assert_eq!(statistics.node(5).reassembly_errors, 2);
+        assert_eq!(statistics.node(6).reassembly_errors, 1);
+        assert_eq!(statistics.node(0).reassembly_errors, 0);
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod tests {
+    use std::vec;
+    use std::vec::Vec;
+    use crate::storage::flash::{FlashParameterStore, FlashSectors};
+    use crate::storage::file::FileParameterStore;
+    use crate::storage::{decode_record, encode_record, ParameterStore, ParameterValues, StoreError};
+
+    const SECTOR_SIZE: usize = 128;
+
+    struct RamFlash {
+        sectors: Vec<Vec<u8>>,
+        erase_counts: Vec<u32>,
+    }
+
+    impl RamFlash {
+        fn new(count: usize) -> Self {
+            RamFlash {
+                sectors: vec![vec![0xFF; SECTOR_SIZE]; count],
+                erase_counts: vec![0; count],
+            }
+        }
+    }
+
+    impl FlashSectors for RamFlash {
+        fn sector_count(&self) -> usize {
+            self.sectors.len()
+        }
+
+        fn sector_size(&self) -> usize {
+            SECTOR_SIZE
+        }
+
+        fn read(&self, sector: usize, offset: usize, buf: &mut [u8]) {
+            buf.copy_from_slice(&self.sectors[sector][offset..offset + buf.len()]);
+        }
+
+        fn erase(&mut self, sector: usize) -> Result<(), StoreError> {
+            self.sectors[sector].fill(0xFF);
+            self.erase_counts[sector] += 1;
+            Ok(())
+        }
+
+        fn program(&mut self, sector: usize, offset: usize, data: &[u8]) -> Result<(), StoreError> {
+            self.sectors[sector][offset..offset + data.len()].copy_from_slice(data);
+            Ok(())
+        }
+    }
+
+    fn values(value: f64) -> ParameterValues {
+        let mut values = ParameterValues::new();
+        values.insert((1, 1), value);
+        values.insert((1, 2), -value);
+        values
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        let record = encode_record(7, &values(1.5)).unwrap();
+        let (sequence, decoded) = decode_record(&record).unwrap();
+        assert_eq!(sequence, 7);
+        assert_eq!(decoded, values(1.5));
+    }
+
+    #[test]
+    fn test_record_crc_mismatch() {
+        let mut record = encode_record(1, &values(1.5)).unwrap();
+        record[12] ^= 0x01;
+        assert_eq!(decode_record(&record), Err(StoreError::Corrupted));
+    }
+
+    #[test]
+    fn test_record_layout_mismatch() {
+        let mut record = encode_record(1, &values(1.5)).unwrap();
+        record[2] = 99;
+        assert_eq!(decode_record(&record), Err(StoreError::LayoutMismatch(99)));
+    }
+
+    #[test]
+    fn test_flash_store_returns_latest_record() {
+        let mut store = FlashParameterStore::new(RamFlash::new(3)).unwrap();
+        assert!(store.load().unwrap().is_empty());
+
+        for i in 0..10 {
+            store.save(&values(i as f64)).unwrap();
+        }
+
+        let mut reloaded = FlashParameterStore::new(store.free()).unwrap();
+        assert_eq!(reloaded.load().unwrap(), values(9.0));
+        assert_eq!(reloaded.sequence(), 10);
+    }
+
+    #[test]
+    fn test_flash_store_needs_two_sectors() {
+        assert!(matches!(FlashParameterStore::new(RamFlash::new(0)), Err(StoreError::TooFewSectors)));
+        assert!(matches!(FlashParameterStore::new(RamFlash::new(1)), Err(StoreError::TooFewSectors)));
+    }
+
+    #[test]
+    fn test_flash_store_spreads_erases() {
+        let mut store = FlashParameterStore::new(RamFlash::new(3)).unwrap();
+        for i in 0..30 {
+            store.save(&values(i as f64)).unwrap();
+        }
+        let flash = store.free();
+        let max = *flash.erase_counts.iter().max().unwrap();
+        let min = *flash.erase_counts.iter().min().unwrap();
+        assert!(max - min <= 1);
+    }
+
+    #[test]
+    fn test_flash_store_skips_damaged_record() {
+        let mut store = FlashParameterStore::new(RamFlash::new(2)).unwrap();
+        store.save(&values(1.0)).unwrap();
+        store.save(&values(2.0)).unwrap();
+
+        // Corrupt the value of the second record in sector 0.
+        let mut flash = store.free();
+        flash.sectors[0][62] ^= 0xFF;
+
+        let mut reloaded = FlashParameterStore::new(flash).unwrap();
+        assert_eq!(reloaded.load().unwrap(), values(1.0));
+    }
+
+    #[test]
+    fn test_file_store_roundtrip() {
+        let path = std::env::temp_dir().join("spacecan_storage_test.bin");
+        let _ = std::fs::remove_file(&path);
+
+        let mut store = FileParameterStore::new(&path);
+        assert!(store.load().unwrap().is_empty());
+        store.save(&values(3.25)).unwrap();
+
+        let mut reloaded = FileParameterStore::new(&path);
+        assert_eq!(reloaded.load().unwrap(), values(3.25));
+        assert_eq!(reloaded.sequence(), 1);
+        let _ = std::fs::remove_file(&path);
+    }
+}
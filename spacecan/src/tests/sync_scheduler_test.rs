@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::scheduler::{Instant, SimulatedClock};
+    use crate::primitives::sync::SyncScheduler;
+    use core::time::Duration;
+    use std::boxed::Box;
+    use std::sync::{Arc, Mutex};
+    use std::vec::Vec;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn scheduler() -> (SyncScheduler, Arc<SimulatedClock>) {
+        let clock = Arc::new(SimulatedClock::new());
+        (SyncScheduler::with_clock(ms(100), clock.clone()), clock)
+    }
+
+    fn at(clock: &SimulatedClock, millis: u64) {
+        clock.set(Instant::from_ticks(millis * 1000));
+    }
+
+    #[test]
+    fn test_actions_run_every_nth_sync() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (mut scheduler, clock) = scheduler();
+        let every_sync = {
+            let log = log.clone();
+            scheduler.add_action(1, Box::new(move |count| log.lock().unwrap().push(('a', count))))
+        };
+        {
+            let log = log.clone();
+            scheduler.add_action_with_offset(3, 1, Box::new(move |count| log.lock().unwrap().push(('b', count))));
+        }
+
+        for count in 0..5 {
+            at(&clock, count * 100);
+            scheduler.received_sync();
+        }
+        assert_eq!(
+            log.lock().unwrap()[..],
+            [('a', 0), ('a', 1), ('b', 1), ('a', 2), ('a', 3), ('a', 4), ('b', 4)]
+        );
+
+        assert!(scheduler.remove_action(every_sync));
+        assert!(!scheduler.remove_action(every_sync));
+        at(&clock, 500);
+        assert_eq!(scheduler.received_sync(), 0);
+        assert_eq!(scheduler.count(), 6);
+    }
+
+    #[test]
+    fn test_jitter_and_missed_syncs() {
+        let (mut scheduler, clock) = scheduler();
+        at(&clock, 1000);
+        assert!(!scheduler.is_overdue());
+
+        scheduler.received_sync();
+        at(&clock, 1104);
+        scheduler.received_sync();
+        assert_eq!(scheduler.last_jitter(), Some(ms(4)));
+        at(&clock, 1197);
+        scheduler.received_sync();
+        assert_eq!(scheduler.last_jitter(), Some(ms(7)));
+        assert_eq!(scheduler.max_jitter(), ms(7));
+        assert_eq!(scheduler.missed(), 0);
+
+        at(&clock, 1340);
+        assert!(!scheduler.is_overdue());
+        at(&clock, 1350);
+        assert!(scheduler.is_overdue());
+        at(&clock, 1500);
+        scheduler.received_sync();
+        assert_eq!(scheduler.missed(), 2);
+        assert_eq!(scheduler.last_jitter(), Some(ms(7)));
+
+        scheduler.reset_statistics();
+        assert_eq!((scheduler.count(), scheduler.missed(), scheduler.max_jitter()), (0, 0, ms(0)));
+    }
+}
@@ -1,30 +1,104 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::sync::{SyncProducer, SyncConsumer};
-    use std::sync::Arc;
+    use crate::primitives::can_frame::{CanFrame, CanFrameError};
+    use crate::primitives::network::Parent;
+    use crate::primitives::scheduler::{Duration as Ticks, SimulatedClock};
+    use crate::primitives::sync::{SyncProducer, SyncConsumer};
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
-    use std::thread;
-    
+    use std::vec;
+    use std::vec::Vec;
+
+    struct MockNetwork {
+        sent: Mutex<Vec<CanFrame>>,
+    }
+
+    impl MockNetwork {
+        fn new() -> Self {
+            MockNetwork { sent: Mutex::new(Vec::new()) }
+        }
+    }
+
+    impl Parent for MockNetwork {
+        fn received_frame(&self, _can_frame: CanFrame) {}
+
+        fn send(&self, can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            self.sent.lock().unwrap().push(can_frame.clone());
+            Ok(())
+        }
+    }
+
     #[test]
     fn test_sync_producer_send() {
         let producer = SyncProducer::new(Arc::new(MockNetwork::new())).unwrap();
         assert!(producer.send().is_ok());
     }
-    
+
     #[test]
     fn test_sync_consumer_receive() {
-        let consumer = SyncConsumer::new(Duration::from_secs(2));
+        let clock = Arc::new(SimulatedClock::new());
+        let consumer = SyncConsumer::with_clock(Duration::from_secs(2), clock.clone());
         consumer.receive_sync();
-        
+
         assert!(!consumer.check_timeout()); // Should not timeout immediately
+        clock.advance(Ticks::secs(2));
+        assert!(!consumer.check_timeout());
     }
-    
+
     #[test]
     fn test_sync_consumer_timeout() {
-        let consumer = SyncConsumer::new(Duration::from_millis(100));
-        thread::sleep(Duration::from_millis(200)); // Wait longer than timeout
-        
+        let clock = Arc::new(SimulatedClock::new());
+        let consumer = SyncConsumer::with_clock(Duration::from_millis(100), clock.clone());
+        consumer.receive_sync();
+        clock.advance(Ticks::millis(200)); // Wait longer than timeout
+
         assert!(consumer.check_timeout()); // Should detect timeout
     }
+
+    #[test]
+    fn test_sync_counter_overflows() {
+        let network = Arc::new(MockNetwork::new());
+        let mut producer = SyncProducer::new(network.clone()).unwrap();
+        producer.send().unwrap();
+        producer.set_counter(Some(3));
+        assert_eq!(producer.counter(), None);
+        for _ in 0..4 {
+            producer.send().unwrap();
+        }
+        assert_eq!(producer.counter(), Some(1));
+
+        let data: Vec<Vec<u8>> = network.sent.lock().unwrap().iter().map(|frame| frame.data().clone()).collect();
+        assert_eq!(data, [vec![], vec![1], vec![2], vec![3], vec![1]]);
+    }
+
+    #[test]
+    fn test_sync_consumer_detects_missed_syncs() {
+        let mut consumer = SyncConsumer::new(Duration::from_secs(1));
+        consumer.set_counter_overflow(Some(4));
+        assert_eq!(consumer.counter(), None);
+
+        consumer.receive_sync_frame(&[3]);
+        consumer.receive_sync_frame(&[4]);
+        consumer.receive_sync_frame(&[1]);
+        assert_eq!(consumer.missed(), 0);
+        // Syncs 2 and 3 were lost.
+        consumer.receive_sync_frame(&[4]);
+        assert_eq!(consumer.missed(), 2);
+        assert_eq!(consumer.counter(), Some(4));
+    }
+
+    #[test]
+    fn test_sync_consumer_window() {
+        let clock = Arc::new(SimulatedClock::new());
+        let mut consumer = SyncConsumer::with_clock(Duration::from_secs(1), clock.clone());
+        consumer.set_window(Some(Duration::from_millis(50)));
+        assert!(!consumer.is_in_window());
+
+        consumer.receive_sync_frame(&[]);
+        assert!(consumer.is_in_window());
+        clock.advance(Ticks::millis(50));
+        assert!(consumer.is_in_window());
+        clock.advance(Ticks::millis(1));
+        assert!(!consumer.is_in_window());
+    }
 }
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::scheduler::{Clock, Duration as Ticks, Instant, SimulatedClock};
+    use crate::services::ST17_test::{Packet, Parent, Reachability, TestServiceController};
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<(Vec<u8>, u32)>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+    }
+
+    /// Clock that can be set to any time, e.g. a wall clock corrected backwards.
+    struct SettableClock {
+        micros: AtomicU64,
+    }
+
+    impl Clock for SettableClock {
+        fn now(&self) -> Instant {
+            Instant::from_ticks(self.micros.load(Ordering::Relaxed))
+        }
+    }
+
+    fn controller() -> (Arc<RecordingParent>, TestServiceController) {
+        let parent = Arc::new(RecordingParent { sent: Mutex::new(Vec::new()) });
+        let controller = TestServiceController::new(parent.clone());
+        (parent, controller)
+    }
+
+    #[test]
+    fn test_connection_test_is_addressed() {
+        let (parent, controller) = controller();
+        controller.send_connection_test(5);
+        controller.send_application_connection_test(6, 42);
+
+        let sent = parent.sent.lock().unwrap();
+        assert_eq!(sent[0], (vec![17, 1], 5));
+        assert_eq!(sent[1], (vec![17, 3, 42], 6));
+    }
+
+    #[test]
+    fn test_report_measures_round_trip() {
+        let (_parent, controller) = controller();
+        controller.send_connection_test(5);
+        assert_eq!(controller.reachability(5, None), Some(Reachability::Pending));
+
+        controller.process(17, 2, vec![], 5);
+        assert!(matches!(controller.reachability(5, None), Some(Reachability::Reachable(_))));
+    }
+
+    #[test]
+    fn test_unanswered_test_times_out() {
+        let (_parent, mut controller) = controller();
+        let clock = Arc::new(SimulatedClock::new());
+        controller.set_clock(clock.clone());
+        controller.set_timeout(Duration::from_millis(10));
+        controller.send_application_connection_test(5, 1);
+        clock.advance(Ticks::millis(9));
+        assert!(controller.check_timeouts().is_empty());
+        clock.advance(Ticks::millis(1));
+
+        assert_eq!(controller.check_timeouts(), vec![(5, Some(1))]);
+        assert_eq!(controller.reachability(5, Some(1)), Some(Reachability::TimedOut));
+
+        // A late report does not revive a timed out test.
+        controller.process(17, 4, vec![1], 5);
+        assert_eq!(controller.reachability(5, Some(1)), Some(Reachability::TimedOut));
+    }
+
+    #[test]
+    fn test_ping_all_sweep() {
+        let (_parent, mut controller) = controller();
+        let clock = Arc::new(SimulatedClock::new());
+        controller.set_clock(clock.clone());
+        controller.set_timeout(Duration::from_millis(10));
+        controller.set_nodes(vec![3, 1, 2]);
+
+        // Nodes 1 and 3 answer, node 2 stays silent.
+        controller.ping_all();
+        clock.advance(Ticks::millis(2));
+        controller.process(17, 2, vec![], 1);
+        controller.process(17, 2, vec![], 3);
+        assert!(controller.check_timeouts().is_empty());
+        clock.advance(Ticks::millis(8));
+        assert_eq!(controller.check_timeouts(), vec![(2, None)]);
+
+        let table = controller.sweep_results();
+        let nodes: Vec<u32> = table.iter().map(|result| result.node_id).collect();
+        assert_eq!(nodes, vec![1, 2, 3]);
+        assert_eq!(table[0].reachability, Reachability::Reachable(Duration::from_millis(2)));
+        assert_eq!(table[1].reachability, Reachability::TimedOut);
+        assert_eq!(table[2].reachability, Reachability::Reachable(Duration::from_millis(2)));
+    }
+
+    #[test]
+    fn test_clock_set_back_does_not_panic() {
+        let (_parent, mut controller) = controller();
+        let clock = Arc::new(SettableClock { micros: AtomicU64::new(1_000_000) });
+        controller.set_clock(clock.clone());
+        controller.send_connection_test(5);
+        controller.send_connection_test(6);
+
+        clock.micros.store(0, Ordering::Relaxed);
+        assert!(controller.check_timeouts().is_empty());
+        controller.process(17, 2, vec![], 5);
+        assert_eq!(controller.reachability(5, None), Some(Reachability::Reachable(Duration::ZERO)));
+        assert_eq!(controller.reachability(6, None), Some(Reachability::Pending));
+    }
+}
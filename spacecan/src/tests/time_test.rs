@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::can_frame::CanFrame;
+    use crate::primitives::time::{decode_time_frame, scet_frame, utc_frame, CdsTime, CucTime, OnboardClock, TimeFrame};
+    use crate::services::ST09_time_management::{
+        Packet, Parent, TimeManagementServiceController, TimeManagementServiceResponder, TimeReport,
+        TIME_REPORTS_DISABLED,
+    };
+    use core::time::Duration;
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct RecordingParent {
+        sent: Mutex<Vec<(Vec<u8>, u32)>>,
+    }
+
+    impl Parent for RecordingParent {
+        fn send(&self, packet: Packet, node_id: u32) {
+            self.sent.lock().unwrap().push((packet.data, node_id));
+        }
+    }
+
+    fn parent() -> Arc<RecordingParent> {
+        Arc::new(RecordingParent { sent: Mutex::new(Vec::new()) })
+    }
+
+    #[test]
+    fn test_time_code_roundtrip() {
+        let cuc = CucTime::from_duration(Duration::new(1_000, 500_000_000));
+        assert_eq!(cuc, CucTime::new(1_000, 0x80_0000));
+        assert_eq!(cuc.to_bytes(), [0, 0, 0x03, 0xE8, 0x80, 0, 0]);
+        assert_eq!(CucTime::from_bytes(&cuc.to_bytes()), Some(cuc));
+        assert_eq!(cuc.to_duration(), Duration::new(1_000, 500_000_000));
+
+        let cds = CdsTime::new(2, 3_600_000, 250);
+        assert_eq!(cds.to_bytes(), [0, 2, 0, 0x36, 0xEE, 0x80, 0, 0xFA]);
+        assert_eq!(CdsTime::from_bytes(&cds.to_bytes()), Some(cds));
+        assert_eq!(CdsTime::from_duration(cds.to_duration()), cds);
+    }
+
+    #[test]
+    fn test_time_frames() {
+        let cuc = CucTime::new(0x0102_0304, 0x0A0B0C);
+        let frame = scet_frame(&cuc).unwrap();
+        assert_eq!(frame.can_id(), 0x180);
+        assert_eq!(frame.data()[..], [0x0A, 0x0B, 0x0C, 0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(decode_time_frame(&frame), Some(TimeFrame::Scet(cuc)));
+
+        let cds = CdsTime::new(7, 1_000, 5);
+        let frame = utc_frame(&cds).unwrap();
+        assert_eq!(frame.can_id(), 0x200);
+        assert_eq!(frame.data()[..], [0, 5, 0, 0, 0x03, 0xE8, 0, 7]);
+        assert_eq!(decode_time_frame(&frame), Some(TimeFrame::Utc(cds)));
+
+        let frame = CanFrame::new(0x080, None).unwrap();
+        assert_eq!(decode_time_frame(&frame), None);
+    }
+
+    #[test]
+    fn test_onboard_clock_discipline() {
+        let mut clock = OnboardClock::new();
+        assert!(!clock.is_synchronized());
+        assert_eq!(clock.time_at(Duration::from_secs(1)), None);
+
+        clock.discipline(CucTime::new(100, 0), Duration::from_secs(10));
+        assert_eq!(clock.time_at(Duration::from_secs(12)), Some(CucTime::new(102, 0)));
+
+        // The master runs 100 ppm fast: the clock trims its rate towards it.
+        clock.discipline(CucTime::from_duration(Duration::from_micros(110_001_000)), Duration::from_secs(20));
+        assert!(clock.rate() > 1.0);
+        assert!(clock.last_error().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_time_report_rate() {
+        let parent = parent();
+        let responder = TimeManagementServiceResponder::new(parent.clone());
+        assert_eq!(responder.rate_exponent(), TIME_REPORTS_DISABLED);
+        assert!(!responder.poll(Duration::from_secs(1)));
+
+        responder.process(9, 1, vec![2], 5);
+        assert_eq!(responder.rate_exponent(), 2);
+        // Not synchronized yet, nothing to report.
+        assert!(!responder.poll(Duration::from_secs(1)));
+
+        responder.received_scet(CucTime::new(500, 0), Duration::from_secs(1));
+        assert!(responder.poll(Duration::from_secs(2)));
+        assert!(!responder.poll(Duration::from_secs(5)));
+        assert!(responder.poll(Duration::from_secs(6)));
+
+        let sent = parent.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].0[..3], [9, 2, 2]);
+        assert_eq!(sent[0].1, 0);
+
+        let controller = TimeManagementServiceController::new(parent.clone());
+        controller.process(9, 2, sent[0].0[2..].to_vec(), 5);
+        assert_eq!(
+            controller.last_time_report(5),
+            Some(TimeReport::Cuc {
+                rate_exponent: 2,
+                time: CucTime::new(501, 0)
+            })
+        );
+    }
+}
@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod tests {
+    use crate::primitives::can_frame::{CanFrame, CanFrameError};
+    use crate::primitives::network::{Bus, BusSelection, Network, Parent};
+    use crate::primitives::redundancy::{RedundancyManager, SwitchPolicy};
+    use crate::primitives::scheduler::SimulatedClock;
+    use crate::primitives::transmit_queue::{OverflowPolicy, TransmitQueue};
+    use std::sync::{Arc, Mutex};
+    use std::vec;
+    use std::vec::Vec;
+
+    struct NullParent;
+
+    impl Parent for NullParent {
+        fn received_frame(&self, _can_frame: CanFrame) {}
+
+        fn send(&self, _can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingBus {
+        failing: Mutex<bool>,
+        sent: Mutex<Vec<CanFrame>>,
+    }
+
+    impl Bus for RecordingBus {
+        fn flush_frame_buffer(&self) {}
+
+        fn start_receive(&self) {}
+
+        fn stop_receive(&self) {}
+
+        fn send(&self, can_frame: &CanFrame) -> Result<(), CanFrameError> {
+            if *self.failing.lock().unwrap() {
+                return Err(CanFrameError::SendFailed);
+            }
+            self.sent.lock().unwrap().push(can_frame.clone());
+            Ok(())
+        }
+
+        fn get_frame(&self) -> Option<CanFrame> {
+            None
+        }
+    }
+
+    fn frame(can_id: u32, index: u8) -> CanFrame {
+        CanFrame::new(can_id, Some(vec![index])).unwrap()
+    }
+
+    fn ids(frames: impl IntoIterator<Item = CanFrame>) -> Vec<(u32, u8)> {
+        frames.into_iter().map(|frame| (frame.can_id(), frame.data()[0])).collect()
+    }
+
+    fn push(queue: &mut TransmitQueue, can_id: u32, index: u8) -> Result<Vec<(u32, u8)>, CanFrameError> {
+        queue.push(frame(can_id, index)).map(ids)
+    }
+
+    fn drain(queue: &mut TransmitQueue) -> Vec<(u32, u8)> {
+        ids(core::iter::from_fn(|| queue.pop()))
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let mut queue = TransmitQueue::new(8, OverflowPolicy::Reject);
+        queue.push_all(&[frame(0x305, 0), frame(0x305, 1), frame(0x305, 2)]).unwrap();
+        queue.push(frame(0x285, 0)).unwrap();
+        queue.push(frame(0x700, 0)).unwrap();
+        queue.push(frame(0x080, 0)).unwrap();
+        queue.push(frame(0x301, 0)).unwrap();
+        assert_eq!(
+            drain(&mut queue),
+            [(0x080, 0), (0x700, 0), (0x285, 0), (0x301, 0), (0x305, 0), (0x305, 1), (0x305, 2)]
+        );
+        assert_eq!(queue.report().high_water_mark, 7);
+    }
+
+    #[test]
+    fn test_overflow_policies() {
+        let mut queue = TransmitQueue::new(2, OverflowPolicy::Reject);
+        queue.push(frame(0x305, 0)).unwrap();
+        assert_eq!(queue.push_all(&[frame(0x305, 1), frame(0x305, 2)]).map(ids), Err(CanFrameError::QueueFull));
+        assert_eq!(queue.len(), 1);
+        queue.push(frame(0x305, 1)).unwrap();
+        assert_eq!(push(&mut queue, 0x080, 0), Err(CanFrameError::QueueFull));
+        assert_eq!(queue.report().rejected, 3);
+
+        queue.set_policy(OverflowPolicy::DropNewest);
+        assert_eq!(push(&mut queue, 0x080, 0), Ok(vec![(0x080, 0)]));
+
+        // A higher priority frame displaces the last packet of the lowest priority.
+        queue.set_policy(OverflowPolicy::DropLowestPriority);
+        assert_eq!(push(&mut queue, 0x080, 0), Ok(vec![(0x305, 1)]));
+        assert_eq!(push(&mut queue, 0x305, 2), Ok(vec![(0x305, 2)]));
+        let report = queue.report();
+        assert_eq!((report.dropped, report.last_dropped), (3, Some(0x305)));
+        assert_eq!(drain(&mut queue), [(0x080, 0), (0x305, 0)]);
+    }
+
+    #[test]
+    fn test_packets_are_dropped_whole() {
+        let mut queue = TransmitQueue::new(4, OverflowPolicy::DropNewest);
+        let packet = [frame(0x305, 0), frame(0x305, 1), frame(0x305, 2)];
+        queue.push_all(&packet).unwrap();
+        let urgent = [frame(0x285, 0), frame(0x285, 1)];
+        assert_eq!(queue.push_all(&urgent).map(ids), Ok(vec![(0x285, 0), (0x285, 1)]));
+        queue.push(frame(0x306, 0)).unwrap();
+
+        // Lower priority packets make room as a whole, lowest priority first.
+        queue.set_policy(OverflowPolicy::DropLowestPriority);
+        assert_eq!(push(&mut queue, 0x285, 0), Ok(vec![(0x306, 0)]));
+        assert_eq!(push(&mut queue, 0x285, 1), Ok(vec![(0x305, 0), (0x305, 1), (0x305, 2)]));
+        queue.push_all(&urgent).unwrap();
+        // Nothing queued has a lower priority, so the packet is dropped.
+        assert_eq!(push(&mut queue, 0x306, 1), Ok(vec![(0x306, 1)]));
+
+        let report = queue.report();
+        assert_eq!((report.dropped, report.last_dropped), (7, Some(0x306)));
+        assert_eq!(drain(&mut queue), [(0x285, 0), (0x285, 1), (0x285, 0), (0x285, 1)]);
+
+        // A packet whose first frame was sent is not discarded.
+        queue.push_all(&packet).unwrap();
+        queue.push(frame(0x306, 0)).unwrap();
+        assert_eq!(ids(queue.pop()), [(0x305, 0)]);
+        queue.push(frame(0x285, 0)).unwrap();
+        assert_eq!(push(&mut queue, 0x285, 1), Ok(vec![(0x306, 0)]));
+        assert_eq!(push(&mut queue, 0x285, 2), Ok(vec![(0x285, 2)]));
+        assert_eq!(drain(&mut queue), [(0x285, 0), (0x285, 1), (0x305, 1), (0x305, 2)]);
+    }
+
+    #[test]
+    fn test_network_retains_unsent_frames() {
+        let network = Network::with_redundancy(
+            Arc::new(NullParent),
+            0,
+            RecordingBus::default(),
+            RecordingBus::default(),
+            RedundancyManager::new(SwitchPolicy::ErrorThreshold { max_errors: 2 }),
+            Arc::new(SimulatedClock::new()),
+        );
+        network.set_transmit_queue(4, OverflowPolicy::Reject);
+
+        *network.bus(BusSelection::A).failing.lock().unwrap() = true;
+        let packet = [frame(0x305, 0), frame(0x305, 1), frame(0x305, 2)];
+        // The packet is queued even though the bus refuses it.
+        assert_eq!(network.send_all(&packet), Ok(()));
+        assert_eq!(network.queued_frames(), 3);
+        assert!(network.is_transmit_stalled());
+        assert_eq!(network.send_all(&packet), Err(CanFrameError::QueueFull));
+        assert_eq!(network.overflow_report().rejected, 3);
+
+        // Retries of the stalled queue count as one failure.
+        for _ in 0..3 {
+            assert_eq!(network.transmit(), Err(CanFrameError::SendFailed));
+        }
+        assert_eq!(network.bus_health(BusSelection::A).tx_failures, 1);
+        assert_eq!(network.selected_bus(), BusSelection::A);
+
+        // A bus error reaches the threshold; bus B sends the retained frames after the heartbeat.
+        assert!(network.bus_error(BusSelection::A).is_some());
+        assert!(!network.is_transmit_stalled());
+        network.send(&frame(0x700, 0)).unwrap();
+        let sent: Vec<(u32, u8)> = network
+            .bus(BusSelection::B)
+            .sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|frame| (frame.can_id(), frame.data()[0]))
+            .collect();
+        assert_eq!(sent, [(0x700, 0), (0x305, 0), (0x305, 1), (0x305, 2)]);
+        assert_eq!(network.queued_frames(), 0);
+    }
+}
@@ -1,15 +1,13 @@
 use crate::primitives::can_frame::{CanFrame, CanFrameError};
 extern crate alloc;
 
-use alloc::sync::Arc;
-use cortex_m::interrupt::{Mutex, free as interrupt_free};
-use alloc::vec::Vec;
-use core::option::Option;
-use core::option::Option::{Some, None};
-use core::result::Result;
-use core::result::Result::{Ok, Err};
-use core::cell::UnsafeCell;
-use core::borrow::Borrow;
+use alloc::collections::VecDeque;
+#[cfg(not(feature = "std"))]
+use core::cell::RefCell;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+#[cfg(not(feature = "std"))]
+use cortex_m::interrupt::Mutex;
 
 // Define a trait for Bus operations
 pub trait Bus {
@@ -20,25 +18,44 @@ pub trait Bus {
     fn get_frame(&self) -> Option<CanFrame>;
 }
 
-// Implementation of a basic Bus
+// Implementation of a basic Bus; frames are received in the order they were sent
 pub struct BusImpl {
-    buffer: UnsafeCell<Vec<CanFrame>>
+    #[cfg(feature = "std")]
+    buffer: Mutex<VecDeque<CanFrame>>,
+    #[cfg(not(feature = "std"))]
+    buffer: Mutex<RefCell<VecDeque<CanFrame>>>,
+}
+
+impl Default for BusImpl {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BusImpl {
     pub fn new() -> Self {
         BusImpl {
-            buffer: UnsafeCell::new(Vec::new()),
+            #[cfg(feature = "std")]
+            buffer: Mutex::new(VecDeque::new()),
+            #[cfg(not(feature = "std"))]
+            buffer: Mutex::new(RefCell::new(VecDeque::new())),
         }
     }
+
+    #[cfg(feature = "std")]
+    fn with_buffer<R>(&self, f: impl FnOnce(&mut VecDeque<CanFrame>) -> R) -> R {
+        f(&mut self.buffer.lock().unwrap())
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_buffer<R>(&self, f: impl FnOnce(&mut VecDeque<CanFrame>) -> R) -> R {
+        cortex_m::interrupt::free(|cs| f(&mut self.buffer.borrow(cs).borrow_mut()))
+    }
 }
 
 impl Bus for BusImpl {
     fn flush_frame_buffer(&self) {
-        interrupt_free(|_cs| {
-            let buffer = unsafe { &mut *self.buffer.get() }; // Use UnsafeCell::get
-            buffer.clear();
-        });
+        self.with_buffer(|buffer| buffer.clear());
     }
     
     fn start_receive(&self) {
@@ -50,19 +67,11 @@ impl Bus for BusImpl {
     }
     
     fn send(&self, can_frame: &CanFrame) -> Result<(), CanFrameError> {
-        interrupt_free(|_cs| {
-            let buffer = unsafe { &mut *self.buffer.get() }; // Use UnsafeCell::get
-            buffer.push(can_frame.clone());
-        });
+        self.with_buffer(|buffer| buffer.push_back(can_frame.clone()));
         Ok(())
     }
     
     fn get_frame(&self) -> Option<CanFrame> {
-        let mut frame = None;
-        interrupt_free(|_cs| {
-            let buffer = unsafe { &mut *self.buffer.get() }; // Use UnsafeCell::get
-            frame = buffer.pop();
-        });
-        frame
+        self.with_buffer(|buffer| buffer.pop_front())
     }
 }